@@ -76,11 +76,13 @@ fn main() {
                 _ if event.id == save_item.id() => {
                     println!("Save menu item activated!");
                     counter += 1;
-                    save_item.set_label(format!("&Save activated {counter} times"));
+                    save_item
+                        .set_label(format!("&Save activated {counter} times"))
+                        .unwrap();
 
                     if !open_item_disabled {
                         println!("Open item disabled!");
-                        open_item.set_enabled(false);
+                        open_item.set_enabled(false).unwrap();
                         open_item_disabled = true;
                     }
                 }
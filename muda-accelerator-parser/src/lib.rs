@@ -0,0 +1,759 @@
+//! The accelerator string parser shared by [muda](https://docs.rs/muda) and
+//! [muda-macros](https://docs.rs/muda-macros).
+//!
+//! It only depends on [`keyboard_types`], so `muda-macros` can parse accelerators at compile time
+//! without building the platform dependencies of `muda` for the host. Use `muda::accelerator` instead
+//! of depending on this crate directly.
+
+use std::{fmt, ops::Range, str::FromStr};
+
+pub use keyboard_types::{Code, Key, Modifiers};
+
+/// Every [`Code`] that can be used in an accelerator, that is all of them except [`Code::Unidentified`].
+pub const ALL_CODES: &[Code] = &[
+    Code::Backquote,
+    Code::Backslash,
+    Code::BracketLeft,
+    Code::BracketRight,
+    Code::Comma,
+    Code::Digit0,
+    Code::Digit1,
+    Code::Digit2,
+    Code::Digit3,
+    Code::Digit4,
+    Code::Digit5,
+    Code::Digit6,
+    Code::Digit7,
+    Code::Digit8,
+    Code::Digit9,
+    Code::Equal,
+    Code::IntlBackslash,
+    Code::IntlRo,
+    Code::IntlYen,
+    Code::KeyA,
+    Code::KeyB,
+    Code::KeyC,
+    Code::KeyD,
+    Code::KeyE,
+    Code::KeyF,
+    Code::KeyG,
+    Code::KeyH,
+    Code::KeyI,
+    Code::KeyJ,
+    Code::KeyK,
+    Code::KeyL,
+    Code::KeyM,
+    Code::KeyN,
+    Code::KeyO,
+    Code::KeyP,
+    Code::KeyQ,
+    Code::KeyR,
+    Code::KeyS,
+    Code::KeyT,
+    Code::KeyU,
+    Code::KeyV,
+    Code::KeyW,
+    Code::KeyX,
+    Code::KeyY,
+    Code::KeyZ,
+    Code::Minus,
+    Code::Period,
+    Code::Quote,
+    Code::Semicolon,
+    Code::Slash,
+    Code::AltLeft,
+    Code::AltRight,
+    Code::Backspace,
+    Code::CapsLock,
+    Code::ContextMenu,
+    Code::ControlLeft,
+    Code::ControlRight,
+    Code::Enter,
+    Code::MetaLeft,
+    Code::MetaRight,
+    Code::ShiftLeft,
+    Code::ShiftRight,
+    Code::Space,
+    Code::Tab,
+    Code::Convert,
+    Code::KanaMode,
+    Code::Lang1,
+    Code::Lang2,
+    Code::Lang3,
+    Code::Lang4,
+    Code::Lang5,
+    Code::NonConvert,
+    Code::Delete,
+    Code::End,
+    Code::Help,
+    Code::Home,
+    Code::Insert,
+    Code::PageDown,
+    Code::PageUp,
+    Code::ArrowDown,
+    Code::ArrowLeft,
+    Code::ArrowRight,
+    Code::ArrowUp,
+    Code::NumLock,
+    Code::Numpad0,
+    Code::Numpad1,
+    Code::Numpad2,
+    Code::Numpad3,
+    Code::Numpad4,
+    Code::Numpad5,
+    Code::Numpad6,
+    Code::Numpad7,
+    Code::Numpad8,
+    Code::Numpad9,
+    Code::NumpadAdd,
+    Code::NumpadBackspace,
+    Code::NumpadClear,
+    Code::NumpadClearEntry,
+    Code::NumpadComma,
+    Code::NumpadDecimal,
+    Code::NumpadDivide,
+    Code::NumpadEnter,
+    Code::NumpadEqual,
+    Code::NumpadHash,
+    Code::NumpadMemoryAdd,
+    Code::NumpadMemoryClear,
+    Code::NumpadMemoryRecall,
+    Code::NumpadMemoryStore,
+    Code::NumpadMemorySubtract,
+    Code::NumpadMultiply,
+    Code::NumpadParenLeft,
+    Code::NumpadParenRight,
+    Code::NumpadStar,
+    Code::NumpadSubtract,
+    Code::Escape,
+    Code::F1,
+    Code::F2,
+    Code::F3,
+    Code::F4,
+    Code::F5,
+    Code::F6,
+    Code::F7,
+    Code::F8,
+    Code::F9,
+    Code::F10,
+    Code::F11,
+    Code::F12,
+    Code::Fn,
+    Code::FnLock,
+    Code::PrintScreen,
+    Code::ScrollLock,
+    Code::Pause,
+    Code::BrowserBack,
+    Code::BrowserFavorites,
+    Code::BrowserForward,
+    Code::BrowserHome,
+    Code::BrowserRefresh,
+    Code::BrowserSearch,
+    Code::BrowserStop,
+    Code::Eject,
+    Code::LaunchApp1,
+    Code::LaunchApp2,
+    Code::LaunchMail,
+    Code::MediaPlayPause,
+    Code::MediaSelect,
+    Code::MediaStop,
+    Code::MediaTrackNext,
+    Code::MediaTrackPrevious,
+    Code::Power,
+    Code::Sleep,
+    Code::AudioVolumeDown,
+    Code::AudioVolumeMute,
+    Code::AudioVolumeUp,
+    Code::WakeUp,
+    Code::Hyper,
+    Code::Super,
+    Code::Turbo,
+    Code::Abort,
+    Code::Resume,
+    Code::Suspend,
+    Code::Again,
+    Code::Copy,
+    Code::Cut,
+    Code::Find,
+    Code::Open,
+    Code::Paste,
+    Code::Props,
+    Code::Select,
+    Code::Undo,
+    Code::Hiragana,
+    Code::Katakana,
+    Code::F13,
+    Code::F14,
+    Code::F15,
+    Code::F16,
+    Code::F17,
+    Code::F18,
+    Code::F19,
+    Code::F20,
+    Code::F21,
+    Code::F22,
+    Code::F23,
+    Code::F24,
+    Code::BrightnessDown,
+    Code::BrightnessUp,
+    Code::DisplayToggleIntExt,
+    Code::KeyboardLayoutSelect,
+    Code::LaunchAssistant,
+    Code::LaunchControlPanel,
+    Code::LaunchScreenSaver,
+    Code::MailForward,
+    Code::MailReply,
+    Code::MailSend,
+    Code::MediaFastForward,
+    Code::MediaPause,
+    Code::MediaPlay,
+    Code::MediaRecord,
+    Code::MediaRewind,
+    Code::MicrophoneMuteToggle,
+    Code::PrivacyScreenToggle,
+    Code::SelectTask,
+    Code::ShowAllWindows,
+    Code::ZoomToggle,
+];
+
+/// Describes why an accelerator or chord string couldn't be parsed.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AcceleratorParseErrorKind {
+    /// A token between two `+` or a whole chord is empty, for example `Ctrl++KeyS` or `Ctrl+`.
+    EmptyToken,
+    /// A token isn't a modifier, a [`Code`] name, a key alias or a quoted [`Key`].
+    UnknownKey,
+    /// A key follows the main key, for example `Ctrl+KeyS+KeyA`.
+    MultipleKeys,
+    /// A modifier follows the main key, for example `KeyS+Ctrl`.
+    ModifierAfterKey,
+    /// The accelerator only has modifiers, for example `Ctrl+Shift`.
+    MissingKey,
+}
+
+/// An error returned when parsing an accelerator or a chord from a string fails.
+///
+/// The error carries the byte span of the offending token in the parsed string,
+/// which can be used to highlight it.
+///
+/// ```
+/// # use muda_accelerator_parser::{parse_accelerator, AcceleratorParseErrorKind};
+/// #
+/// let error = parse_accelerator("Ctrl+Shft+KeyS").unwrap_err();
+/// assert_eq!(error.kind(), AcceleratorParseErrorKind::UnknownKey);
+/// assert_eq!(error.span(), 5..9);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AcceleratorParseError {
+    kind: AcceleratorParseErrorKind,
+    span: Range<usize>,
+}
+
+impl AcceleratorParseError {
+    fn new(kind: AcceleratorParseErrorKind, span: Range<usize>) -> Self {
+        Self { kind, span }
+    }
+
+    /// Returns the kind of this error.
+    pub fn kind(&self) -> AcceleratorParseErrorKind {
+        self.kind
+    }
+
+    /// Returns the byte span of the offending token in the parsed string,
+    /// the span is empty for [`AcceleratorParseErrorKind::EmptyToken`].
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    fn offset(mut self, offset: usize) -> Self {
+        self.span = self.span.start + offset..self.span.end + offset;
+        self
+    }
+}
+
+impl fmt::Display for AcceleratorParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self.kind {
+            AcceleratorParseErrorKind::EmptyToken => "Unexpected empty token",
+            AcceleratorParseErrorKind::UnknownKey => "Couldn't identify token as a valid key",
+            AcceleratorParseErrorKind::MultipleKeys => "Only one main key is allowed",
+            AcceleratorParseErrorKind::ModifierAfterKey => {
+                "Modifiers have to be listed before the main key"
+            }
+            AcceleratorParseErrorKind::MissingKey => "Couldn't find a main key",
+        };
+        write!(
+            f,
+            "[AcceleratorParseError]: {} at {}..{}",
+            description, self.span.start, self.span.end
+        )
+    }
+}
+
+impl std::error::Error for AcceleratorParseError {}
+
+/// The key of a parsed accelerator.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ParsedKey {
+    /// A physical key, written using its [`Code`] name or an alias, like `KeyS` or `S`.
+    Physical(Code),
+    /// A logical key, written in double quotes, like `"s"` or `"Enter"`.
+    Logical(Key),
+}
+
+/// An accelerator as it was written, see [`parse_accelerator`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParsedAccelerator {
+    /// The modifiers of the accelerator, including the ones restricted to one side
+    /// but not `CmdOrCtrl`, which is reported by [`ParsedAccelerator::cmd_or_ctrl`].
+    pub mods: Modifiers,
+    /// The modifiers restricted to their left key, like `LeftCtrl`.
+    pub left: Modifiers,
+    /// The modifiers restricted to their right key, like `RightCtrl`.
+    pub right: Modifiers,
+    /// Whether the accelerator uses `CmdOrCtrl`, which is left to the caller
+    /// since it depends on the target platform.
+    pub cmd_or_ctrl: bool,
+    /// The main key of the accelerator.
+    pub key: ParsedKey,
+}
+
+/// Parses an accelerator, modifiers are separated by `+` and have to be listed before the main key.
+///
+/// ```
+/// # use muda_accelerator_parser::{parse_accelerator, Code, Modifiers, ParsedKey};
+/// #
+/// let parsed = parse_accelerator("CmdOrCtrl+RightShift+S").unwrap();
+/// assert_eq!(parsed.mods, Modifiers::SHIFT);
+/// assert_eq!(parsed.right, Modifiers::SHIFT);
+/// assert!(parsed.cmd_or_ctrl);
+/// assert_eq!(parsed.key, ParsedKey::Physical(Code::KeyS));
+/// ```
+pub fn parse_accelerator(
+    accelerator_string: &str,
+) -> Result<ParsedAccelerator, AcceleratorParseError> {
+    let mut mods = Modifiers::empty();
+    let mut left = Modifiers::empty();
+    let mut right = Modifiers::empty();
+    let mut cmd_or_ctrl = false;
+    let mut key = None;
+
+    let tokens = split_unquoted(accelerator_string, |c| c == '+');
+    for (i, (start, raw)) in tokens.iter().enumerate() {
+        let token = raw.trim();
+        let start = start + (raw.len() - raw.trim_start().len());
+        let span = start..start + token.len();
+        if token.is_empty() {
+            return Err(AcceleratorParseError::new(
+                AcceleratorParseErrorKind::EmptyToken,
+                span,
+            ));
+        }
+
+        if key.is_some() {
+            // at this point we already parsed the modifiers and found a main key but
+            // the function received more then one main key or it is not in the right order
+            // examples:
+            // 1. "Ctrl+Shift+C+A" => only one main key should be allowd.
+            // 2. "Ctrl+C+Shift" => wrong order
+            let kind = if parse_modifier(token).is_some() {
+                AcceleratorParseErrorKind::ModifierAfterKey
+            } else {
+                AcceleratorParseErrorKind::MultipleKeys
+            };
+            return Err(AcceleratorParseError::new(kind, span));
+        }
+
+        // The last token is always the main key, this allows keys that share
+        // their name with a modifier, like `Code::Super`, to be parsed.
+        let is_last = i == tokens.len() - 1;
+        if is_last {
+            if let Some(code) = parse_code_name(token) {
+                key = Some(ParsedKey::Physical(code));
+                continue;
+            }
+        }
+
+        match parse_modifier(token) {
+            Some(Modifier::Key(modifier, side)) => {
+                mods.insert(modifier);
+                match side {
+                    Some(Side::Left) => left.insert(modifier),
+                    Some(Side::Right) => right.insert(modifier),
+                    None => {}
+                }
+            }
+            Some(Modifier::CmdOrCtrl) => cmd_or_ctrl = true,
+            None => match parse_key(token) {
+                Some((parsed, shift)) => {
+                    mods.insert(shift);
+                    key = Some(parsed);
+                }
+                None => {
+                    return Err(AcceleratorParseError::new(
+                        AcceleratorParseErrorKind::UnknownKey,
+                        span,
+                    ))
+                }
+            },
+        }
+    }
+
+    match key {
+        Some(key) => Ok(ParsedAccelerator {
+            mods,
+            left,
+            right,
+            cmd_or_ctrl,
+            key,
+        }),
+        None => {
+            let trimmed = accelerator_string.trim_start();
+            let start = accelerator_string.len() - trimmed.len();
+            Err(AcceleratorParseError::new(
+                AcceleratorParseErrorKind::MissingKey,
+                start..start + trimmed.trim_end().len(),
+            ))
+        }
+    }
+}
+
+/// Parses a chord, a sequence of accelerators separated by whitespace, see [`parse_accelerator`].
+///
+/// Whitespace around a `+` belongs to the accelerator itself, so `Ctrl + KeyK Ctrl + KeyC` has two strokes.
+pub fn parse_chord(chord_string: &str) -> Result<Vec<ParsedAccelerator>, AcceleratorParseError> {
+    let mut strokes: Vec<(usize, usize)> = Vec::new();
+    for (start, word) in split_unquoted(chord_string, char::is_whitespace) {
+        if word.is_empty() {
+            continue;
+        }
+
+        let end = start + word.len();
+        match strokes.last_mut() {
+            Some(stroke)
+                if chord_string[stroke.0..stroke.1].ends_with('+') || word.starts_with('+') =>
+            {
+                stroke.1 = end
+            }
+            _ => strokes.push((start, end)),
+        }
+    }
+
+    if strokes.is_empty() {
+        return Err(AcceleratorParseError::new(
+            AcceleratorParseErrorKind::EmptyToken,
+            0..0,
+        ));
+    }
+
+    strokes
+        .iter()
+        .map(|(start, end)| {
+            parse_accelerator(&chord_string[*start..*end]).map_err(|e| e.offset(*start))
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy)]
+enum Side {
+    Left,
+    Right,
+}
+
+/// A modifier token, see [`parse_modifier`].
+enum Modifier {
+    /// A modifier and the side of the keyboard it is restricted to, if any.
+    Key(Modifiers, Option<Side>),
+    /// `CmdOrCtrl`, which depends on the target platform.
+    CmdOrCtrl,
+}
+
+/// Parses a modifier and the side of the keyboard it is restricted to, if any,
+/// sides are written as a `Left`/`L` or `Right`/`R` prefix, for example `RightCtrl` or `LShift`.
+fn parse_modifier(token: &str) -> Option<Modifier> {
+    let token = token.to_uppercase();
+    let sided = |name: &str| {
+        Some(match name {
+            "OPTION" | "ALT" => Modifiers::ALT,
+            "CONTROL" | "CTRL" => Modifiers::CONTROL,
+            "COMMAND" | "CMD" | "SUPER" => Modifiers::SUPER,
+            "SHIFT" => Modifiers::SHIFT,
+            _ => return None,
+        })
+    };
+    if let Some(modifier) = sided(&token) {
+        return Some(Modifier::Key(modifier, None));
+    }
+    for (prefixes, side) in [(["LEFT", "L"], Side::Left), (["RIGHT", "R"], Side::Right)] {
+        for prefix in prefixes {
+            if let Some(modifier) = token.strip_prefix(prefix).and_then(sided) {
+                return Some(Modifier::Key(modifier, Some(side)));
+            }
+        }
+    }
+
+    Some(match token.as_str() {
+        "ALTGR" | "ALTGRAPH" => Modifier::Key(Modifiers::ALT_GRAPH, None),
+        "HYPER" => Modifier::Key(Modifiers::HYPER, None),
+        "COMMANDORCONTROL" | "COMMANDORCTRL" | "CMDORCTRL" | "CMDORCONTROL" => Modifier::CmdOrCtrl,
+        _ => return None,
+    })
+}
+
+/// Splits `string` at every char matching `is_separator` that isn't part of a quoted logical key
+/// and returns each piece along with its byte offset in `string`.
+///
+/// A quote only starts a logical key at the start of `string` or after a `+` or whitespace,
+/// and the first char after it is always part of the key, so `"+"` and `"""` are valid keys.
+fn split_unquoted(string: &str, is_separator: impl Fn(char) -> bool) -> Vec<(usize, &str)> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut prev: Option<char> = None;
+    let mut chars = string.char_indices();
+    while let Some((i, c)) = chars.next() {
+        let starts_key = match prev {
+            Some(prev) => prev == '+' || prev.is_whitespace(),
+            None => true,
+        };
+        if c == '"' && starts_key {
+            chars.next();
+            for (_, c) in chars.by_ref() {
+                if c == '"' {
+                    break;
+                }
+            }
+            prev = Some('"');
+            continue;
+        }
+
+        if is_separator(c) {
+            pieces.push((start, &string[start..i]));
+            start = i + c.len_utf8();
+        }
+        prev = Some(c);
+    }
+    pieces.push((start, &string[start..]));
+    pieces
+}
+
+/// Parses a key token, in order of precedence, as a quoted logical [`Key`], as a shifted symbol,
+/// as a [`Code`] name, see [`parse_code_name`], or as an alias from [`parse_key_alias`].
+/// Also returns [`Modifiers::SHIFT`] for shifted symbols, see [`parse_shifted_symbol`].
+fn parse_key(token: &str) -> Option<(ParsedKey, Modifiers)> {
+    // Logical keys are quoted, like `"z"` or `"Enter"`.
+    if let Some(name) = token
+        .strip_prefix('"')
+        .and_then(|token| token.strip_suffix('"'))
+        .filter(|name| !name.is_empty())
+    {
+        return match Key::from_str(name) {
+            Ok(Key::Unidentified) | Err(_) => None,
+            Ok(key) => Some((ParsedKey::Logical(key), Modifiers::empty())),
+        };
+    }
+
+    if let Some(code) = parse_shifted_symbol(token) {
+        return Some((ParsedKey::Physical(code), Modifiers::SHIFT));
+    }
+
+    parse_code_name(token)
+        .or_else(|| parse_key_alias(token))
+        .map(|code| (ParsedKey::Physical(code), Modifiers::empty()))
+}
+
+/// Parses a [`Code`] name, ignoring case, like `KeyS` or `arrowup`.
+fn parse_code_name(token: &str) -> Option<Code> {
+    match Code::from_str(token) {
+        Ok(Code::Unidentified) => None,
+        Ok(code) => Some(code),
+        Err(_) => ALL_CODES
+            .iter()
+            .find(|code| code.to_string().eq_ignore_ascii_case(token))
+            .copied(),
+    }
+}
+
+/// Maps the symbols typed with shift on a US layout to the key typing them, for example `?` to [`Code::Slash`].
+fn parse_shifted_symbol(token: &str) -> Option<Code> {
+    let mut chars = token.chars();
+    let c = match (chars.next(), chars.next()) {
+        (Some(c), None) => c,
+        _ => return None,
+    };
+    Some(match c {
+        ')' => Code::Digit0,
+        '!' => Code::Digit1,
+        '@' => Code::Digit2,
+        '#' => Code::Digit3,
+        '$' => Code::Digit4,
+        '%' => Code::Digit5,
+        '^' => Code::Digit6,
+        '&' => Code::Digit7,
+        '*' => Code::Digit8,
+        '(' => Code::Digit9,
+        '_' => Code::Minus,
+        '{' => Code::BracketLeft,
+        '}' => Code::BracketRight,
+        ':' => Code::Semicolon,
+        '"' => Code::Quote,
+        '<' => Code::Comma,
+        '>' => Code::Period,
+        '?' => Code::Slash,
+        '|' => Code::Backslash,
+        '~' => Code::Backquote,
+        _ => return None,
+    })
+}
+
+/// Maps single characters, common abbreviations and the key names
+/// used by Electron and Tauri accelerators to a [`Code`].
+fn parse_key_alias(token: &str) -> Option<Code> {
+    let mut chars = token.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(match c.to_ascii_uppercase() {
+            'A' => Code::KeyA,
+            'B' => Code::KeyB,
+            'C' => Code::KeyC,
+            'D' => Code::KeyD,
+            'E' => Code::KeyE,
+            'F' => Code::KeyF,
+            'G' => Code::KeyG,
+            'H' => Code::KeyH,
+            'I' => Code::KeyI,
+            'J' => Code::KeyJ,
+            'K' => Code::KeyK,
+            'L' => Code::KeyL,
+            'M' => Code::KeyM,
+            'N' => Code::KeyN,
+            'O' => Code::KeyO,
+            'P' => Code::KeyP,
+            'Q' => Code::KeyQ,
+            'R' => Code::KeyR,
+            'S' => Code::KeyS,
+            'T' => Code::KeyT,
+            'U' => Code::KeyU,
+            'V' => Code::KeyV,
+            'W' => Code::KeyW,
+            'X' => Code::KeyX,
+            'Y' => Code::KeyY,
+            'Z' => Code::KeyZ,
+            '0' => Code::Digit0,
+            '1' => Code::Digit1,
+            '2' => Code::Digit2,
+            '3' => Code::Digit3,
+            '4' => Code::Digit4,
+            '5' => Code::Digit5,
+            '6' => Code::Digit6,
+            '7' => Code::Digit7,
+            '8' => Code::Digit8,
+            '9' => Code::Digit9,
+            '-' => Code::Minus,
+            '=' => Code::Equal,
+            '[' => Code::BracketLeft,
+            ']' => Code::BracketRight,
+            ';' => Code::Semicolon,
+            '\'' => Code::Quote,
+            ',' => Code::Comma,
+            '.' => Code::Period,
+            '/' => Code::Slash,
+            '\\' => Code::Backslash,
+            '`' => Code::Backquote,
+            _ => return None,
+        });
+    }
+
+    Some(match token.to_uppercase().as_str() {
+        "ESC" => Code::Escape,
+        "RETURN" => Code::Enter,
+        "SPACEBAR" => Code::Space,
+        "BKSP" => Code::Backspace,
+        "DEL" => Code::Delete,
+        "INS" => Code::Insert,
+        "PGUP" => Code::PageUp,
+        "PGDN" | "PGDOWN" => Code::PageDown,
+        "UP" => Code::ArrowUp,
+        "DOWN" => Code::ArrowDown,
+        "LEFT" => Code::ArrowLeft,
+        "RIGHT" => Code::ArrowRight,
+        // `+` can't be used since it separates the tokens and on most layouts it shares its key with `=`.
+        "PLUS" => Code::Equal,
+        "CAPS" => Code::CapsLock,
+        "NUMLK" => Code::NumLock,
+        "SCRLK" => Code::ScrollLock,
+        "PRTSC" | "PRTSCR" | "PRINTSCR" | "PRINT" => Code::PrintScreen,
+        "BREAK" => Code::Pause,
+        "MENU" | "APPS" => Code::ContextMenu,
+        "NUM0" | "NUMPAD0" => Code::Numpad0,
+        "NUM1" | "NUMPAD1" => Code::Numpad1,
+        "NUM2" | "NUMPAD2" => Code::Numpad2,
+        "NUM3" | "NUMPAD3" => Code::Numpad3,
+        "NUM4" | "NUMPAD4" => Code::Numpad4,
+        "NUM5" | "NUMPAD5" => Code::Numpad5,
+        "NUM6" | "NUMPAD6" => Code::Numpad6,
+        "NUM7" | "NUMPAD7" => Code::Numpad7,
+        "NUM8" | "NUMPAD8" => Code::Numpad8,
+        "NUM9" | "NUMPAD9" => Code::Numpad9,
+        "NUMADD" | "NUMPLUS" => Code::NumpadAdd,
+        "NUMSUB" | "NUMMINUS" => Code::NumpadSubtract,
+        "NUMMULT" | "NUMMULTIPLY" => Code::NumpadMultiply,
+        "NUMDIV" | "NUMDIVIDE" => Code::NumpadDivide,
+        "NUMDEC" | "NUMDECIMAL" => Code::NumpadDecimal,
+        "NUMENTER" => Code::NumpadEnter,
+        "VOLUMEUP" | "VOLUP" => Code::AudioVolumeUp,
+        "VOLUMEDOWN" | "VOLDOWN" => Code::AudioVolumeDown,
+        "VOLUMEMUTE" | "MUTE" => Code::AudioVolumeMute,
+        "MEDIANEXTTRACK" => Code::MediaTrackNext,
+        "MEDIAPREVIOUSTRACK" | "MEDIAPREVTRACK" => Code::MediaTrackPrevious,
+        "MEDIAPLAY" | "MEDIAPAUSE" => Code::MediaPlayPause,
+        _ => return None,
+    })
+}
+
+#[test]
+fn test_parse_cmd_or_ctrl() {
+    let parsed = parse_accelerator("CmdOrCtrl+Shift+KeyS").unwrap();
+    assert!(parsed.cmd_or_ctrl);
+    assert_eq!(parsed.mods, Modifiers::SHIFT);
+
+    let parsed = parse_accelerator("CmdOrCtrl+Ctrl+KeyS").unwrap();
+    assert!(parsed.cmd_or_ctrl);
+    assert_eq!(parsed.mods, Modifiers::CONTROL);
+
+    let parsed = parse_accelerator("RightAlt+\"z\"").unwrap();
+    assert!(!parsed.cmd_or_ctrl);
+    assert_eq!(parsed.mods, Modifiers::ALT);
+    assert_eq!(parsed.right, Modifiers::ALT);
+    assert_eq!(parsed.key, ParsedKey::Logical(Key::Character("z".into())));
+
+    assert_eq!(
+        parse_accelerator("CmdOrCtrl+Shft+KeyS").unwrap_err().span(),
+        10..14
+    );
+}
+
+#[test]
+fn test_parse_shifted_symbols() {
+    for (symbol, code) in [
+        ("?", Code::Slash),
+        ("{", Code::BracketLeft),
+        ("@", Code::Digit2),
+    ] {
+        let parsed = parse_accelerator(&format!("Ctrl+{}", symbol)).unwrap();
+        assert_eq!(parsed.mods, Modifiers::CONTROL | Modifiers::SHIFT);
+        assert_eq!(parsed.key, ParsedKey::Physical(code));
+    }
+
+    let parsed = parse_accelerator("Ctrl+/").unwrap();
+    assert_eq!(parsed.mods, Modifiers::CONTROL);
+    assert_eq!(parsed.key, ParsedKey::Physical(Code::Slash));
+}
+
+#[test]
+fn test_parse_chord_spans() {
+    assert_eq!(parse_chord("Ctrl+KeyK  Ctrl + KeyC").unwrap().len(), 2);
+    let error = parse_chord("Ctrl+KeyK Ctrl+Shft+KeyC").unwrap_err();
+    assert_eq!(error.kind(), AcceleratorParseErrorKind::UnknownKey);
+    assert_eq!(error.span(), 15..19);
+    assert_eq!(parse_chord("  ").unwrap_err().span(), 0..0);
+}
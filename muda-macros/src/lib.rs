@@ -0,0 +1,523 @@
+//! Procedural macros for [muda](https://docs.rs/muda).
+//!
+//! - [`accelerator!`]: Creates an [`Accelerator`](https://docs.rs/muda/latest/muda/accelerator/struct.Accelerator.html)
+//!   from a string which is validated at compile time.
+//! - [`menu!`]: Creates a [`Menu`](https://docs.rs/muda/latest/muda/struct.Menu.html) from a tree of submenus, items and separators.
+
+use std::ops::Range;
+
+use muda_accelerator_parser::{Key, Modifiers, ParsedKey};
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    braced, parenthesized,
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    Expr, Ident, LitStr, Token,
+};
+
+/// Creates an [`Accelerator`](https://docs.rs/muda/latest/muda/accelerator/struct.Accelerator.html) from a string using the same
+/// grammar as [`FromStr`](std::str::FromStr), but parsed at compile time.
+///
+/// Unlike parsing at runtime, an invalid accelerator is a compile error naming the offending token,
+/// and the accelerator can be used in `const` items.
+///
+/// The error points at the whole string literal, proc macros can only point inside a literal
+/// on nightly Rust with the `procmacro2_semver_exempt` cfg of `proc-macro2` enabled.
+///
+/// ```
+/// use muda::accelerator::Accelerator;
+/// use muda_macros::accelerator;
+///
+/// const SAVE_AS: Accelerator = accelerator!("CmdOrCtrl+Shift+KeyS");
+/// assert_eq!(SAVE_AS, "CmdOrCtrl+Shift+KeyS".parse().unwrap());
+///
+/// const UNDO: Accelerator = accelerator!("Ctrl+\"Z\"");
+/// assert_eq!(UNDO, "Ctrl+\"z\"".parse().unwrap());
+/// ```
+///
+/// ```compile_fail
+/// let save = muda_macros::accelerator!("Ctrl+Shft+KeyS");
+/// ```
+///
+/// `CmdOrCtrl` is resolved for the target platform, not for the platform running the compiler.
+#[proc_macro]
+pub fn accelerator(input: TokenStream) -> TokenStream {
+    let literal = parse_macro_input!(input as LitStr);
+    match accelerator_expr(&literal) {
+        Ok(expr) => expr.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// Returns an expression creating the accelerator `literal` describes, or an error pointing at
+/// the offending token of `literal` if it can't be parsed.
+fn accelerator_expr(literal: &LitStr) -> syn::Result<TokenStream2> {
+    let accelerator = match muda_accelerator_parser::parse_accelerator(&literal.value()) {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            let message = match literal.value().get(error.span()) {
+                Some(token) if !token.is_empty() => format!("{}: `{}`", error, token),
+                _ => error.to_string(),
+            };
+            return Err(syn::Error::new(token_span(literal, error.span()), message));
+        }
+    };
+
+    let mut mods = modifiers_bits(accelerator.mods);
+    if accelerator.cmd_or_ctrl {
+        mods.push(quote! {
+            if cfg!(target_os = "macos") {
+                ::muda::accelerator::Modifiers::SUPER.bits()
+            } else {
+                ::muda::accelerator::Modifiers::CONTROL.bits()
+            }
+        });
+    }
+    let mods = modifiers(mods);
+    let left = modifiers(modifiers_bits(accelerator.left));
+    let right = modifiers(modifiers_bits(accelerator.right));
+    let sides = quote! {
+        ::muda::accelerator::ModifierSides {
+            left: #left,
+            right: #right,
+        }
+    };
+    let key = match accelerator.key {
+        // `Key::Character` can't be created in `const` items, so characters have their own constructor
+        ParsedKey::Logical(Key::Character(c)) => {
+            let c = c.to_lowercase();
+            return Ok(quote! {
+                ::muda::accelerator::Accelerator::__from_character_parts(#mods, #sides, #c)
+            });
+        }
+        ParsedKey::Physical(code) => {
+            let code = format_ident!("{}", code.to_string());
+            quote!(::muda::accelerator::AcceleratorKey::Physical(
+                ::muda::accelerator::Code::#code
+            ))
+        }
+        ParsedKey::Logical(key) => {
+            let key = format_ident!("{}", key.to_string());
+            quote!(::muda::accelerator::AcceleratorKey::Logical(
+                ::muda::accelerator::Key::#key
+            ))
+        }
+    };
+
+    Ok(quote! {
+        ::muda::accelerator::Accelerator::__from_parts(#mods, #sides, #key)
+    })
+}
+
+/// Creates a [`Menu`](https://docs.rs/muda/latest/muda/struct.Menu.html) from a tree of submenus, items and separators,
+/// expanding to the builders of [`muda::builder`](https://docs.rs/muda/latest/muda/builder/index.html).
+///
+/// ```no_run
+/// use muda::icon::Icon;
+/// use muda_macros::menu;
+///
+/// let recent = vec!["notes.txt", "todo.txt"];
+/// let menu = menu! {
+///     "&File" => {
+///         "&Save" (id = "file.save", accelerator = "CmdOrCtrl+S"),
+///         "Save &As" (accelerator = "CmdOrCtrl+Shift+S", enabled = false),
+///         check "&Autosave" (checked = true, help_text = "Save after every change"),
+///         "&Recent" (icon = Icon::from_name("document-open-recent")) => {
+///             { recent[0] },
+///             { recent[1] },
+///         },
+///         separator,
+///         native Quit,
+///     },
+///     "&Help" => {
+///         native About("muda".into(), Default::default()),
+///     },
+/// };
+/// ```
+///
+/// The menu is a comma-separated list of submenus, written as `LABEL [(OPTIONS)] => { ITEMS }`,
+/// where `ITEMS` is a comma-separated list of:
+///
+/// - `LABEL [(OPTIONS)]`: A [`MenuItem`](https://docs.rs/muda/latest/muda/struct.MenuItem.html).
+/// - `check LABEL [(OPTIONS)]`: A [`CheckMenuItem`](https://docs.rs/muda/latest/muda/struct.CheckMenuItem.html).
+/// - `LABEL [(OPTIONS)] => { ITEMS }`: A nested [`Submenu`](https://docs.rs/muda/latest/muda/struct.Submenu.html).
+/// - `native ITEM`: A [`NativeMenuItem`](https://docs.rs/muda/latest/muda/enum.NativeMenuItem.html), the `NativeMenuItem::` prefix is optional.
+/// - `separator`: A separator.
+///
+/// A `LABEL` is a string literal or an expression in braces. `OPTIONS` are comma-separated
+/// `name = expression` pairs named after the methods of the builders:
+///
+/// - Items: `id`, `enabled`, `accelerator`, `icon`, `tooltip` and `help_text`.
+/// - Check items: the options of items and `checked`.
+/// - Submenus: `enabled`, `icon` and `tooltip`.
+///
+/// String literal accelerators are parsed at compile time, like with [`accelerator!`],
+/// other expressions must be an [`Accelerator`](https://docs.rs/muda/latest/muda/accelerator/struct.Accelerator.html).
+///
+/// ```compile_fail
+/// let menu = muda_macros::menu! {
+///     "&File" => {
+///         "&Save" (accelerator = "CmdOrCtrl+Shft+S"),
+///     },
+/// };
+/// ```
+///
+/// ```compile_fail
+/// let menu = muda_macros::menu! {
+///     "&File" (checked = true) => {},
+/// };
+/// ```
+#[proc_macro]
+pub fn menu(input: TokenStream) -> TokenStream {
+    let menu = parse_macro_input!(input as MenuInput);
+    match menu.expand() {
+        Ok(expr) => expr.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+const ITEM_OPTIONS: &[&str] = &[
+    "id",
+    "enabled",
+    "accelerator",
+    "icon",
+    "tooltip",
+    "help_text",
+];
+const CHECK_ITEM_OPTIONS: &[&str] = &[
+    "id",
+    "enabled",
+    "accelerator",
+    "icon",
+    "tooltip",
+    "help_text",
+    "checked",
+];
+const SUBMENU_OPTIONS: &[&str] = &["enabled", "icon", "tooltip"];
+
+struct MenuInput {
+    nodes: Punctuated<Node, Token![,]>,
+}
+
+impl Parse for MenuInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            nodes: Punctuated::parse_terminated(input)?,
+        })
+    }
+}
+
+impl MenuInput {
+    fn expand(&self) -> syn::Result<TokenStream2> {
+        let mut submenus = Vec::new();
+        for node in &self.nodes {
+            match node {
+                Node::Submenu { .. } => submenus.push(node.expand()?),
+                _ => {
+                    return Err(syn::Error::new(
+                        node.span(),
+                        "only submenus can be added to a menu",
+                    ))
+                }
+            }
+        }
+
+        Ok(quote! {
+            ::muda::builder::MenuBuilder::new()
+                #(.submenu(#submenus))*
+                .build()
+                .unwrap_or_else(|e| ::core::unreachable!("{}", e))
+        })
+    }
+}
+
+enum Node {
+    Item {
+        label: Label,
+        options: Options,
+    },
+    CheckItem {
+        check: Ident,
+        label: Label,
+        options: Options,
+    },
+    Submenu {
+        label: Label,
+        options: Options,
+        children: Punctuated<Node, Token![,]>,
+    },
+    NativeItem {
+        native: Ident,
+        item: Expr,
+    },
+    Separator(Ident),
+}
+
+impl Parse for Node {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Ident) {
+            let ident: Ident = input.parse()?;
+            return match ident.to_string().as_str() {
+                "separator" => Ok(Node::Separator(ident)),
+                "check" => Ok(Node::CheckItem {
+                    check: ident,
+                    label: input.parse()?,
+                    options: input.parse()?,
+                }),
+                "native" => Ok(Node::NativeItem {
+                    native: ident,
+                    item: input.parse()?,
+                }),
+                _ => Err(syn::Error::new(
+                    ident.span(),
+                    "expected a label, `check`, `native` or `separator`",
+                )),
+            };
+        }
+
+        let label = input.parse()?;
+        let options = input.parse()?;
+        if input.peek(Token![=>]) {
+            input.parse::<Token![=>]>()?;
+            let content;
+            braced!(content in input);
+            Ok(Node::Submenu {
+                label,
+                options,
+                children: Punctuated::parse_terminated(&content)?,
+            })
+        } else {
+            Ok(Node::Item { label, options })
+        }
+    }
+}
+
+impl Node {
+    fn span(&self) -> Span {
+        match self {
+            Node::Item { label, .. } | Node::Submenu { label, .. } => label.span(),
+            Node::CheckItem { check, .. } => check.span(),
+            Node::NativeItem { native, .. } => native.span(),
+            Node::Separator(separator) => separator.span(),
+        }
+    }
+
+    /// Returns the builder of this node, or the native menu item for `native` nodes.
+    fn expand(&self) -> syn::Result<TokenStream2> {
+        match self {
+            Node::Item { label, options } => {
+                let options = options.expand(ITEM_OPTIONS)?;
+                Ok(quote!(::muda::builder::MenuItemBuilder::new(#label) #(#options)*))
+            }
+            Node::CheckItem { label, options, .. } => {
+                let options = options.expand(CHECK_ITEM_OPTIONS)?;
+                Ok(quote!(::muda::builder::CheckMenuItemBuilder::new(#label) #(#options)*))
+            }
+            Node::Submenu {
+                label,
+                options,
+                children,
+            } => {
+                let options = options.expand(SUBMENU_OPTIONS)?;
+                let children = children
+                    .iter()
+                    .map(|child| {
+                        let method = match child {
+                            Node::Item { .. } => quote!(item),
+                            Node::CheckItem { .. } => quote!(check_item),
+                            Node::Submenu { .. } => quote!(submenu),
+                            Node::NativeItem { .. } => quote!(native_item),
+                            Node::Separator(_) => return Ok(quote!(.separator())),
+                        };
+                        let child = child.expand()?;
+                        Ok(quote!(.#method(#child)))
+                    })
+                    .collect::<syn::Result<Vec<_>>>()?;
+                Ok(quote! {
+                    ::muda::builder::SubmenuBuilder::new(#label)
+                        #(#options)*
+                        #(#children)*
+                })
+            }
+            Node::NativeItem { item, .. } => Ok(native_item(item)),
+            Node::Separator(_) => Ok(quote!(::muda::NativeMenuItem::Separator)),
+        }
+    }
+}
+
+/// Prefixes `item` with `NativeMenuItem::` when it is a variant name, like `Quit` or `About(..)`.
+fn native_item(item: &Expr) -> TokenStream2 {
+    let is_variant = |expr: &Expr| match expr {
+        Expr::Path(path) => {
+            path.qself.is_none()
+                && path.path.leading_colon.is_none()
+                && path.path.segments.len() == 1
+        }
+        _ => false,
+    };
+    match item {
+        Expr::Call(call) if is_variant(&call.func) => {
+            let func = &call.func;
+            let args = &call.args;
+            quote!(::muda::NativeMenuItem::#func(#args))
+        }
+        item if is_variant(item) => quote!(::muda::NativeMenuItem::#item),
+        item => quote!(#item),
+    }
+}
+
+enum Label {
+    Literal(LitStr),
+    Expr(Expr),
+}
+
+impl Parse for Label {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(syn::token::Brace) {
+            let content;
+            braced!(content in input);
+            Ok(Label::Expr(content.parse()?))
+        } else {
+            Ok(Label::Literal(input.parse()?))
+        }
+    }
+}
+
+impl Label {
+    fn span(&self) -> Span {
+        match self {
+            Label::Literal(literal) => literal.span(),
+            Label::Expr(expr) => syn::spanned::Spanned::span(expr),
+        }
+    }
+}
+
+impl quote::ToTokens for Label {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        match self {
+            Label::Literal(literal) => literal.to_tokens(tokens),
+            Label::Expr(expr) => expr.to_tokens(tokens),
+        }
+    }
+}
+
+struct MenuOption {
+    name: Ident,
+    value: Expr,
+}
+
+impl Parse for MenuOption {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        input.parse::<Token![=]>()?;
+        Ok(Self {
+            name,
+            value: input.parse()?,
+        })
+    }
+}
+
+/// The optional `(name = value, ..)` options of a node.
+#[derive(Default)]
+struct Options(Punctuated<MenuOption, Token![,]>);
+
+impl Parse for Options {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if !input.peek(syn::token::Paren) {
+            return Ok(Self::default());
+        }
+        let content;
+        parenthesized!(content in input);
+        Ok(Self(Punctuated::parse_terminated(&content)?))
+    }
+}
+
+impl Options {
+    /// Returns the builder method calls setting these options, fails on options
+    /// which aren't in `allowed` or which are set twice.
+    fn expand(&self, allowed: &[&str]) -> syn::Result<Vec<TokenStream2>> {
+        let mut calls = Vec::new();
+        for (i, option) in self.0.iter().enumerate() {
+            let name = option.name.to_string();
+            if !allowed.contains(&name.as_str()) {
+                return Err(syn::Error::new(
+                    option.name.span(),
+                    format!(
+                        "unknown option `{}`, expected one of: {}",
+                        name,
+                        allowed.join(", ")
+                    ),
+                ));
+            }
+            if self.0.iter().take(i).any(|previous| previous.name == name) {
+                return Err(syn::Error::new(
+                    option.name.span(),
+                    format!("option `{}` is set more than once", name),
+                ));
+            }
+
+            let method = &option.name;
+            let value = &option.value;
+            calls.push(match value {
+                Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(literal),
+                    ..
+                }) if name == "accelerator" => {
+                    let accelerator = accelerator_expr(literal)?;
+                    quote!(.accelerator(#accelerator))
+                }
+                _ if name == "accelerator" => {
+                    quote!(.accelerator::<::muda::accelerator::Accelerator>(#value))
+                }
+                _ => quote!(.#method(#value)),
+            });
+        }
+        Ok(calls)
+    }
+}
+
+/// Returns the bits of each modifier in `mods` as a `const` expression.
+fn modifiers_bits(mods: Modifiers) -> Vec<TokenStream2> {
+    [
+        (Modifiers::SHIFT, "SHIFT"),
+        (Modifiers::CONTROL, "CONTROL"),
+        (Modifiers::ALT, "ALT"),
+        (Modifiers::SUPER, "SUPER"),
+        (Modifiers::ALT_GRAPH, "ALT_GRAPH"),
+        (Modifiers::HYPER, "HYPER"),
+    ]
+    .into_iter()
+    .filter(|(modifier, _)| mods.contains(*modifier))
+    .map(|(_, name)| {
+        let name = format_ident!("{}", name);
+        quote!(::muda::accelerator::Modifiers::#name.bits())
+    })
+    .collect()
+}
+
+fn modifiers(bits: Vec<TokenStream2>) -> TokenStream2 {
+    quote!(::muda::accelerator::Modifiers::from_bits_truncate(0 #(| #bits)*))
+}
+
+/// Returns the span of `range` inside the string literal, or the span of the whole literal
+/// when the compiler can't point inside it. `Span::subspan` always returns `None` on stable Rust,
+/// so this only narrows the span on nightly with `procmacro2_semver_exempt`,
+/// and never for literals whose source differs from their value because of escapes.
+fn token_span(literal: &LitStr, range: Range<usize>) -> Span {
+    let token = literal.token();
+    let source = token.to_string();
+    let offset = if source.starts_with('r') {
+        source.find('"').map(|quote| quote + 1)
+    } else if source.get(1..source.len() - 1) == Some(literal.value().as_str()) {
+        Some(1)
+    } else {
+        None
+    };
+
+    offset
+        .and_then(|offset| token.subspan(range.start + offset..range.end + offset))
+        .unwrap_or_else(|| literal.span())
+}
@@ -0,0 +1,78 @@
+// macOS menus can only be created on the main thread, which tests don't run on
+#![cfg(any(target_os = "linux", target_os = "windows"))]
+
+use muda::{accelerator::Accelerator, MenuItemKind, NativeMenuItem};
+use muda_macros::menu;
+
+#[test]
+fn test_menu_tree() {
+    // gtk needs a display, the test is skipped without one
+    #[cfg(target_os = "linux")]
+    if gtk::init().is_err() {
+        return;
+    }
+
+    let recent = ["notes.txt", "todo.txt"];
+    let menu = menu! {
+        "&File" => {
+            "&Save" (id = "file.save", accelerator = "CmdOrCtrl+S"),
+            "Save &As" (enabled = false, help_text = "Save a copy"),
+            check "&Autosave" (checked = true, id = "file.autosave"),
+            "&Recent" => {
+                { recent[0] },
+                { recent[1] },
+            },
+            separator,
+            native Quit,
+        },
+        "&Help" (enabled = false) => {},
+    };
+
+    let submenus = menu.items();
+    assert_eq!(submenus.len(), 2);
+    let file = submenus[0].as_submenu().unwrap();
+    assert_eq!(file.label(), "&File");
+    let help = submenus[1].as_submenu().unwrap();
+    assert!(!help.enabled() && help.items().is_empty());
+
+    let items = file.items();
+    assert_eq!(items.len(), 6);
+
+    let save = items[0].as_menu_item().unwrap();
+    assert_eq!(save.label(), "&Save");
+    assert_eq!(save.id(), "file.save");
+    let accelerator: Accelerator = "CmdOrCtrl+S".parse().unwrap();
+    assert_eq!(save.accelerator(), Some(accelerator));
+
+    let save_as = items[1].as_menu_item().unwrap();
+    assert!(!save_as.enabled() && save_as.accelerator().is_none());
+    assert_eq!(save_as.help_text().as_deref(), Some("Save a copy"));
+
+    let autosave = items[2].as_check_menu_item().unwrap();
+    assert!(autosave.checked());
+    assert_eq!(autosave.id(), "file.autosave");
+
+    let recent_items = items[3].as_submenu().unwrap().items();
+    let labels: Vec<_> = recent_items
+        .iter()
+        .map(|item| item.as_menu_item().unwrap().label())
+        .collect();
+    assert_eq!(labels, recent);
+
+    assert!(matches!(
+        items[4],
+        MenuItemKind::NativeMenuItem(NativeMenuItem::Separator)
+    ));
+    assert!(matches!(
+        items[5],
+        MenuItemKind::NativeMenuItem(NativeMenuItem::Quit)
+    ));
+
+    // the ids given in the macro find the items like the ids given to the builders
+    assert!(menu.find_by_id("file.autosave").is_some());
+    assert_eq!(
+        menu.find_by_path("File/Recent/todo.txt")
+            .and_then(|item| item.as_menu_item().map(|item| item.label())),
+        Some("todo.txt".into())
+    );
+}
@@ -0,0 +1,5 @@
+use muda_macros::accelerator;
+
+fn main() {
+    let _save = accelerator!("Ctrl+Shft+KeyS");
+}
@@ -0,0 +1,9 @@
+use muda_macros::menu;
+
+fn main() {
+    let _menu = menu! {
+        "&File" => {
+            "&Save" (accelerator = "CmdOrCtrl+Shft+S"),
+        },
+    };
+}
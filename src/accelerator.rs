@@ -28,36 +28,432 @@
 //! # assert!("shift+KeyQ+alt".parse::<Accelerator>().is_err());
 //! ```
 //!
+//! Parsing a string reports mistakes at runtime, the `accelerator!` macro of the companion
+//! `muda-macros` crate parses it at compile time instead, `muda_macros::accelerator!("CmdOrCtrl+Shift+KeyS")`.
+//!
+//! Keys can be written using their [`Code`] name, like `KeyS` or `BracketLeft`,
+//! or using a more friendly alias, like `S`, `[`, `Esc` or `PgUp`. Both modifiers and keys
+//! are matched case-insensitively. Symbols typed with shift on a US layout add `Shift`,
+//! so `Ctrl+?` is the same as `Ctrl+Shift+Slash`.
+//! ```
+//! # use muda::accelerator::{Accelerator, Modifiers, Code};
+//! #
+//! assert_eq!(
+//!     "ctrl+s".parse::<Accelerator>().unwrap(),
+//!     Accelerator::new(Modifiers::CONTROL, Code::KeyS)
+//! );
+//! assert_eq!(
+//!     "Shift+Esc".parse::<Accelerator>().unwrap(),
+//!     "Shift+Escape".parse::<Accelerator>().unwrap()
+//! );
+//! assert_eq!(
+//!     "Ctrl+?".parse::<Accelerator>().unwrap(),
+//!     "Ctrl+Shift+Slash".parse::<Accelerator>().unwrap()
+//! );
+//! ```
+//!
+//! Keys written in double quotes are logical keys, which are matched by the character
+//! or named [`Key`] they produce in the current keyboard layout instead of their position,
+//! see [`AcceleratorKey`].
+//! ```
+//! # use muda::accelerator::{Accelerator, Modifiers, Key};
+//! #
+//! let accelerator: Accelerator = "Ctrl+\"z\"".parse().unwrap();
+//! assert_eq!(accelerator, Accelerator::new(Modifiers::CONTROL, Key::Character("z".into())));
+//! assert!(accelerator.matches(Modifiers::CONTROL, Key::Character("z".into())));
+//! ```
+//!
+//! Accelerators can also be converted back into a string using their [`Display`](std::fmt::Display)
+//! implementation, which produces a canonical form that can be parsed again.
+//! ```
+//! # use muda::accelerator::{Accelerator, Modifiers, Code};
+//! #
+//! let accelerator = Accelerator::new(Modifiers::ALT | Modifiers::SHIFT, Code::KeyQ);
+//! assert_eq!(accelerator.to_string(), "Shift+Alt+KeyQ");
+//! assert_eq!(accelerator.to_string().parse::<Accelerator>().unwrap(), accelerator);
+//! ```
+//!
+//! To display an accelerator to users, for example in a tooltip, use [`AcceleratorFormatter`]
+//! which produces the same text the native menus display.
+//! ```
+//! # use muda::accelerator::{Accelerator, AcceleratorStyle};
+//! #
+//! let accelerator: Accelerator = "CmdOrCtrl+Shift+S".parse().unwrap();
+//! println!("{}", accelerator.to_display_string(AcceleratorStyle::native()));
+//! ```
+//!
+
+pub(crate) mod conflicts;
+mod format;
+pub(crate) mod keys;
+
+pub use conflicts::AcceleratorConflict;
+pub use format::{AcceleratorFormatter, AcceleratorStyle, ModifierNames};
+pub use keyboard_types::{Code, Key, Modifiers};
+pub(crate) use muda_accelerator_parser::ALL_CODES;
+pub use muda_accelerator_parser::{AcceleratorParseError, AcceleratorParseErrorKind};
+use muda_accelerator_parser::{ParsedAccelerator, ParsedKey};
+use std::{
+    borrow::{Borrow, Cow},
+    fmt,
+    hash::{Hash, Hasher},
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+/// The key of an [`Accelerator`], which is either a physical or a logical key.
+///
+/// Physical keys are identified by their position on the keyboard, `Ctrl+KeyZ` is triggered
+/// by the key in the bottom left corner of the letters, which is `Y` on a German and `W` on a French layout.
+/// Logical keys are identified by the character they produce in the current keyboard layout,
+/// `Ctrl+"z"` is triggered by whichever key produces `z`.
+///
+/// ## Platform-specific:
+///
+/// - **macOS:** menus match key equivalents by character, so physical keys
+///   are registered using the character they produce on a US layout.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AcceleratorKey {
+    /// A physical key, independent of the keyboard layout.
+    Physical(Code),
+    /// A logical key like [`Key::Enter`] or [`Key::Character`], as produced by the keyboard layout.
+    ///
+    /// Characters are case-insensitive and stored in lowercase, use [`Modifiers::SHIFT`] to require shift.
+    Logical(Key),
+}
+
+impl AcceleratorKey {
+    fn normalized(self) -> Self {
+        match self {
+            AcceleratorKey::Logical(Key::Character(c)) if c.chars().any(char::is_uppercase) => {
+                AcceleratorKey::Logical(Key::Character(c.to_lowercase()))
+            }
+            key => key,
+        }
+    }
+}
+
+/// The key as it is stored in an [`Accelerator`], a [`Key::Character`] can't be created
+/// in `const` items so the characters of accelerators created there are stored as a `&'static str`.
+#[derive(Debug, Clone)]
+enum StoredKey {
+    Key(AcceleratorKey),
+    Character(&'static str),
+}
+
+impl From<Code> for AcceleratorKey {
+    fn from(code: Code) -> Self {
+        AcceleratorKey::Physical(code)
+    }
+}
+
+impl From<&Code> for AcceleratorKey {
+    fn from(code: &Code) -> Self {
+        AcceleratorKey::Physical(*code)
+    }
+}
 
-pub use keyboard_types::{Code, Modifiers};
-use std::{borrow::Borrow, hash::Hash, str::FromStr};
+impl From<Key> for AcceleratorKey {
+    fn from(key: Key) -> Self {
+        AcceleratorKey::Logical(key).normalized()
+    }
+}
+
+impl From<&Key> for AcceleratorKey {
+    fn from(key: &Key) -> Self {
+        AcceleratorKey::from(key.clone())
+    }
+}
+
+/// Formats physical keys using their [`Code`] name and logical keys
+/// using their [`Key`] name in double quotes, for example `KeyZ` and `"z"`.
+impl fmt::Display for AcceleratorKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AcceleratorKey::Physical(code) => write!(f, "{}", code),
+            AcceleratorKey::Logical(key) => write!(f, "\"{}\"", key),
+        }
+    }
+}
+
+/// The modifiers held with the keys on the left and on the right side of the keyboard.
+///
+/// This is used to restrict the modifiers of an [`Accelerator`] to one side, see [`Accelerator::with_sides`],
+/// and to describe which modifier keys are held when matching, see [`Accelerator::matches_with_sides`].
+/// Only [`Modifiers::SHIFT`], [`Modifiers::CONTROL`], [`Modifiers::ALT`] and [`Modifiers::SUPER`] have sides.
+///
+/// ```
+/// # use muda::accelerator::{Code, ModifierSides, Modifiers};
+/// #
+/// let mut held = ModifierSides::default();
+/// held.update(Code::ControlRight, true);
+/// assert_eq!(held.right, Modifiers::CONTROL);
+/// held.update(Code::ControlRight, false);
+/// assert!(held.is_empty());
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ModifierSides {
+    /// The modifiers held with, or restricted to, their left key.
+    pub left: Modifiers,
+    /// The modifiers held with, or restricted to, their right key.
+    pub right: Modifiers,
+}
+
+impl ModifierSides {
+    /// Returns `true` if no modifier is held with, or restricted to, a specific side.
+    pub fn is_empty(&self) -> bool {
+        self.left.is_empty() && self.right.is_empty()
+    }
+
+    /// Updates the held modifiers from a key press or release of `code`,
+    /// keys other than modifier keys are ignored.
+    pub fn update(&mut self, code: Code, pressed: bool) {
+        let (side, modifier) = match code {
+            Code::ShiftLeft => (&mut self.left, Modifiers::SHIFT),
+            Code::ControlLeft => (&mut self.left, Modifiers::CONTROL),
+            Code::AltLeft => (&mut self.left, Modifiers::ALT),
+            Code::MetaLeft => (&mut self.left, Modifiers::SUPER),
+            Code::ShiftRight => (&mut self.right, Modifiers::SHIFT),
+            Code::ControlRight => (&mut self.right, Modifiers::CONTROL),
+            Code::AltRight => (&mut self.right, Modifiers::ALT),
+            Code::MetaRight => (&mut self.right, Modifiers::SUPER),
+            _ => return,
+        };
+        side.set(modifier, pressed);
+    }
+
+    fn canonical(self) -> Self {
+        let sided = Modifiers::SHIFT | Modifiers::CONTROL | Modifiers::ALT | Modifiers::SUPER;
+        Self {
+            left: canonical_mods(self.left) & sided,
+            right: canonical_mods(self.right) & sided,
+        }
+    }
+}
 
 /// Base `Accelerator` functions.
-#[derive(Debug, Clone, PartialEq, Hash)]
+///
+/// Two accelerators are considered equal if they are triggered by the same key and modifiers,
+/// regardless of how they were spelled, for example `Modifiers::META` is treated as `Modifiers::SUPER`
+/// and modifiers that don't take part in matching (like `Modifiers::CAPS_LOCK`) are ignored.
+///
+/// Besides `Shift`, `Control`, `Alt` and `Super`, accelerators can use [`Modifiers::ALT_GRAPH`]
+/// and [`Modifiers::HYPER`], and restrict modifiers to the key on one side of the keyboard,
+/// see [`Accelerator::with_sides`].
+#[derive(Debug, Clone)]
 pub struct Accelerator {
     pub(crate) mods: Modifiers,
-    pub(crate) key: Code,
+    pub(crate) sides: ModifierSides,
+    key: StoredKey,
 }
 
 impl Accelerator {
     /// Creates a new accelerator to define keyboard shortcuts throughout your application.
-    pub fn new(mods: impl Into<Option<Modifiers>>, key: Code) -> Self {
+    ///
+    /// `key` is either a physical [`Code`] or a logical [`Key`], see [`AcceleratorKey`].
+    pub fn new(mods: impl Into<Option<Modifiers>>, key: impl Into<AcceleratorKey>) -> Self {
         Self {
             mods: mods.into().unwrap_or_else(Modifiers::empty),
-            key,
+            sides: ModifierSides::default(),
+            key: StoredKey::Key(key.into().normalized()),
         }
     }
 
-    /// Returns `true` if this [`Code`] and [`Modifiers`] matches this `Accelerator`.
+    /// Restricts modifiers of this accelerator to the key on one side of the keyboard,
+    /// for example [`Modifiers::CONTROL`] in `sides.right` requires the right control key.
+    /// The restricted modifiers are added to the modifiers of this accelerator.
+    ///
+    /// ```
+    /// # use muda::accelerator::{Accelerator, Code, ModifierSides, Modifiers};
+    /// #
+    /// let accelerator = Accelerator::new(None, Code::KeyS).with_sides(ModifierSides {
+    ///     right: Modifiers::CONTROL,
+    ///     ..Default::default()
+    /// });
+    /// assert_eq!(accelerator, "RightCtrl+KeyS".parse().unwrap());
+    /// ```
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Windows / macOS:** The restrictions are ignored, native menus can't tell the sides apart.
+    pub fn with_sides(mut self, sides: ModifierSides) -> Self {
+        let sides = sides.canonical();
+        self.mods |= sides.left | sides.right;
+        self.sides = sides;
+        self
+    }
+
+    /// Creates an accelerator from its parts as they are stored, this is what the `accelerator!`
+    /// macro of `muda-macros` expands to so it can be used in `const` items.
+    /// Restricted modifiers have to be included in `mods`.
+    #[doc(hidden)]
+    pub const fn __from_parts(mods: Modifiers, sides: ModifierSides, key: AcceleratorKey) -> Self {
+        Self {
+            mods,
+            sides,
+            key: StoredKey::Key(key),
+        }
+    }
+
+    /// Like [`Accelerator::__from_parts`] for a logical key producing `character`,
+    /// which has to be lowercase.
+    #[doc(hidden)]
+    pub const fn __from_character_parts(
+        mods: Modifiers,
+        sides: ModifierSides,
+        character: &'static str,
+    ) -> Self {
+        Self {
+            mods,
+            sides,
+            key: StoredKey::Character(character),
+        }
+    }
+
+    /// Returns the modifiers of this accelerator that are restricted to one side of the keyboard.
+    pub fn sides(&self) -> ModifierSides {
+        self.sides
+    }
+
+    /// Returns the modifiers of this accelerator, including the ones restricted to one side.
+    pub fn mods(&self) -> Modifiers {
+        self.mods
+    }
+
+    /// Returns the key of this accelerator.
+    pub fn key(&self) -> Cow<'_, AcceleratorKey> {
+        match &self.key {
+            StoredKey::Key(key) => Cow::Borrowed(key),
+            StoredKey::Character(c) => {
+                Cow::Owned(AcceleratorKey::Logical(Key::Character((*c).to_owned())))
+            }
+        }
+    }
+
+    /// Returns whether the key of this accelerator is `key`, without allocating for characters.
+    fn key_eq(&self, key: &AcceleratorKey) -> bool {
+        match (&self.key, key) {
+            (StoredKey::Key(own), key) => own == key,
+            (StoredKey::Character(own), AcceleratorKey::Logical(Key::Character(c))) => own == c,
+            (StoredKey::Character(_), _) => false,
+        }
+    }
+
+    /// Returns `true` if this [`Code`] or [`Key`] and [`Modifiers`] matches this `Accelerator`.
+    ///
+    /// Physical accelerators only match a [`Code`] and logical accelerators only match a [`Key`],
+    /// so when handling a keyboard event that has both, check each of them.
+    ///
+    /// Lock modifiers, like [`Modifiers::CAPS_LOCK`] and [`Modifiers::NUM_LOCK`], are ignored
+    /// and so are the sides set using [`Accelerator::with_sides`], use [`Accelerator::matches_with_sides`] to check them.
+    /// [`Modifiers::ALT_GRAPH`] is ignored when matching a [`Key`], since it is part of typing
+    /// the character on layouts where the character needs it.
     ///
     /// [`Code`]: Code
     /// [`Modifiers`]: crate::accelerator::Modifiers
-    pub fn matches(&self, modifiers: impl Borrow<Modifiers>, key: impl Borrow<Code>) -> bool {
-        // Should be a const but const bit_or doesn't work here.
-        let base_mods = Modifiers::SHIFT | Modifiers::CONTROL | Modifiers::ALT | Modifiers::SUPER;
-        let modifiers = modifiers.borrow();
-        let key = key.borrow();
-        self.mods == *modifiers & base_mods && self.key == *key
+    pub fn matches(
+        &self,
+        modifiers: impl Borrow<Modifiers>,
+        key: impl Into<AcceleratorKey>,
+    ) -> bool {
+        let key = key.into().normalized();
+        let mut mods = canonical_mods(*modifiers.borrow());
+        let mut own_mods = self.canonical_mods();
+        if let AcceleratorKey::Logical(_) = key {
+            mods.remove(Modifiers::ALT_GRAPH);
+            own_mods.remove(Modifiers::ALT_GRAPH);
+        }
+        own_mods == mods && self.key_eq(&key)
+    }
+
+    /// Like [`Accelerator::matches`], but also checks that the modifiers restricted to one side
+    /// of the keyboard are held on that side, `held` being the modifiers held on each side.
+    pub fn matches_with_sides(
+        &self,
+        modifiers: impl Borrow<Modifiers>,
+        held: ModifierSides,
+        key: impl Into<AcceleratorKey>,
+    ) -> bool {
+        self.matches(modifiers, key) && self.sides_match(held)
+    }
+
+    fn sides_match(&self, held: ModifierSides) -> bool {
+        let held = held.canonical();
+        held.left.contains(self.sides.left) && held.right.contains(self.sides.right)
+    }
+
+    /// Returns the modifiers of this accelerator in their canonical form,
+    /// which is what [`PartialEq`], [`Hash`] and [`Display`](fmt::Display) operate on.
+    pub(crate) fn canonical_mods(&self) -> Modifiers {
+        canonical_mods(self.mods)
+    }
+}
+
+/// Treats `Modifiers::META` as `Modifiers::SUPER` and removes the modifiers
+/// that don't take part in matching, like lock modifiers.
+fn canonical_mods(mut mods: Modifiers) -> Modifiers {
+    if mods.contains(Modifiers::META) {
+        mods.remove(Modifiers::META);
+        mods.insert(Modifiers::SUPER);
+    }
+    mods & (Modifiers::SHIFT
+        | Modifiers::CONTROL
+        | Modifiers::ALT
+        | Modifiers::SUPER
+        | Modifiers::ALT_GRAPH
+        | Modifiers::HYPER)
+}
+
+impl PartialEq for Accelerator {
+    fn eq(&self, other: &Self) -> bool {
+        self.canonical_mods() == other.canonical_mods()
+            && self.sides == other.sides
+            && other.key_eq(&self.key())
+    }
+}
+
+impl Eq for Accelerator {}
+
+impl Hash for Accelerator {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.canonical_mods().hash(state);
+        self.sides.hash(state);
+        self.key().hash(state);
+    }
+}
+
+/// Formats the accelerator in its canonical form, modifiers are always written
+/// in the order `Shift`, `Control`, `Alt`, `Super`, `AltGraph` and `Hyper` followed by the key,
+/// for example `Shift+Control+KeyS` or `Control+"s"`, see [`AcceleratorKey`].
+/// Modifiers restricted to one side are prefixed with `Left` or `Right`, for example `RightControl+KeyS`.
+///
+/// The resulting string can be parsed back using [`FromStr`].
+impl fmt::Display for Accelerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mods = self.canonical_mods();
+        for (modifier, name) in [
+            (Modifiers::SHIFT, "Shift"),
+            (Modifiers::CONTROL, "Control"),
+            (Modifiers::ALT, "Alt"),
+            (Modifiers::SUPER, "Super"),
+            (Modifiers::ALT_GRAPH, "AltGraph"),
+            (Modifiers::HYPER, "Hyper"),
+        ] {
+            let (left, right) = (
+                self.sides.left.contains(modifier),
+                self.sides.right.contains(modifier),
+            );
+            if left {
+                write!(f, "Left{}+", name)?;
+            }
+            if right {
+                write!(f, "Right{}+", name)?;
+            }
+            if mods.contains(modifier) && !left && !right {
+                write!(f, "{}+", name)?;
+            }
+        }
+        write!(f, "{}", self.key())
     }
 }
 
@@ -71,6 +467,348 @@ impl FromStr for Accelerator {
     }
 }
 
+impl TryFrom<&str> for Accelerator {
+    type Error = AcceleratorParseError;
+    fn try_from(accelerator_string: &str) -> Result<Self, Self::Error> {
+        parse_accelerator(accelerator_string)
+    }
+}
+
+/// Serializes the accelerator to its canonical string form, see [`Display`](fmt::Display).
+#[cfg(feature = "serde")]
+impl serde::Serialize for Accelerator {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Deserializes the accelerator from a string, see [`FromStr`].
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Accelerator {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let accelerator = String::deserialize(deserializer)?;
+        parse_accelerator(&accelerator).map_err(serde::de::Error::custom)
+    }
+}
+
+/// A multi-stroke accelerator, also known as a key chord, consisting of one or more
+/// [`Accelerator`]s that have to be pressed one after another, for example `Ctrl+KeyK Ctrl+KeyC`.
+///
+/// Chords can be parsed from strings where each stroke is separated by whitespace.
+/// ```
+/// # use muda::accelerator::{Accelerator, Chord, Modifiers, Code};
+/// #
+/// let chord: Chord = "Ctrl+KeyK Ctrl+KeyC".parse().unwrap();
+/// assert_eq!(chord.strokes().len(), 2);
+/// assert_eq!(chord.strokes()[0], Accelerator::new(Modifiers::CONTROL, Code::KeyK));
+/// assert_eq!(chord.to_string(), "Control+KeyK Control+KeyC");
+/// ```
+///
+/// ## Platform-specific:
+///
+/// - **Windows / macOS:** Menu items can't be activated by a chord, see `Submenu::add_chord_item`,
+///   use a [`ChordMatcher`] with the key events of the window instead.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Chord(Vec<Accelerator>);
+
+impl Chord {
+    /// Creates a new chord from a sequence of strokes.
+    ///
+    /// ## Panics:
+    ///
+    /// Panics if `strokes` is empty.
+    pub fn new(strokes: impl IntoIterator<Item = Accelerator>) -> Self {
+        let strokes = strokes.into_iter().collect::<Vec<_>>();
+        assert!(!strokes.is_empty(), "a chord needs at least one stroke");
+        Self(strokes)
+    }
+
+    /// Returns the strokes of this chord in the order they have to be pressed.
+    pub fn strokes(&self) -> &[Accelerator] {
+        &self.0
+    }
+
+    /// Returns the first stroke of this chord.
+    pub fn first(&self) -> &Accelerator {
+        &self.0[0]
+    }
+}
+
+impl From<Accelerator> for Chord {
+    fn from(accelerator: Accelerator) -> Self {
+        Self(vec![accelerator])
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, stroke) in self.0.iter().enumerate() {
+            if i != 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", stroke)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Chord {
+    type Err = AcceleratorParseError;
+    fn from_str(chord_string: &str) -> Result<Self, Self::Err> {
+        parse_chord(chord_string)
+    }
+}
+
+/// Serializes the chord to its canonical string form, see [`Display`](fmt::Display).
+#[cfg(feature = "serde")]
+impl serde::Serialize for Chord {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Deserializes the chord from a string, see [`FromStr`].
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Chord {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let chord = String::deserialize(deserializer)?;
+        parse_chord(&chord).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Describes the result of feeding a key press to a [`ChordMatcher`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChordState<T> {
+    /// The key press didn't match the start of any chord and wasn't consumed.
+    Unmatched,
+    /// The key press matched the start of one or more chords
+    /// and the matcher is now waiting for the next stroke.
+    Pending,
+    /// The key press completed a chord, contains the value the chord was registered with.
+    Completed(T),
+    /// A pending chord was cancelled, either because `Escape` was pressed
+    /// or because the key press didn't continue any of the pending chords.
+    Cancelled,
+}
+
+/// A state machine that tracks key presses and reports when a registered [`Chord`] is completed.
+///
+/// Once the first stroke of a chord is pressed, the matcher is armed and waits
+/// for the next stroke. Pressing `Escape`, pressing a key that doesn't continue any chord
+/// or waiting longer than the timeout between two strokes cancels the pending chord.
+///
+/// ```
+/// # use muda::accelerator::{ChordMatcher, ChordState, Modifiers, Code};
+/// #
+/// let mut matcher = ChordMatcher::new();
+/// matcher.register("Ctrl+KeyK Ctrl+KeyC".parse().unwrap(), "comment");
+///
+/// assert_eq!(matcher.process(Modifiers::CONTROL, Code::KeyK), ChordState::Pending);
+/// assert_eq!(matcher.process(Modifiers::CONTROL, Code::KeyC), ChordState::Completed("comment"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ChordMatcher<T> {
+    chords: Vec<(Chord, T)>,
+    pending: Vec<KeyPress>,
+    last_stroke: Option<Instant>,
+    timeout: Duration,
+}
+
+impl<T: Clone> Default for ChordMatcher<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> ChordMatcher<T> {
+    /// The default time allowed between two strokes of a chord.
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+
+    /// Creates a new matcher with [`ChordMatcher::DEFAULT_TIMEOUT`].
+    pub fn new() -> Self {
+        Self::with_timeout(Self::DEFAULT_TIMEOUT)
+    }
+
+    /// Creates a new matcher that cancels a pending chord
+    /// if the next stroke isn't pressed within `timeout`.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            chords: Vec::new(),
+            pending: Vec::new(),
+            last_stroke: None,
+            timeout,
+        }
+    }
+
+    /// Gets the time allowed between two strokes of a chord.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Sets the time allowed between two strokes of a chord.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Registers a new chord, `value` is returned in [`ChordState::Completed`] once the chord is completed.
+    pub fn register(&mut self, chord: Chord, value: T) {
+        self.chords.push((chord, value));
+    }
+
+    /// Removes all chords that were registered with `value`.
+    pub fn unregister(&mut self, value: &T)
+    where
+        T: PartialEq,
+    {
+        self.chords.retain(|(_, v)| v != value);
+        self.cancel();
+    }
+
+    /// Returns `true` if the first strokes of a chord were pressed and the matcher is waiting for the next one.
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Cancels the pending chord, if any.
+    pub fn cancel(&mut self) {
+        self.pending.clear();
+        self.last_stroke = None;
+    }
+
+    /// Feeds a key press to the matcher and returns the new state.
+    ///
+    /// Only matches strokes with a physical key, use [`ChordMatcher::process_key`]
+    /// to match strokes with a logical key too.
+    pub fn process(
+        &mut self,
+        modifiers: impl Borrow<Modifiers>,
+        code: impl Borrow<Code>,
+    ) -> ChordState<T> {
+        self.process_at(*modifiers.borrow(), *code.borrow(), None, Instant::now())
+    }
+
+    /// Feeds a key press with both its physical [`Code`] and the logical [`Key`]
+    /// it produced to the matcher and returns the new state.
+    pub fn process_key(
+        &mut self,
+        modifiers: impl Borrow<Modifiers>,
+        code: impl Borrow<Code>,
+        key: impl Borrow<Key>,
+    ) -> ChordState<T> {
+        self.process_at(
+            *modifiers.borrow(),
+            *code.borrow(),
+            Some(key.borrow().clone()),
+            Instant::now(),
+        )
+    }
+
+    fn process_at(
+        &mut self,
+        mods: Modifiers,
+        code: Code,
+        key: Option<Key>,
+        now: Instant,
+    ) -> ChordState<T> {
+        if let Some(last_stroke) = self.last_stroke {
+            if now.saturating_duration_since(last_stroke) > self.timeout {
+                self.cancel();
+            }
+        }
+
+        if self.is_pending() {
+            if code == Code::Escape {
+                self.cancel();
+                return ChordState::Cancelled;
+            }
+
+            // Pressing a modifier on its own to prepare the next stroke shouldn't cancel the chord.
+            if is_modifier_key(code) {
+                return ChordState::Pending;
+            }
+        }
+
+        let mut candidate = self.pending.clone();
+        candidate.push(KeyPress {
+            mods,
+            code,
+            key,
+            sides: None,
+        });
+
+        let matches = |strokes: &[Accelerator]| {
+            strokes
+                .iter()
+                .zip(&candidate)
+                .all(|(stroke, press)| press.matches(stroke))
+        };
+
+        if let Some((_, value)) = self
+            .chords
+            .iter()
+            .find(|(chord, _)| chord.strokes().len() == candidate.len() && matches(chord.strokes()))
+        {
+            let value = value.clone();
+            self.cancel();
+            return ChordState::Completed(value);
+        }
+
+        let is_prefix = self
+            .chords
+            .iter()
+            .any(|(chord, _)| chord.strokes().len() > candidate.len() && matches(chord.strokes()));
+        if is_prefix {
+            self.pending = candidate;
+            self.last_stroke = Some(now);
+            return ChordState::Pending;
+        }
+
+        if self.is_pending() {
+            self.cancel();
+            ChordState::Cancelled
+        } else {
+            ChordState::Unmatched
+        }
+    }
+}
+
+/// A key press fed to a [`ChordMatcher`] or dispatched to a menu.
+#[derive(Debug, Clone)]
+pub(crate) struct KeyPress {
+    pub(crate) mods: Modifiers,
+    pub(crate) code: Code,
+    pub(crate) key: Option<Key>,
+    /// The modifiers held on each side of the keyboard, if known,
+    /// otherwise the sides of accelerators are ignored.
+    pub(crate) sides: Option<ModifierSides>,
+}
+
+impl KeyPress {
+    pub(crate) fn matches(&self, stroke: &Accelerator) -> bool {
+        let sides_match = match self.sides {
+            Some(sides) => stroke.sides_match(sides),
+            None => true,
+        };
+        sides_match
+            && (stroke.matches(self.mods, self.code)
+                || matches!(&self.key, Some(key) if stroke.matches(self.mods, key)))
+    }
+}
+
+fn is_modifier_key(key: Code) -> bool {
+    matches!(
+        key,
+        Code::ShiftLeft
+            | Code::ShiftRight
+            | Code::ControlLeft
+            | Code::ControlRight
+            | Code::AltLeft
+            | Code::AltRight
+            | Code::MetaLeft
+            | Code::MetaRight
+    )
+}
+
 /// Represents the active modifier keys.
 ///
 /// This is intended to be clearer than [`Modifiers`], when describing accelerators.
@@ -96,6 +834,8 @@ pub enum Mods {
     AltMetaShift,
     CtrlMetaShift,
     AltCtrlMetaShift,
+    AltGr,
+    Hyper,
 }
 
 impl From<Mods> for Option<Modifiers> {
@@ -128,90 +868,78 @@ impl From<Mods> for Modifiers {
             Mods::AltCtrlShift => (true, true, false, true),
             Mods::CtrlMetaShift => (false, true, true, true),
             Mods::AltCtrlMetaShift => (true, true, true, true),
+            Mods::AltGr | Mods::Hyper => (false, false, false, false),
         };
         let mut mods = Modifiers::empty();
         mods.set(Modifiers::ALT, alt);
         mods.set(Modifiers::CONTROL, ctrl);
         mods.set(Modifiers::SUPER, meta);
         mods.set(Modifiers::SHIFT, shift);
+        mods.set(Modifiers::ALT_GRAPH, src == Mods::AltGr);
+        mods.set(Modifiers::HYPER, src == Mods::Hyper);
         mods
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct AcceleratorParseError(String);
+/// An error returned when an [`Accelerator`] can't be registered
+/// because the platform has no equivalent for its key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedAcceleratorError {
+    accelerator: Accelerator,
+}
 
-impl std::fmt::Display for AcceleratorParseError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[AcceleratorParseError]: {}", self.0)
+impl UnsupportedAcceleratorError {
+    #[allow(unused)]
+    pub(crate) fn new(accelerator: Accelerator) -> Self {
+        Self { accelerator }
+    }
+
+    /// The accelerator that couldn't be registered.
+    pub fn accelerator(&self) -> &Accelerator {
+        &self.accelerator
+    }
+}
+
+impl fmt::Display for UnsupportedAcceleratorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[UnsupportedAcceleratorError]: `{}` can't be registered on this platform",
+            self.accelerator
+        )
     }
 }
 
+impl std::error::Error for UnsupportedAcceleratorError {}
+
+/// The modifier `CmdOrCtrl` stands for on the current platform.
+#[cfg(target_os = "macos")]
+const CMD_OR_CTRL: Modifiers = Modifiers::SUPER;
+#[cfg(not(target_os = "macos"))]
+const CMD_OR_CTRL: Modifiers = Modifiers::CONTROL;
+
 fn parse_accelerator(accelerator_string: &str) -> Result<Accelerator, AcceleratorParseError> {
-    let mut mods = Modifiers::empty();
-    let mut key = Code::Unidentified;
-
-    for raw in accelerator_string.split('+') {
-        let token = raw.trim().to_string();
-        if token.is_empty() {
-            return Err(AcceleratorParseError(
-                "Unexpected empty token while parsing accelerator".into(),
-            ));
-        }
+    muda_accelerator_parser::parse_accelerator(accelerator_string).map(accelerator_from_parsed)
+}
 
-        if key != Code::Unidentified {
-            // at this point we already parsed the modifiers and found a main key but
-            // the function received more then one main key or it is not in the right order
-            // examples:
-            // 1. "Ctrl+Shift+C+A" => only one main key should be allowd.
-            // 2. "Ctrl+C+Shift" => wrong order
-            return Err(AcceleratorParseError(format!(
-                "Unexpected accelerator string format: \"{}\"",
-                accelerator_string
-            )));
-        }
+fn parse_chord(chord_string: &str) -> Result<Chord, AcceleratorParseError> {
+    muda_accelerator_parser::parse_chord(chord_string)
+        .map(|strokes| Chord(strokes.into_iter().map(accelerator_from_parsed).collect()))
+}
 
-        match token.to_uppercase().as_str() {
-            "OPTION" | "ALT" => {
-                mods.set(Modifiers::ALT, true);
-            }
-            "CONTROL" | "CTRL" => {
-                mods.set(Modifiers::CONTROL, true);
-            }
-            "COMMAND" | "CMD" | "SUPER" => {
-                mods.set(Modifiers::SUPER, true);
-            }
-            "SHIFT" => {
-                mods.set(Modifiers::SHIFT, true);
-            }
-            "COMMANDORCONTROL" | "COMMANDORCTRL" | "CMDORCTRL" | "CMDORCONTROL" => {
-                #[cfg(target_os = "macos")]
-                mods.set(Modifiers::SUPER, true);
-                #[cfg(not(target_os = "macos"))]
-                mods.set(Modifiers::CONTROL, true);
-            }
-            _ => {
-                if let Ok(code) = Code::from_str(token.as_str()) {
-                    match code {
-                        Code::Unidentified => {
-                            return Err(AcceleratorParseError(format!(
-                                "Couldn't identify \"{}\" as a valid `Code`",
-                                token
-                            )))
-                        }
-                        _ => key = code,
-                    }
-                } else {
-                    return Err(AcceleratorParseError(format!(
-                        "Couldn't identify \"{}\" as a valid `Code`",
-                        token
-                    )));
-                }
-            }
-        }
+fn accelerator_from_parsed(parsed: ParsedAccelerator) -> Accelerator {
+    let mut mods = parsed.mods;
+    if parsed.cmd_or_ctrl {
+        mods.insert(CMD_OR_CTRL);
     }
-
-    Ok(Accelerator { key, mods })
+    let key = match parsed.key {
+        ParsedKey::Physical(code) => AcceleratorKey::Physical(code),
+        ParsedKey::Logical(key) => AcceleratorKey::from(key),
+    };
+    Accelerator::new(mods, key).with_sides(ModifierSides {
+        left: parsed.left,
+        right: parsed.right,
+    })
 }
 
 #[test]
@@ -220,56 +948,66 @@ fn test_parse_accelerator() {
         parse_accelerator("CTRL+KeyX").unwrap(),
         Accelerator {
             mods: Modifiers::CONTROL,
-            key: Code::KeyX,
+            sides: ModifierSides::default(),
+            key: StoredKey::Key(AcceleratorKey::Physical(Code::KeyX)),
         }
     );
     assert_eq!(
         parse_accelerator("SHIFT+KeyC").unwrap(),
         Accelerator {
             mods: Modifiers::SHIFT,
-            key: Code::KeyC,
+            sides: ModifierSides::default(),
+            key: StoredKey::Key(AcceleratorKey::Physical(Code::KeyC)),
         }
     );
     assert_eq!(
         parse_accelerator("CTRL+KeyZ").unwrap(),
         Accelerator {
             mods: Modifiers::CONTROL,
-            key: Code::KeyZ,
+            sides: ModifierSides::default(),
+            key: StoredKey::Key(AcceleratorKey::Physical(Code::KeyZ)),
         }
     );
     assert_eq!(
         parse_accelerator("super+ctrl+SHIFT+alt+ArrowUp").unwrap(),
         Accelerator {
             mods: Modifiers::SUPER | Modifiers::CONTROL | Modifiers::SHIFT | Modifiers::ALT,
-            key: Code::ArrowUp,
+            sides: ModifierSides::default(),
+            key: StoredKey::Key(AcceleratorKey::Physical(Code::ArrowUp)),
         }
     );
     assert_eq!(
         parse_accelerator("Digit5").unwrap(),
         Accelerator {
             mods: Modifiers::empty(),
-            key: Code::Digit5,
+            sides: ModifierSides::default(),
+            key: StoredKey::Key(AcceleratorKey::Physical(Code::Digit5)),
         }
     );
     assert_eq!(
         parse_accelerator("KeyG").unwrap(),
         Accelerator {
             mods: Modifiers::empty(),
-            key: Code::KeyG,
+            sides: ModifierSides::default(),
+            key: StoredKey::Key(AcceleratorKey::Physical(Code::KeyG)),
         }
     );
 
-    let acc = parse_accelerator("+G");
-    assert!(acc.is_err());
-
-    let acc = parse_accelerator("SHGSH+G");
-    assert!(acc.is_err());
+    assert_eq!(
+        parse_error("+G"),
+        (AcceleratorParseErrorKind::EmptyToken, 0..0)
+    );
+    assert_eq!(
+        parse_error("SHGSH+G"),
+        (AcceleratorParseErrorKind::UnknownKey, 0..5)
+    );
 
     assert_eq!(
         parse_accelerator("SHiFT+F12").unwrap(),
         Accelerator {
             mods: Modifiers::SHIFT,
-            key: Code::F12,
+            sides: ModifierSides::default(),
+            key: StoredKey::Key(AcceleratorKey::Physical(Code::F12)),
         }
     );
     assert_eq!(
@@ -279,10 +1017,582 @@ fn test_parse_accelerator() {
             mods: Modifiers::SUPER,
             #[cfg(not(target_os = "macos"))]
             mods: Modifiers::CONTROL,
-            key: Code::Space,
+            sides: ModifierSides::default(),
+            key: StoredKey::Key(AcceleratorKey::Physical(Code::Space)),
         }
     );
 
-    let acc = parse_accelerator("CTRL+");
-    assert!(acc.is_err());
+    assert_eq!(
+        parse_error("CTRL+"),
+        (AcceleratorParseErrorKind::EmptyToken, 5..5)
+    );
+    assert_eq!(
+        parse_error("CTRL + + KeyS"),
+        (AcceleratorParseErrorKind::EmptyToken, 7..7)
+    );
+    assert_eq!(
+        parse_error(" CTRL+SHIFT "),
+        (AcceleratorParseErrorKind::MissingKey, 1..11)
+    );
+    assert_eq!(
+        parse_error("CTRL+KeyS+KeyA"),
+        (AcceleratorParseErrorKind::MultipleKeys, 10..14)
+    );
+    assert_eq!(
+        parse_error("KeyS + Ctrl"),
+        (AcceleratorParseErrorKind::ModifierAfterKey, 7..11)
+    );
+
+    assert_eq!(
+        parse_accelerator("Shift+Super").unwrap(),
+        Accelerator {
+            mods: Modifiers::SHIFT,
+            sides: ModifierSides::default(),
+            key: StoredKey::Key(AcceleratorKey::Physical(Code::Super)),
+        }
+    );
+    assert_eq!(
+        parse_accelerator("shift+super").unwrap(),
+        parse_accelerator("Shift+Super").unwrap()
+    );
+}
+
+#[cfg(test)]
+fn parse_error(accelerator_string: &str) -> (AcceleratorParseErrorKind, std::ops::Range<usize>) {
+    let error = parse_accelerator(accelerator_string).unwrap_err();
+    (error.kind(), error.span())
+}
+
+#[cfg(test)]
+fn test_modifier_combinations() -> Vec<Modifiers> {
+    let mods = [
+        Modifiers::SHIFT,
+        Modifiers::CONTROL,
+        Modifiers::ALT,
+        Modifiers::SUPER,
+        Modifiers::ALT_GRAPH,
+        Modifiers::HYPER,
+    ];
+    (0..1 << mods.len())
+        .map(|bits: usize| {
+            mods.iter()
+                .enumerate()
+                .filter(|(i, _)| bits & (1 << i) != 0)
+                .fold(Modifiers::empty(), |acc, (_, m)| acc | *m)
+        })
+        .collect()
+}
+
+#[test]
+fn test_accelerator_round_trip() {
+    for mods in test_modifier_combinations() {
+        for key in ALL_CODES {
+            let accelerator = Accelerator::new(mods, *key);
+            let string = accelerator.to_string();
+            let parsed = parse_accelerator(&string)
+                .unwrap_or_else(|e| panic!("failed to parse \"{}\": {}", string, e));
+            assert_eq!(parsed, accelerator, "\"{}\" didn't round-trip", string);
+            assert_eq!(parsed.to_string(), string);
+
+            let lowercase = string.to_lowercase();
+            let parsed = parse_accelerator(&lowercase)
+                .unwrap_or_else(|e| panic!("failed to parse \"{}\": {}", lowercase, e));
+            assert_eq!(parsed, accelerator, "\"{}\" didn't round-trip", lowercase);
+        }
+    }
+}
+
+#[test]
+fn test_accelerator_canonical_form() {
+    use std::collections::hash_map::DefaultHasher;
+
+    fn hash(accelerator: &Accelerator) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        accelerator.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    let spellings = [
+        "super+ctrl+SHIFT+alt+ArrowUp",
+        "Alt+Shift+Cmd+Control+ArrowUp",
+        "option + shift + command + ctrl + ArrowUp",
+        "Shift+Control+Alt+Super+ArrowUp",
+    ];
+    let expected = parse_accelerator(spellings[0]).unwrap();
+    for spelling in spellings {
+        let accelerator = parse_accelerator(spelling).unwrap();
+        assert_eq!(accelerator, expected);
+        assert_eq!(hash(&accelerator), hash(&expected));
+        assert_eq!(accelerator.to_string(), "Shift+Control+Alt+Super+ArrowUp");
+    }
+
+    let with_meta = Accelerator::new(Modifiers::META | Modifiers::SHIFT, Code::KeyS);
+    let with_super = Accelerator::new(Modifiers::SUPER | Modifiers::SHIFT, Code::KeyS);
+    assert_eq!(with_meta, with_super);
+    assert_eq!(hash(&with_meta), hash(&with_super));
+    assert_eq!(with_meta.to_string(), "Shift+Super+KeyS");
+
+    let with_lock = Accelerator::new(Modifiers::CONTROL | Modifiers::CAPS_LOCK, Code::KeyS);
+    assert_eq!(with_lock, Accelerator::new(Modifiers::CONTROL, Code::KeyS));
+    assert_eq!(with_lock.to_string(), "Control+KeyS");
+}
+
+#[test]
+fn test_parse_chord() {
+    assert_eq!(
+        parse_chord("Ctrl+KeyK Ctrl+KeyC").unwrap(),
+        Chord::new([
+            Accelerator::new(Modifiers::CONTROL, Code::KeyK),
+            Accelerator::new(Modifiers::CONTROL, Code::KeyC),
+        ])
+    );
+    assert_eq!(
+        parse_chord("  ctrl + KeyK   shift+alt+KeyC ").unwrap(),
+        Chord::new([
+            Accelerator::new(Modifiers::CONTROL, Code::KeyK),
+            Accelerator::new(Modifiers::SHIFT | Modifiers::ALT, Code::KeyC),
+        ])
+    );
+    assert_eq!(
+        parse_chord("F1").unwrap(),
+        Chord::from(Accelerator::new(None, Code::F1))
+    );
+
+    let chord_error = |string| parse_chord(string).map(|_| ()).unwrap_err();
+    assert_eq!(
+        chord_error("").kind(),
+        AcceleratorParseErrorKind::EmptyToken
+    );
+    assert_eq!(chord_error("Ctrl+KeyK Ctrl+").span(), 15..15);
+    assert_eq!(
+        chord_error("Ctrl+KeyK Foo").kind(),
+        AcceleratorParseErrorKind::UnknownKey
+    );
+    assert_eq!(chord_error("Ctrl+KeyK  Ctrl + Foo").span(), 18..21);
+
+    let chord = parse_chord("Ctrl+KeyK Ctrl+KeyC").unwrap();
+    assert_eq!(parse_chord(&chord.to_string()).unwrap(), chord);
+}
+
+#[test]
+fn test_chord_matcher() {
+    let mut matcher = ChordMatcher::new();
+    matcher.register(parse_chord("Ctrl+KeyK Ctrl+KeyC").unwrap(), 1);
+    matcher.register(parse_chord("Ctrl+KeyK KeyU").unwrap(), 2);
+    matcher.register(parse_chord("Ctrl+KeyS").unwrap(), 3);
+
+    let start = Instant::now();
+    let ctrl = Modifiers::CONTROL;
+
+    // single stroke chords complete right away
+    assert_eq!(
+        matcher.process_at(ctrl, Code::KeyS, None, start),
+        ChordState::Completed(3)
+    );
+
+    // lock modifiers and releasing a modifier between strokes don't matter
+    assert_eq!(
+        matcher.process_at(ctrl | Modifiers::NUM_LOCK, Code::KeyK, None, start),
+        ChordState::Pending
+    );
+    assert!(matcher.is_pending());
+    assert_eq!(
+        matcher.process_at(ctrl, Code::ControlLeft, None, start),
+        ChordState::Pending
+    );
+    assert_eq!(
+        matcher.process_at(Modifiers::empty(), Code::KeyU, None, start),
+        ChordState::Completed(2)
+    );
+    assert!(!matcher.is_pending());
+
+    // escape cancels
+    assert_eq!(
+        matcher.process_at(ctrl, Code::KeyK, None, start),
+        ChordState::Pending
+    );
+    assert_eq!(
+        matcher.process_at(Modifiers::empty(), Code::Escape, None, start),
+        ChordState::Cancelled
+    );
+    assert_eq!(
+        matcher.process_at(ctrl, Code::KeyC, None, start),
+        ChordState::Unmatched
+    );
+
+    // a key that doesn't continue any chord cancels
+    assert_eq!(
+        matcher.process_at(ctrl, Code::KeyK, None, start),
+        ChordState::Pending
+    );
+    assert_eq!(
+        matcher.process_at(ctrl, Code::KeyX, None, start),
+        ChordState::Cancelled
+    );
+
+    // timeout cancels
+    assert_eq!(
+        matcher.process_at(ctrl, Code::KeyK, None, start),
+        ChordState::Pending
+    );
+    let later = start + ChordMatcher::<i32>::DEFAULT_TIMEOUT + Duration::from_millis(1);
+    assert_eq!(
+        matcher.process_at(ctrl, Code::KeyC, None, later),
+        ChordState::Unmatched
+    );
+
+    // within the timeout it completes
+    assert_eq!(
+        matcher.process_at(ctrl, Code::KeyK, None, start),
+        ChordState::Pending
+    );
+    let later = start + Duration::from_millis(500);
+    assert_eq!(
+        matcher.process_at(ctrl, Code::KeyC, None, later),
+        ChordState::Completed(1)
+    );
+
+    matcher.unregister(&1);
+    assert_eq!(
+        matcher.process_at(ctrl, Code::KeyK, None, start),
+        ChordState::Pending
+    );
+    assert_eq!(
+        matcher.process_at(ctrl, Code::KeyC, None, start),
+        ChordState::Cancelled
+    );
+}
+
+#[test]
+fn test_parse_key_aliases() {
+    let cases = [
+        ("Ctrl+S", Modifiers::CONTROL, Code::KeyS),
+        ("ctrl+s", Modifiers::CONTROL, Code::KeyS),
+        ("Alt+5", Modifiers::ALT, Code::Digit5),
+        ("Ctrl+[", Modifiers::CONTROL, Code::BracketLeft),
+        ("Ctrl+]", Modifiers::CONTROL, Code::BracketRight),
+        ("Ctrl+\\", Modifiers::CONTROL, Code::Backslash),
+        ("Ctrl+/", Modifiers::CONTROL, Code::Slash),
+        ("Ctrl+-", Modifiers::CONTROL, Code::Minus),
+        ("Ctrl+=", Modifiers::CONTROL, Code::Equal),
+        ("Ctrl+Plus", Modifiers::CONTROL, Code::Equal),
+        ("Ctrl+,", Modifiers::CONTROL, Code::Comma),
+        ("Ctrl+.", Modifiers::CONTROL, Code::Period),
+        ("Ctrl+`", Modifiers::CONTROL, Code::Backquote),
+        ("Ctrl+'", Modifiers::CONTROL, Code::Quote),
+        ("Esc", Modifiers::empty(), Code::Escape),
+        ("Shift+Return", Modifiers::SHIFT, Code::Enter),
+        ("Ctrl+PgUp", Modifiers::CONTROL, Code::PageUp),
+        ("Ctrl+PgDn", Modifiers::CONTROL, Code::PageDown),
+        ("Del", Modifiers::empty(), Code::Delete),
+        ("Shift+Ins", Modifiers::SHIFT, Code::Insert),
+        ("Alt+Up", Modifiers::ALT, Code::ArrowUp),
+        ("Alt+left", Modifiers::ALT, Code::ArrowLeft),
+        ("Ctrl+num5", Modifiers::CONTROL, Code::Numpad5),
+        ("Ctrl+numadd", Modifiers::CONTROL, Code::NumpadAdd),
+        ("VolumeUp", Modifiers::empty(), Code::AudioVolumeUp),
+        ("MediaNextTrack", Modifiers::empty(), Code::MediaTrackNext),
+        ("PrintScreen", Modifiers::empty(), Code::PrintScreen),
+        ("Shift+menu", Modifiers::SHIFT, Code::ContextMenu),
+        // case-insensitive `Code` names keep working
+        ("ctrl+arrowup", Modifiers::CONTROL, Code::ArrowUp),
+        ("CTRL+KEYS", Modifiers::CONTROL, Code::KeyS),
+        ("shift+f12", Modifiers::SHIFT, Code::F12),
+        ("Ctrl+Space", Modifiers::CONTROL, Code::Space),
+        // shifted symbols add shift, like the physical key name with shift
+        ("Ctrl+?", Modifiers::CONTROL | Modifiers::SHIFT, Code::Slash),
+        (
+            "Ctrl+Shift+/",
+            Modifiers::CONTROL | Modifiers::SHIFT,
+            Code::Slash,
+        ),
+        (
+            "Ctrl+Shift+?",
+            Modifiers::CONTROL | Modifiers::SHIFT,
+            Code::Slash,
+        ),
+        (
+            "Ctrl+{",
+            Modifiers::CONTROL | Modifiers::SHIFT,
+            Code::BracketLeft,
+        ),
+        (
+            "Ctrl+}",
+            Modifiers::CONTROL | Modifiers::SHIFT,
+            Code::BracketRight,
+        ),
+        ("Ctrl+<", Modifiers::CONTROL | Modifiers::SHIFT, Code::Comma),
+        (
+            "Ctrl+>",
+            Modifiers::CONTROL | Modifiers::SHIFT,
+            Code::Period,
+        ),
+        (
+            "Ctrl+:",
+            Modifiers::CONTROL | Modifiers::SHIFT,
+            Code::Semicolon,
+        ),
+        (
+            "Ctrl+\"",
+            Modifiers::CONTROL | Modifiers::SHIFT,
+            Code::Quote,
+        ),
+        (
+            "Ctrl+|",
+            Modifiers::CONTROL | Modifiers::SHIFT,
+            Code::Backslash,
+        ),
+        (
+            "Ctrl+~",
+            Modifiers::CONTROL | Modifiers::SHIFT,
+            Code::Backquote,
+        ),
+        ("Ctrl+_", Modifiers::CONTROL | Modifiers::SHIFT, Code::Minus),
+        ("Alt+!", Modifiers::ALT | Modifiers::SHIFT, Code::Digit1),
+        ("Alt+@", Modifiers::ALT | Modifiers::SHIFT, Code::Digit2),
+        ("Alt+#", Modifiers::ALT | Modifiers::SHIFT, Code::Digit3),
+    ];
+
+    for (string, mods, key) in cases {
+        assert_eq!(
+            parse_accelerator(string).unwrap(),
+            Accelerator::new(mods, key),
+            "\"{}\" was parsed incorrectly",
+            string
+        );
+    }
+
+    assert_eq!(
+        parse_error("Ctrl+Foo"),
+        (AcceleratorParseErrorKind::UnknownKey, 5..8)
+    );
+    assert_eq!(
+        parse_error("Ctrl+é"),
+        (AcceleratorParseErrorKind::UnknownKey, 5..7)
+    );
+    assert_eq!(
+        parse_error("Ctrl+S+A"),
+        (AcceleratorParseErrorKind::MultipleKeys, 7..8)
+    );
+}
+
+#[test]
+fn test_logical_keys() {
+    let character = |c: &str| AcceleratorKey::Logical(Key::Character(c.into()));
+    let cases = [
+        (
+            "Ctrl+\"z\"",
+            Modifiers::CONTROL,
+            character("z"),
+            "Control+\"z\"",
+        ),
+        (
+            "ctrl + \"Z\"",
+            Modifiers::CONTROL,
+            character("z"),
+            "Control+\"z\"",
+        ),
+        (
+            "Ctrl+\"+\"",
+            Modifiers::CONTROL,
+            character("+"),
+            "Control+\"+\"",
+        ),
+        ("Alt+\"\"\"", Modifiers::ALT, character("\""), "Alt+\"\"\""),
+        (
+            "Shift+\"ß\"",
+            Modifiers::SHIFT,
+            character("ß"),
+            "Shift+\"ß\"",
+        ),
+        ("\" \"", Modifiers::empty(), character(" "), "\" \""),
+        (
+            "Shift+\"Enter\"",
+            Modifiers::SHIFT,
+            AcceleratorKey::Logical(Key::Enter),
+            "Shift+\"Enter\"",
+        ),
+    ];
+    for (string, mods, key, canonical) in cases {
+        let accelerator = parse_accelerator(string).unwrap();
+        assert_eq!(accelerator, Accelerator::new(mods, key), "{}", string);
+        assert_eq!(accelerator.to_string(), canonical);
+        assert_eq!(parse_accelerator(canonical).unwrap(), accelerator);
+    }
+
+    // physical and logical keys are different accelerators
+    assert_ne!(
+        parse_accelerator("Ctrl+\"z\"").unwrap(),
+        parse_accelerator("Ctrl+KeyZ").unwrap()
+    );
+
+    assert_eq!(
+        parse_error("Ctrl+\"\""),
+        (AcceleratorParseErrorKind::UnknownKey, 5..7)
+    );
+    assert_eq!(
+        parse_error("Ctrl+\"zz\""),
+        (AcceleratorParseErrorKind::UnknownKey, 5..9)
+    );
+    assert_eq!(
+        parse_error("Ctrl+\"Unidentified\""),
+        (AcceleratorParseErrorKind::UnknownKey, 5..19)
+    );
+    assert_eq!(
+        parse_error("Ctrl+\"z"),
+        (AcceleratorParseErrorKind::UnknownKey, 5..7)
+    );
+    assert_eq!(
+        parse_error("\"z\"+Ctrl"),
+        (AcceleratorParseErrorKind::ModifierAfterKey, 4..8)
+    );
+
+    let accelerator = parse_accelerator("Ctrl+\"z\"").unwrap();
+    assert!(accelerator.matches(Modifiers::CONTROL, Key::Character("z".into())));
+    assert!(accelerator.matches(Modifiers::CONTROL, Key::Character("Z".into())));
+    assert!(!accelerator.matches(Modifiers::CONTROL, Code::KeyZ));
+    assert!(!accelerator.matches(Modifiers::ALT, Key::Character("z".into())));
+    assert!(!parse_accelerator("Ctrl+KeyZ")
+        .unwrap()
+        .matches(Modifiers::CONTROL, Key::Character("z".into())));
+
+    let chord = parse_chord("Ctrl+\"k\" \" \"").unwrap();
+    assert_eq!(chord.strokes().len(), 2);
+    assert_eq!(chord.to_string(), "Control+\"k\" \" \"");
+    assert_eq!(parse_chord(&chord.to_string()).unwrap(), chord);
+
+    // on an AZERTY layout, the physical KeyQ produces `a`
+    let mut matcher = ChordMatcher::new();
+    matcher.register(parse_chord("Ctrl+\"a\" Ctrl+KeyQ").unwrap(), 1);
+    let start = Instant::now();
+    let a = Some(Key::Character("a".into()));
+    assert_eq!(
+        matcher.process_at(Modifiers::CONTROL, Code::KeyQ, a.clone(), start),
+        ChordState::Pending
+    );
+    assert_eq!(
+        matcher.process_at(Modifiers::CONTROL, Code::KeyQ, a, start),
+        ChordState::Completed(1)
+    );
+    assert_eq!(
+        matcher.process_at(Modifiers::CONTROL, Code::KeyA, None, start),
+        ChordState::Unmatched
+    );
+}
+
+#[test]
+fn test_extended_modifiers() {
+    let right_ctrl = ModifierSides {
+        right: Modifiers::CONTROL,
+        ..Default::default()
+    };
+    let cases = [
+        ("RightCtrl+KeyS", "RightControl+KeyS"),
+        ("rctrl+KeyS", "RightControl+KeyS"),
+        ("LShift+LeftAlt+KeyS", "LeftShift+LeftAlt+KeyS"),
+        ("RCmd+LShift+KeyS", "LeftShift+RightSuper+KeyS"),
+        ("LeftCtrl+RightCtrl+KeyS", "LeftControl+RightControl+KeyS"),
+        ("AltGr+KeyE", "AltGraph+KeyE"),
+        ("Hyper+Shift+KeyH", "Shift+Hyper+KeyH"),
+        // the last token is always the key
+        ("Shift+Hyper", "Shift+Hyper"),
+        ("Shift+AltRight", "Shift+AltRight"),
+    ];
+    for (string, canonical) in cases {
+        let accelerator = parse_accelerator(string).unwrap();
+        assert_eq!(accelerator.to_string(), canonical, "{}", string);
+        assert_eq!(parse_accelerator(canonical).unwrap(), accelerator);
+    }
+    assert_eq!(
+        parse_accelerator("RightCtrl+KeyS").unwrap(),
+        Accelerator::new(None, Code::KeyS).with_sides(right_ctrl)
+    );
+    assert_ne!(
+        parse_accelerator("RightCtrl+KeyS").unwrap(),
+        parse_accelerator("Ctrl+KeyS").unwrap()
+    );
+    assert_ne!(
+        parse_accelerator("AltGr+KeyE").unwrap(),
+        parse_accelerator("Ctrl+Alt+KeyE").unwrap()
+    );
+    assert_eq!(
+        parse_error("Ctrl+RightFoo+KeyS"),
+        (AcceleratorParseErrorKind::UnknownKey, 5..13)
+    );
+
+    let accelerator = parse_accelerator("RightCtrl+KeyS").unwrap();
+    let mut held = ModifierSides::default();
+    held.update(Code::ControlLeft, true);
+    assert!(accelerator.matches(Modifiers::CONTROL, Code::KeyS));
+    assert!(!accelerator.matches_with_sides(Modifiers::CONTROL, held, Code::KeyS));
+    held.update(Code::ControlRight, true);
+    assert!(accelerator.matches_with_sides(Modifiers::CONTROL, held, Code::KeyS));
+    assert!(!accelerator.matches_with_sides(Modifiers::CONTROL, held, Code::KeyA));
+
+    // lock modifiers never take part in matching
+    let locks = Modifiers::CAPS_LOCK | Modifiers::NUM_LOCK | Modifiers::SCROLL_LOCK;
+    let accelerator = parse_accelerator("AltGr+KeyE").unwrap();
+    assert!(accelerator.matches(Modifiers::ALT_GRAPH | locks, Code::KeyE));
+    assert!(!accelerator.matches(Modifiers::CONTROL | Modifiers::ALT, Code::KeyE));
+    assert!(parse_accelerator("Ctrl+KeyS")
+        .unwrap()
+        .matches(Modifiers::CONTROL | locks, Code::KeyS));
+    assert!(parse_accelerator("Super+KeyS")
+        .unwrap()
+        .matches(Modifiers::META, Code::KeyS));
+
+    // AltGr is part of typing a character, not of logical accelerators
+    let at = Key::Character("@".into());
+    let accelerator = Accelerator::new(Modifiers::CONTROL, at.clone());
+    assert!(accelerator.matches(Modifiers::CONTROL | Modifiers::ALT_GRAPH, at.clone()));
+    assert!(accelerator.matches(Modifiers::CONTROL, at.clone()));
+    assert!(!accelerator.matches(Modifiers::ALT_GRAPH, at));
+}
+
+#[test]
+fn test_const_accelerator() {
+    const SAVE: Accelerator = Accelerator::__from_character_parts(
+        Modifiers::CONTROL,
+        ModifierSides {
+            left: Modifiers::empty(),
+            right: Modifiers::empty(),
+        },
+        "s",
+    );
+    let parsed = parse_accelerator("Ctrl+\"S\"").unwrap();
+    assert_eq!(SAVE, parsed);
+    assert_eq!(parsed, SAVE);
+    assert!(SAVE.matches(Modifiers::CONTROL, Key::Character("S".into())));
+    let set = std::collections::HashSet::from([SAVE]);
+    assert!(set.contains(&parsed));
+    assert_eq!(
+        SAVE,
+        Accelerator::new(Modifiers::CONTROL, Key::Character("s".into()))
+    );
+    assert_eq!(
+        SAVE.key().into_owned(),
+        AcceleratorKey::from(Key::Character("S".into()))
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    use serde::{de::IntoDeserializer, Deserialize};
+
+    fn deserialize<'de, T: Deserialize<'de>>(
+        string: &'de str,
+    ) -> Result<T, serde::de::value::Error> {
+        T::deserialize(string.into_deserializer())
+    }
+
+    assert_eq!(
+        deserialize::<Accelerator>("ctrl+shift+s").unwrap(),
+        Accelerator::new(Modifiers::CONTROL | Modifiers::SHIFT, Code::KeyS)
+    );
+    assert_eq!(
+        deserialize::<Chord>("Ctrl+KeyK Ctrl+\"c\"").unwrap(),
+        parse_chord("Ctrl+KeyK Ctrl+\"c\"").unwrap()
+    );
+    assert!(deserialize::<Accelerator>("Ctrl+Shift").is_err());
 }
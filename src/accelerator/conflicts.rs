@@ -0,0 +1,285 @@
+use keyboard_types::{Code, Modifiers};
+
+use super::{Accelerator, Chord};
+use crate::NativeMenuItem;
+
+/// A problem with the accelerators of a [`Menu`](crate::Menu),
+/// found by [`Menu::accelerator_conflicts`](crate::Menu::accelerator_conflicts).
+///
+/// Menu items are identified by their path, which is the labels of their parent submenus
+/// and their own label joined by `/` with mnemonics removed, for example `File/Save`.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AcceleratorConflict {
+    /// Two or more menu items use the same accelerator or chord.
+    Duplicate {
+        /// The accelerator or chord, single accelerators are represented by a single stroke chord.
+        chord: Chord,
+        /// Paths of the menu items using `chord`.
+        items: Vec<String>,
+    },
+    /// The chord of a menu item starts with the accelerator or chord of another menu item,
+    /// so one of them can't be activated.
+    Prefix {
+        /// The shorter accelerator or chord.
+        prefix: Chord,
+        /// Path of the menu item using `prefix`.
+        item: String,
+        /// Path of the menu item whose chord starts with `prefix`.
+        chord_item: String,
+    },
+    /// A menu item uses the same accelerator as a [`NativeMenuItem`].
+    Native {
+        /// The accelerator, or the first stroke of a chord.
+        accelerator: Accelerator,
+        /// Path of the menu item.
+        item: String,
+        /// Path of the native menu item.
+        native_item: String,
+    },
+    /// A menu item uses an accelerator reserved by the platform,
+    /// for example `F10` which activates the menubar on Windows and Linux.
+    Reserved {
+        /// The accelerator, or the first stroke of a chord.
+        accelerator: Accelerator,
+        /// Path of the menu item.
+        item: String,
+    },
+    /// The accelerator of a menu item can't be registered on this platform and won't work.
+    Unsupported {
+        /// The accelerator.
+        accelerator: Accelerator,
+        /// Path of the menu item.
+        item: String,
+    },
+}
+
+impl AcceleratorConflict {
+    /// Returns the paths of the menu items involved in this conflict.
+    pub fn items(&self) -> Vec<&str> {
+        match self {
+            AcceleratorConflict::Duplicate { items, .. } => {
+                items.iter().map(String::as_str).collect()
+            }
+            AcceleratorConflict::Prefix {
+                item, chord_item, ..
+            } => vec![item, chord_item],
+            AcceleratorConflict::Native {
+                item, native_item, ..
+            } => vec![item, native_item],
+            AcceleratorConflict::Reserved { item, .. }
+            | AcceleratorConflict::Unsupported { item, .. } => vec![item],
+        }
+    }
+}
+
+/// An accelerator or chord used by a menu item.
+#[derive(Debug, Clone)]
+pub(crate) struct Binding {
+    pub path: String,
+    pub chord: Chord,
+    /// Whether the accelerator is registered with the platform, in which case it has to be supported by it,
+    /// or dispatched by muda itself, like multi-stroke chords.
+    pub registered: bool,
+}
+
+/// Joins a parent path and a label into a menu item path, removing mnemonics from the label.
+pub(crate) fn item_path(parent: &str, label: &str) -> String {
+    let label = label
+        .replace("&&", "\u{0}")
+        .replace('&', "")
+        .replace('\u{0}', "&");
+    if parent.is_empty() {
+        label
+    } else {
+        format!("{}/{}", parent, label)
+    }
+}
+
+/// Returns the accelerator a native menu item uses on this platform, if any.
+pub(crate) fn native_accelerator(item: &NativeMenuItem) -> Option<Accelerator> {
+    #[cfg(target_os = "macos")]
+    let (mods, key) = match item {
+        NativeMenuItem::CloseWindow => (Modifiers::SUPER, Code::KeyW),
+        NativeMenuItem::Quit => (Modifiers::SUPER, Code::KeyQ),
+        NativeMenuItem::Hide => (Modifiers::SUPER, Code::KeyH),
+        NativeMenuItem::HideOthers => (Modifiers::ALT, Code::KeyH),
+        NativeMenuItem::ToggleFullScreen => (Modifiers::CONTROL, Code::KeyF),
+        NativeMenuItem::Minimize => (Modifiers::SUPER, Code::KeyM),
+        NativeMenuItem::Copy => (Modifiers::SUPER, Code::KeyC),
+        NativeMenuItem::Cut => (Modifiers::SUPER, Code::KeyX),
+        NativeMenuItem::Paste => (Modifiers::SUPER, Code::KeyV),
+        NativeMenuItem::Undo => (Modifiers::SUPER, Code::KeyZ),
+        NativeMenuItem::Redo => (Modifiers::SUPER | Modifiers::SHIFT, Code::KeyZ),
+        NativeMenuItem::SelectAll => (Modifiers::SUPER, Code::KeyA),
+        _ => return None,
+    };
+    #[cfg(not(target_os = "macos"))]
+    let (mods, key) = match item {
+        NativeMenuItem::Copy => (Modifiers::CONTROL, Code::KeyC),
+        NativeMenuItem::Cut => (Modifiers::CONTROL, Code::KeyX),
+        NativeMenuItem::Paste => (Modifiers::CONTROL, Code::KeyV),
+        NativeMenuItem::SelectAll => (Modifiers::CONTROL, Code::KeyA),
+        _ => return None,
+    };
+    Some(Accelerator::new(mods, key))
+}
+
+/// Returns the accelerators reserved by the platform.
+pub(crate) fn reserved_accelerators() -> Vec<Accelerator> {
+    #[cfg(target_os = "macos")]
+    return vec![
+        Accelerator::new(Modifiers::SUPER, Code::Tab),
+        Accelerator::new(Modifiers::SUPER, Code::Space),
+        Accelerator::new(Modifiers::SUPER | Modifiers::ALT, Code::Escape),
+    ];
+    #[cfg(not(target_os = "macos"))]
+    return vec![
+        // activates the menubar
+        Accelerator::new(None, Code::F10),
+        // opens the context menu
+        Accelerator::new(Modifiers::SHIFT, Code::F10),
+        Accelerator::new(None, Code::ContextMenu),
+    ];
+}
+
+/// Finds all conflicts between `bindings`, the accelerators of `native_items`
+/// and `reserved` accelerators, in the order the bindings were given.
+pub(crate) fn find_conflicts(
+    bindings: &[Binding],
+    native_items: &[(String, NativeMenuItem)],
+    reserved: &[Accelerator],
+    is_supported: impl Fn(&Accelerator) -> bool,
+) -> Vec<AcceleratorConflict> {
+    let mut conflicts = Vec::new();
+
+    let mut reported = vec![false; bindings.len()];
+    for (i, binding) in bindings.iter().enumerate() {
+        if reported[i] {
+            continue;
+        }
+        let mut items = vec![binding.path.clone()];
+        for (j, other) in bindings.iter().enumerate().skip(i + 1) {
+            if other.chord == binding.chord {
+                items.push(other.path.clone());
+                reported[j] = true;
+            }
+        }
+        if items.len() > 1 {
+            conflicts.push(AcceleratorConflict::Duplicate {
+                chord: binding.chord.clone(),
+                items,
+            });
+        }
+    }
+
+    for binding in bindings {
+        for other in bindings {
+            let (prefix, chord) = (binding.chord.strokes(), other.chord.strokes());
+            if prefix.len() < chord.len() && chord.starts_with(prefix) {
+                conflicts.push(AcceleratorConflict::Prefix {
+                    prefix: binding.chord.clone(),
+                    item: binding.path.clone(),
+                    chord_item: other.path.clone(),
+                });
+            }
+        }
+    }
+
+    for binding in bindings {
+        let accelerator = binding.chord.first();
+
+        for (native_item, native) in native_items {
+            if native_accelerator(native).as_ref() == Some(accelerator) {
+                conflicts.push(AcceleratorConflict::Native {
+                    accelerator: accelerator.clone(),
+                    item: binding.path.clone(),
+                    native_item: native_item.clone(),
+                });
+            }
+        }
+
+        if reserved.contains(accelerator) {
+            conflicts.push(AcceleratorConflict::Reserved {
+                accelerator: accelerator.clone(),
+                item: binding.path.clone(),
+            });
+        }
+
+        if binding.registered && !is_supported(accelerator) {
+            conflicts.push(AcceleratorConflict::Unsupported {
+                accelerator: accelerator.clone(),
+                item: binding.path.clone(),
+            });
+        }
+    }
+
+    conflicts
+}
+
+#[test]
+fn test_find_conflicts() {
+    let binding = |path: &str, chord: &str, registered| Binding {
+        path: path.into(),
+        chord: chord.parse().unwrap(),
+        registered,
+    };
+    let bindings = [
+        binding("File/Save", "Ctrl+S", true),
+        binding("File/Save All", "Ctrl+S", true),
+        binding("Edit/Copy Path", "Ctrl+C", true),
+        binding("Edit/Comment", "Ctrl+K Ctrl+C", false),
+        binding("Edit/Kill", "Ctrl+K", true),
+        binding("View/Menubar", "F10", true),
+        binding("View/Sleep", "Sleep", true),
+        binding("View/Zoom", "Ctrl+Equal", true),
+    ];
+    let native_items = [("Edit/Copy".to_string(), NativeMenuItem::Copy)];
+    let reserved = [Accelerator::new(None, Code::F10)];
+    let conflicts = find_conflicts(&bindings, &native_items, &reserved, |a| {
+        *a.key() != super::AcceleratorKey::Physical(Code::Sleep)
+    });
+
+    let ctrl = |key| Accelerator::new(Modifiers::CONTROL, key);
+    #[cfg(target_os = "macos")]
+    let copy_conflict = None;
+    #[cfg(not(target_os = "macos"))]
+    let copy_conflict = Some(AcceleratorConflict::Native {
+        accelerator: ctrl(Code::KeyC),
+        item: "Edit/Copy Path".into(),
+        native_item: "Edit/Copy".into(),
+    });
+
+    let expected = [
+        Some(AcceleratorConflict::Duplicate {
+            chord: ctrl(Code::KeyS).into(),
+            items: vec!["File/Save".into(), "File/Save All".into()],
+        }),
+        Some(AcceleratorConflict::Prefix {
+            prefix: ctrl(Code::KeyK).into(),
+            item: "Edit/Kill".into(),
+            chord_item: "Edit/Comment".into(),
+        }),
+        copy_conflict,
+        Some(AcceleratorConflict::Reserved {
+            accelerator: Accelerator::new(None, Code::F10),
+            item: "View/Menubar".into(),
+        }),
+        Some(AcceleratorConflict::Unsupported {
+            accelerator: Accelerator::new(None, Code::Sleep),
+            item: "View/Sleep".into(),
+        }),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+
+    assert_eq!(conflicts, expected);
+}
+
+#[test]
+fn test_item_path() {
+    assert_eq!(item_path("", "&File"), "File");
+    assert_eq!(item_path("File", "Save &As"), "File/Save As");
+    assert_eq!(item_path("File", "Save && Quit"), "File/Save & Quit");
+}
@@ -0,0 +1,432 @@
+use std::borrow::Cow;
+
+use keyboard_types::{Code, Key, Modifiers};
+
+use super::{
+    keys::{code_to_char, code_to_keysym_name, named_key_to_code},
+    Accelerator, AcceleratorKey, Chord,
+};
+
+/// Describes how [`AcceleratorFormatter`] displays an accelerator.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AcceleratorStyle {
+    /// The style used in macOS menus, modifiers are displayed as glyphs
+    /// in the order `⌃⌥⇧⌘` without a separator, for example `⇧⌘S`.
+    MacOs,
+    /// The style used in Windows menus, for example `Ctrl+Shift+S`.
+    Windows,
+    /// The style used by gtk in GNOME menus, for example `Shift+Ctrl+S`.
+    Gnome,
+}
+
+impl AcceleratorStyle {
+    /// Returns the style used by the menus of the current platform.
+    pub fn native() -> Self {
+        #[cfg(target_os = "macos")]
+        return Self::MacOs;
+        #[cfg(target_os = "windows")]
+        return Self::Windows;
+        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        return Self::Gnome;
+    }
+}
+
+/// The names displayed for each modifier by [`AcceleratorFormatter`],
+/// can be used to localize the modifiers, for example `Strg` instead of `Ctrl` in German.
+///
+/// # Example
+///
+/// ```
+/// # use muda::accelerator::{Accelerator, AcceleratorFormatter, AcceleratorStyle, ModifierNames};
+/// #
+/// let formatter = AcceleratorFormatter::new(AcceleratorStyle::Windows).modifier_names(ModifierNames {
+///     control: "Strg".into(),
+///     shift: "Umschalt".into(),
+///     ..ModifierNames::for_style(AcceleratorStyle::Windows)
+/// });
+/// let accelerator: Accelerator = "Ctrl+Shift+S".parse().unwrap();
+/// assert_eq!(formatter.format(&accelerator), "Strg+Umschalt+S");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ModifierNames {
+    /// The name of [`Modifiers::SHIFT`].
+    pub shift: String,
+    /// The name of [`Modifiers::CONTROL`].
+    pub control: String,
+    /// The name of [`Modifiers::ALT`].
+    pub alt: String,
+    /// The name of [`Modifiers::SUPER`].
+    pub super_key: String,
+    /// The name of [`Modifiers::ALT_GRAPH`].
+    pub alt_graph: String,
+    /// The name of [`Modifiers::HYPER`].
+    pub hyper: String,
+    /// The prefix of modifiers restricted to their left key, see [`Accelerator::with_sides`].
+    pub left: String,
+    /// The prefix of modifiers restricted to their right key, see [`Accelerator::with_sides`].
+    pub right: String,
+}
+
+impl ModifierNames {
+    /// Returns the default, english, modifier names for `style`.
+    pub fn for_style(style: AcceleratorStyle) -> Self {
+        let (shift, control, alt, super_key) = match style {
+            AcceleratorStyle::MacOs => ("⇧", "⌃", "⌥", "⌘"),
+            AcceleratorStyle::Windows => ("Shift", "Ctrl", "Alt", "Windows"),
+            AcceleratorStyle::Gnome => ("Shift", "Ctrl", "Alt", "Super"),
+        };
+        // macOS has no AltGr key, Option is used to type alternative characters instead.
+        let (alt_graph, hyper, left, right) = match style {
+            AcceleratorStyle::MacOs => ("⌥", "Hyper", "Left", "Right"),
+            AcceleratorStyle::Windows | AcceleratorStyle::Gnome => {
+                ("AltGr", "Hyper", "Left ", "Right ")
+            }
+        };
+        Self {
+            shift: shift.into(),
+            control: control.into(),
+            alt: alt.into(),
+            super_key: super_key.into(),
+            alt_graph: alt_graph.into(),
+            hyper: hyper.into(),
+            left: left.into(),
+            right: right.into(),
+        }
+    }
+}
+
+/// Formats accelerators for display in a specific [`AcceleratorStyle`],
+/// producing the same text the native menus display.
+///
+/// Unlike the [`Display`](std::fmt::Display) implementation of [`Accelerator`],
+/// the output of this formatter is meant for humans and can't be parsed back.
+///
+/// # Example
+///
+/// ```
+/// # use muda::accelerator::{Accelerator, AcceleratorFormatter, AcceleratorStyle};
+/// #
+/// let accelerator: Accelerator = "Ctrl+Shift+S".parse().unwrap();
+/// let format = |style| AcceleratorFormatter::new(style).format(&accelerator);
+/// assert_eq!(format(AcceleratorStyle::MacOs), "⌃⇧S");
+/// assert_eq!(format(AcceleratorStyle::Windows), "Ctrl+Shift+S");
+/// assert_eq!(format(AcceleratorStyle::Gnome), "Shift+Ctrl+S");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AcceleratorFormatter {
+    style: AcceleratorStyle,
+    modifier_names: ModifierNames,
+}
+
+impl AcceleratorFormatter {
+    /// Creates a new formatter for `style` with the default modifier names of that style.
+    pub fn new(style: AcceleratorStyle) -> Self {
+        Self {
+            style,
+            modifier_names: ModifierNames::for_style(style),
+        }
+    }
+
+    /// Sets the names displayed for the modifiers.
+    pub fn modifier_names(mut self, modifier_names: ModifierNames) -> Self {
+        self.modifier_names = modifier_names;
+        self
+    }
+
+    /// Returns the style of this formatter.
+    pub fn style(&self) -> AcceleratorStyle {
+        self.style
+    }
+
+    /// Formats a single accelerator.
+    pub fn format(&self, accelerator: &Accelerator) -> String {
+        let mods = accelerator.canonical_mods();
+        let names = &self.modifier_names;
+        let (order, separator) = match self.style {
+            AcceleratorStyle::MacOs => (
+                [
+                    (Modifiers::CONTROL, &names.control),
+                    (Modifiers::ALT, &names.alt),
+                    (Modifiers::ALT_GRAPH, &names.alt_graph),
+                    (Modifiers::SHIFT, &names.shift),
+                    (Modifiers::SUPER, &names.super_key),
+                    (Modifiers::HYPER, &names.hyper),
+                ],
+                "",
+            ),
+            AcceleratorStyle::Windows => (
+                [
+                    (Modifiers::CONTROL, &names.control),
+                    (Modifiers::SHIFT, &names.shift),
+                    (Modifiers::ALT, &names.alt),
+                    (Modifiers::ALT_GRAPH, &names.alt_graph),
+                    (Modifiers::SUPER, &names.super_key),
+                    (Modifiers::HYPER, &names.hyper),
+                ],
+                "+",
+            ),
+            AcceleratorStyle::Gnome => (
+                [
+                    (Modifiers::SHIFT, &names.shift),
+                    (Modifiers::CONTROL, &names.control),
+                    (Modifiers::ALT, &names.alt),
+                    (Modifiers::ALT_GRAPH, &names.alt_graph),
+                    (Modifiers::SUPER, &names.super_key),
+                    (Modifiers::HYPER, &names.hyper),
+                ],
+                "+",
+            ),
+        };
+
+        let sides = accelerator.sides();
+        let mut result = String::new();
+        // modifiers can share a name, such as Alt and AltGr on macOS
+        let mut shown = Vec::new();
+        for (modifier, name) in order {
+            if sides.left.contains(modifier) {
+                result.push_str(&names.left);
+            }
+            if sides.right.contains(modifier) {
+                result.push_str(&names.right);
+            }
+            if mods.contains(modifier) && !shown.contains(&name) {
+                result.push_str(name);
+                result.push_str(separator);
+                shown.push(name);
+            }
+        }
+        result.push_str(&self.key_name(&accelerator.key()));
+        result
+    }
+
+    /// Formats all strokes of a chord.
+    pub fn format_chord(&self, chord: &Chord) -> String {
+        let separator = match self.style {
+            AcceleratorStyle::MacOs => " ",
+            AcceleratorStyle::Windows | AcceleratorStyle::Gnome => ", ",
+        };
+        chord
+            .strokes()
+            .iter()
+            .map(|stroke| self.format(stroke))
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+
+    fn key_name(&self, key: &AcceleratorKey) -> Cow<'static, str> {
+        match key {
+            AcceleratorKey::Physical(code) => self.code_name(code),
+            AcceleratorKey::Logical(Key::Character(c)) => character_name(c),
+            // Named keys are displayed like the physical key producing them.
+            AcceleratorKey::Logical(key) => match named_key_to_code(key) {
+                Some(code) => self.code_name(&code),
+                None => key.to_string().into(),
+            },
+        }
+    }
+
+    fn code_name(&self, key: &Code) -> Cow<'static, str> {
+        if let Some(name) = self.modifier_key_name(key) {
+            return name.to_owned().into();
+        }
+        match self.style {
+            AcceleratorStyle::MacOs => match key {
+                Code::Enter => "↩".into(),
+                Code::NumpadEnter => "⌤".into(),
+                Code::Tab => "⇥".into(),
+                Code::Backspace => "⌫".into(),
+                Code::Delete => "⌦".into(),
+                Code::Escape => "⎋".into(),
+                Code::CapsLock => "⇪".into(),
+                Code::ArrowUp => "↑".into(),
+                Code::ArrowDown => "↓".into(),
+                Code::ArrowLeft => "←".into(),
+                Code::ArrowRight => "→".into(),
+                Code::PageUp => "⇞".into(),
+                Code::PageDown => "⇟".into(),
+                Code::Home => "↖".into(),
+                Code::End => "↘".into(),
+                _ => self.common_key_name(key),
+            },
+            // These names match LibreOffice.
+            AcceleratorStyle::Windows => match key {
+                Code::Escape => "Esc".into(),
+                Code::Delete => "Del".into(),
+                Code::Insert => "Ins".into(),
+                Code::PageUp => "PgUp".into(),
+                Code::PageDown => "PgDn".into(),
+                Code::ArrowLeft => "Left".into(),
+                Code::ArrowRight => "Right".into(),
+                Code::ArrowUp => "Up".into(),
+                Code::ArrowDown => "Down".into(),
+                Code::NumpadEnter => "Num Enter".into(),
+                _ => match code_to_keysym_name(key).filter(|name| name.starts_with("KP_")) {
+                    Some(name) if keysym_to_char(name).is_some() => {
+                        format!("Num {}", self.common_key_name(key)).into()
+                    }
+                    _ => self.common_key_name(key),
+                },
+            },
+            // gtk spells out the backslash and displays the character typed by other keys,
+            // or their keysym name with underscores replaced by spaces if they don't type one.
+            AcceleratorStyle::Gnome if *key == Code::Backslash => "Backslash".into(),
+            AcceleratorStyle::Gnome => match code_to_keysym_name(key) {
+                Some(name) if code_to_char(key).is_none() && keysym_to_char(name).is_none() => {
+                    name.replace('_', " ").into()
+                }
+                _ => self.common_key_name(key),
+            },
+        }
+    }
+
+    fn common_key_name(&self, key: &Code) -> Cow<'static, str> {
+        let c = code_to_char(key).or_else(|| code_to_keysym_name(key).and_then(keysym_to_char));
+        match c {
+            Some(' ') => "Space".into(),
+            Some(c) => c.to_uppercase().to_string().into(),
+            None => key.to_string().into(),
+        }
+    }
+
+    /// Modifier keys pressed on their own are displayed with the name of their modifier.
+    fn modifier_key_name(&self, key: &Code) -> Option<&str> {
+        let names = &self.modifier_names;
+        Some(match key {
+            Code::ShiftLeft | Code::ShiftRight => &names.shift,
+            Code::ControlLeft | Code::ControlRight => &names.control,
+            Code::AltLeft | Code::AltRight => &names.alt,
+            Code::MetaLeft | Code::MetaRight | Code::Super => &names.super_key,
+            Code::Hyper => &names.hyper,
+            _ => return None,
+        })
+    }
+}
+
+/// The printable character of the keysym `name`, like `gdk_keyval_to_unicode` for the keysyms
+/// of [`code_to_keysym_name`] which don't match the character of [`code_to_char`].
+fn keysym_to_char(name: &str) -> Option<char> {
+    Some(match name {
+        "KP_0" => '0',
+        "KP_1" => '1',
+        "KP_2" => '2',
+        "KP_3" => '3',
+        "KP_4" => '4',
+        "KP_5" => '5',
+        "KP_6" => '6',
+        "KP_7" => '7',
+        "KP_8" => '8',
+        "KP_9" => '9',
+        "KP_Add" => '+',
+        "KP_Subtract" => '-',
+        "KP_Multiply" => '*',
+        "KP_Divide" => '/',
+        "KP_Decimal" => '.',
+        "KP_Separator" => ',',
+        "KP_Equal" => '=',
+        "less" => '<',
+        "yen" => '¥',
+        _ => return None,
+    })
+}
+
+fn character_name(c: &str) -> Cow<'static, str> {
+    match c {
+        " " => "Space".into(),
+        c => c.to_uppercase().into(),
+    }
+}
+
+impl Accelerator {
+    /// Formats this accelerator for display using `style`,
+    /// see [`AcceleratorFormatter`] for more options.
+    pub fn to_display_string(&self, style: AcceleratorStyle) -> String {
+        AcceleratorFormatter::new(style).format(self)
+    }
+}
+
+#[test]
+fn test_format_accelerator() {
+    let cases = [
+        ("Ctrl+Shift+KeyS", "⌃⇧S", "Ctrl+Shift+S", "Shift+Ctrl+S"),
+        (
+            "Super+Shift+KeyS",
+            "⇧⌘S",
+            "Shift+Windows+S",
+            "Shift+Super+S",
+        ),
+        (
+            "Shift+Alt+Ctrl+Super+Digit5",
+            "⌃⌥⇧⌘5",
+            "Ctrl+Shift+Alt+Windows+5",
+            "Shift+Ctrl+Alt+Super+5",
+        ),
+        ("Alt+BracketLeft", "⌥[", "Alt+[", "Alt+["),
+        ("Ctrl+Space", "⌃Space", "Ctrl+Space", "Ctrl+Space"),
+        ("Ctrl+\"z\"", "⌃Z", "Ctrl+Z", "Ctrl+Z"),
+        ("Ctrl+\"ö\"", "⌃Ö", "Ctrl+Ö", "Ctrl+Ö"),
+        ("Shift+\"Enter\"", "⇧↩", "Shift+Enter", "Shift+Return"),
+        ("Enter", "↩", "Enter", "Return"),
+        ("Shift+Tab", "⇧⇥", "Shift+Tab", "Shift+Tab"),
+        ("Escape", "⎋", "Esc", "Escape"),
+        ("Ctrl+PageUp", "⌃⇞", "Ctrl+PgUp", "Ctrl+Page Up"),
+        ("Alt+ArrowLeft", "⌥←", "Alt+Left", "Alt+Left"),
+        ("Backspace", "⌫", "Backspace", "BackSpace"),
+        ("Super+Delete", "⌘⌦", "Windows+Del", "Super+Delete"),
+        ("F12", "F12", "F12", "F12"),
+        ("RightCtrl+KeyS", "Right⌃S", "Right Ctrl+S", "Right Ctrl+S"),
+        ("AltGr+KeyE", "⌥E", "AltGr+E", "AltGr+E"),
+        ("Alt+AltGr+KeyE", "⌥E", "Alt+AltGr+E", "Alt+AltGr+E"),
+        ("Shift+Super", "⇧⌘", "Shift+Windows", "Shift+Super"),
+        ("Ctrl+MetaRight", "⌃⌘", "Ctrl+Windows", "Ctrl+Super"),
+        (
+            "Hyper+Shift+KeyH",
+            "⇧HyperH",
+            "Shift+Hyper+H",
+            "Shift+Hyper+H",
+        ),
+        ("Ctrl+NumpadAdd", "⌃+", "Ctrl+Num +", "Ctrl++"),
+        ("Shift+Numpad1", "⇧1", "Shift+Num 1", "Shift+1"),
+        ("NumpadEnter", "⌤", "Num Enter", "KP Enter"),
+        ("Ctrl+IntlBackslash", "⌃<", "Ctrl+<", "Ctrl+<"),
+        ("Ctrl+Backslash", "⌃\\", "Ctrl+\\", "Ctrl+Backslash"),
+    ];
+
+    for (string, macos, windows, gnome) in cases {
+        let accelerator: Accelerator = string.parse().unwrap();
+        assert_eq!(
+            accelerator.to_display_string(AcceleratorStyle::MacOs),
+            macos
+        );
+        assert_eq!(
+            accelerator.to_display_string(AcceleratorStyle::Windows),
+            windows
+        );
+        assert_eq!(
+            accelerator.to_display_string(AcceleratorStyle::Gnome),
+            gnome
+        );
+    }
+}
+
+#[test]
+fn test_format_localized_and_chords() {
+    let german = AcceleratorFormatter::new(AcceleratorStyle::Gnome).modifier_names(ModifierNames {
+        shift: "Umschalt".into(),
+        control: "Strg".into(),
+        ..ModifierNames::for_style(AcceleratorStyle::Gnome)
+    });
+    let accelerator: Accelerator = "Ctrl+Shift+KeyZ".parse().unwrap();
+    assert_eq!(german.format(&accelerator), "Umschalt+Strg+Z");
+
+    let chord: Chord = "Ctrl+KeyK Ctrl+KeyC".parse().unwrap();
+    assert_eq!(
+        AcceleratorFormatter::new(AcceleratorStyle::MacOs).format_chord(&chord),
+        "⌃K ⌃C"
+    );
+    assert_eq!(
+        AcceleratorFormatter::new(AcceleratorStyle::Windows).format_chord(&chord),
+        "Ctrl+K, Ctrl+C"
+    );
+    assert_eq!(german.format_chord(&chord), "Strg+K, Strg+C");
+}
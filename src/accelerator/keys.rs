@@ -0,0 +1,344 @@
+//! Platform-independent key tables shared by the platform implementations
+//! and the [`AcceleratorFormatter`](super::AcceleratorFormatter).
+
+use keyboard_types::{Code, Key};
+
+use super::ALL_CODES;
+
+/// Returns the character a printable key produces on a US layout without any modifiers.
+pub(crate) fn code_to_char(code: &Code) -> Option<char> {
+    Some(match code {
+        Code::KeyA => 'a',
+        Code::KeyB => 'b',
+        Code::KeyC => 'c',
+        Code::KeyD => 'd',
+        Code::KeyE => 'e',
+        Code::KeyF => 'f',
+        Code::KeyG => 'g',
+        Code::KeyH => 'h',
+        Code::KeyI => 'i',
+        Code::KeyJ => 'j',
+        Code::KeyK => 'k',
+        Code::KeyL => 'l',
+        Code::KeyM => 'm',
+        Code::KeyN => 'n',
+        Code::KeyO => 'o',
+        Code::KeyP => 'p',
+        Code::KeyQ => 'q',
+        Code::KeyR => 'r',
+        Code::KeyS => 's',
+        Code::KeyT => 't',
+        Code::KeyU => 'u',
+        Code::KeyV => 'v',
+        Code::KeyW => 'w',
+        Code::KeyX => 'x',
+        Code::KeyY => 'y',
+        Code::KeyZ => 'z',
+        Code::Digit0 => '0',
+        Code::Digit1 => '1',
+        Code::Digit2 => '2',
+        Code::Digit3 => '3',
+        Code::Digit4 => '4',
+        Code::Digit5 => '5',
+        Code::Digit6 => '6',
+        Code::Digit7 => '7',
+        Code::Digit8 => '8',
+        Code::Digit9 => '9',
+        Code::Comma => ',',
+        Code::Minus => '-',
+        Code::Period => '.',
+        Code::Space => ' ',
+        Code::Equal => '=',
+        Code::Semicolon => ';',
+        Code::Slash => '/',
+        Code::Backslash => '\\',
+        Code::Quote => '\'',
+        Code::Backquote => '`',
+        Code::BracketLeft => '[',
+        Code::BracketRight => ']',
+        _ => return None,
+    })
+}
+
+/// Returns the name of the X11/gdk keysym of a non-printable key,
+/// for printable keys use [`code_to_char`] instead.
+///
+/// Keys whose keysym depends on the keyboard layout use the one from a US or Japanese layout.
+pub(crate) fn code_to_keysym_name(code: &Code) -> Option<&'static str> {
+    Some(match code {
+        Code::Escape => "Escape",
+        Code::Backspace => "BackSpace",
+
+        Code::Tab => "Tab",
+        Code::Enter => "Return",
+
+        Code::ControlLeft => "Control_L",
+        Code::AltLeft => "Alt_L",
+        Code::ShiftLeft => "Shift_L",
+        Code::MetaLeft => "Super_L",
+
+        Code::ControlRight => "Control_R",
+        Code::AltRight => "Alt_R",
+        Code::ShiftRight => "Shift_R",
+        Code::MetaRight => "Super_R",
+
+        Code::Super => "Super_L",
+        Code::Hyper => "Hyper_L",
+
+        Code::IntlBackslash => "less",
+        Code::IntlRo => "backslash",
+        Code::IntlYen => "yen",
+
+        Code::Convert => "Henkan",
+        Code::NonConvert => "Muhenkan",
+        Code::KanaMode => "Hiragana_Katakana",
+        Code::Lang1 => "Hangul",
+        Code::Lang2 => "Hangul_Hanja",
+        Code::Lang3 => "Katakana",
+        Code::Lang4 => "Hiragana",
+        Code::Lang5 => "Zenkaku_Hankaku",
+        Code::Hiragana => "Hiragana",
+        Code::Katakana => "Katakana",
+
+        Code::CapsLock => "Caps_Lock",
+        Code::F1 => "F1",
+        Code::F2 => "F2",
+        Code::F3 => "F3",
+        Code::F4 => "F4",
+        Code::F5 => "F5",
+        Code::F6 => "F6",
+        Code::F7 => "F7",
+        Code::F8 => "F8",
+        Code::F9 => "F9",
+        Code::F10 => "F10",
+        Code::F11 => "F11",
+        Code::F12 => "F12",
+        Code::F13 => "F13",
+        Code::F14 => "F14",
+        Code::F15 => "F15",
+        Code::F16 => "F16",
+        Code::F17 => "F17",
+        Code::F18 => "F18",
+        Code::F19 => "F19",
+        Code::F20 => "F20",
+        Code::F21 => "F21",
+        Code::F22 => "F22",
+        Code::F23 => "F23",
+        Code::F24 => "F24",
+
+        Code::PrintScreen => "Print",
+        Code::ScrollLock => "Scroll_Lock",
+        // Pause/Break not audio.
+        Code::Pause => "Pause",
+
+        Code::Insert => "Insert",
+        Code::Delete => "Delete",
+        Code::Home => "Home",
+        Code::End => "End",
+        Code::PageUp => "Page_Up",
+        Code::PageDown => "Page_Down",
+        Code::Help => "Help",
+
+        Code::NumLock => "Num_Lock",
+        Code::Numpad0 => "KP_0",
+        Code::Numpad1 => "KP_1",
+        Code::Numpad2 => "KP_2",
+        Code::Numpad3 => "KP_3",
+        Code::Numpad4 => "KP_4",
+        Code::Numpad5 => "KP_5",
+        Code::Numpad6 => "KP_6",
+        Code::Numpad7 => "KP_7",
+        Code::Numpad8 => "KP_8",
+        Code::Numpad9 => "KP_9",
+        Code::NumpadAdd => "KP_Add",
+        Code::NumpadSubtract => "KP_Subtract",
+        Code::NumpadMultiply => "KP_Multiply",
+        Code::NumpadDivide => "KP_Divide",
+        Code::NumpadDecimal => "KP_Decimal",
+        Code::NumpadComma => "KP_Separator",
+        Code::NumpadEnter => "KP_Enter",
+        Code::NumpadEqual => "KP_Equal",
+        Code::NumpadClear => "Clear",
+
+        Code::ArrowUp => "Up",
+        Code::ArrowDown => "Down",
+        Code::ArrowLeft => "Left",
+        Code::ArrowRight => "Right",
+
+        Code::ContextMenu => "Menu",
+        Code::Again => "Redo",
+        Code::Undo => "Undo",
+        Code::Find => "Find",
+        Code::Select => "Select",
+        Code::Copy => "XF86Copy",
+        Code::Cut => "XF86Cut",
+        Code::Paste => "XF86Paste",
+        Code::Open => "XF86Open",
+
+        Code::BrowserBack => "XF86Back",
+        Code::BrowserForward => "XF86Forward",
+        Code::BrowserRefresh => "XF86Refresh",
+        Code::BrowserStop => "XF86Stop",
+        Code::BrowserSearch => "XF86Search",
+        Code::BrowserFavorites => "XF86Favorites",
+        Code::BrowserHome => "XF86HomePage",
+
+        Code::AudioVolumeMute => "XF86AudioMute",
+        Code::AudioVolumeDown => "XF86AudioLowerVolume",
+        Code::AudioVolumeUp => "XF86AudioRaiseVolume",
+        Code::MicrophoneMuteToggle => "XF86AudioMicMute",
+        Code::MediaPlayPause => "XF86AudioPlay",
+        Code::MediaPlay => "XF86AudioPlay",
+        Code::MediaPause => "XF86AudioPause",
+        Code::MediaStop => "XF86AudioStop",
+        Code::MediaRecord => "XF86AudioRecord",
+        Code::MediaTrackNext => "XF86AudioNext",
+        Code::MediaTrackPrevious => "XF86AudioPrev",
+        Code::MediaFastForward => "XF86AudioForward",
+        Code::MediaRewind => "XF86AudioRewind",
+        Code::MediaSelect => "XF86AudioMedia",
+        Code::Eject => "XF86Eject",
+
+        Code::LaunchMail => "XF86Mail",
+        Code::MailForward => "XF86MailForward",
+        Code::MailReply => "XF86Reply",
+        Code::MailSend => "XF86Send",
+        Code::LaunchApp1 => "XF86MyComputer",
+        Code::LaunchApp2 => "XF86Calculator",
+        Code::LaunchScreenSaver => "XF86ScreenSaver",
+
+        Code::BrightnessDown => "XF86MonBrightnessDown",
+        Code::BrightnessUp => "XF86MonBrightnessUp",
+        Code::DisplayToggleIntExt => "XF86Display",
+
+        Code::Power => "XF86PowerOff",
+        Code::Sleep => "XF86Sleep",
+        Code::Suspend => "XF86Suspend",
+        Code::WakeUp => "XF86WakeUp",
+        _ => return None,
+    })
+}
+
+/// Returns the named [`Key`] a non-printable key produces regardless of the keyboard layout,
+/// for printable keys use [`code_to_char`] instead.
+pub(crate) fn code_to_named_key(code: &Code) -> Option<Key> {
+    Some(match code {
+        Code::Escape => Key::Escape,
+        Code::Backspace => Key::Backspace,
+        Code::Tab => Key::Tab,
+        Code::Enter | Code::NumpadEnter => Key::Enter,
+
+        Code::ControlLeft | Code::ControlRight => Key::Control,
+        Code::AltLeft | Code::AltRight => Key::Alt,
+        Code::ShiftLeft | Code::ShiftRight => Key::Shift,
+        Code::MetaLeft | Code::MetaRight => Key::Meta,
+        Code::Super => Key::Super,
+        Code::Hyper => Key::Hyper,
+        Code::Fn => Key::Fn,
+        Code::FnLock => Key::FnLock,
+
+        Code::CapsLock => Key::CapsLock,
+        Code::F1 => Key::F1,
+        Code::F2 => Key::F2,
+        Code::F3 => Key::F3,
+        Code::F4 => Key::F4,
+        Code::F5 => Key::F5,
+        Code::F6 => Key::F6,
+        Code::F7 => Key::F7,
+        Code::F8 => Key::F8,
+        Code::F9 => Key::F9,
+        Code::F10 => Key::F10,
+        Code::F11 => Key::F11,
+        Code::F12 => Key::F12,
+        Code::F13 => Key::F13,
+        Code::F14 => Key::F14,
+        Code::F15 => Key::F15,
+        Code::F16 => Key::F16,
+        Code::F17 => Key::F17,
+        Code::F18 => Key::F18,
+        Code::F19 => Key::F19,
+        Code::F20 => Key::F20,
+        Code::F21 => Key::F21,
+        Code::F22 => Key::F22,
+        Code::F23 => Key::F23,
+        Code::F24 => Key::F24,
+
+        Code::PrintScreen => Key::PrintScreen,
+        Code::ScrollLock => Key::ScrollLock,
+        Code::Pause => Key::Pause,
+
+        Code::Insert => Key::Insert,
+        Code::Delete => Key::Delete,
+        Code::Home => Key::Home,
+        Code::End => Key::End,
+        Code::PageUp => Key::PageUp,
+        Code::PageDown => Key::PageDown,
+
+        Code::NumLock => Key::NumLock,
+
+        Code::ArrowUp => Key::ArrowUp,
+        Code::ArrowDown => Key::ArrowDown,
+        Code::ArrowLeft => Key::ArrowLeft,
+        Code::ArrowRight => Key::ArrowRight,
+
+        Code::ContextMenu => Key::ContextMenu,
+        Code::WakeUp => Key::WakeUp,
+        _ => return None,
+    })
+}
+
+/// Returns the [`Code`] of the key that produces a named [`Key`], preferring
+/// the left and non-numpad variants when more than one key produces it.
+pub(crate) fn named_key_to_code(key: &Key) -> Option<Code> {
+    ALL_CODES
+        .iter()
+        .find(|code| code_to_named_key(code).as_ref() == Some(key))
+        .copied()
+}
+
+#[test]
+fn test_keysym_coverage() {
+    // These keys don't have a keysym.
+    let unmapped = [
+        Code::Fn,
+        Code::FnLock,
+        Code::Turbo,
+        Code::Abort,
+        Code::Resume,
+        Code::Props,
+        Code::NumpadBackspace,
+        Code::NumpadClearEntry,
+        Code::NumpadHash,
+        Code::NumpadMemoryAdd,
+        Code::NumpadMemoryClear,
+        Code::NumpadMemoryRecall,
+        Code::NumpadMemoryStore,
+        Code::NumpadMemorySubtract,
+        Code::NumpadParenLeft,
+        Code::NumpadParenRight,
+        Code::NumpadStar,
+        Code::KeyboardLayoutSelect,
+        Code::LaunchAssistant,
+        Code::LaunchControlPanel,
+        Code::PrivacyScreenToggle,
+        Code::SelectTask,
+        Code::ShowAllWindows,
+        Code::ZoomToggle,
+    ];
+
+    for code in ALL_CODES {
+        let char = code_to_char(code);
+        let keysym = code_to_keysym_name(code);
+        assert!(
+            char.is_none() || keysym.is_none(),
+            "{:?} has both a char and a keysym",
+            code
+        );
+        assert_eq!(
+            char.is_some() || keysym.is_some(),
+            !unmapped.contains(code),
+            "unexpected keysym coverage for {:?}",
+            code
+        );
+    }
+}
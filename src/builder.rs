@@ -0,0 +1,422 @@
+//! Builders describing a menu declaratively, as a tree of submenus and items
+//! whose options are set by name instead of by position.
+//!
+//! ```no_run
+//! # use muda::{
+//! #     builder::{CheckMenuItemBuilder, MenuBuilder, MenuItemBuilder, SubmenuBuilder},
+//! #     NativeMenuItem,
+//! # };
+//! #
+//! let menu = MenuBuilder::new()
+//!     .submenu(
+//!         SubmenuBuilder::new("&File")
+//!             .item(
+//!                 MenuItemBuilder::new("&Save")
+//!                     .accelerator("CmdOrCtrl+S")
+//!                     .id("file.save"),
+//!             )
+//!             .check_item(CheckMenuItemBuilder::new("&Autosave").checked(true))
+//!             .separator()
+//!             .native_item(NativeMenuItem::Quit),
+//!     )
+//!     .submenu(SubmenuBuilder::new("&Edit").enabled(false))
+//!     .build()
+//!     .unwrap();
+//! ```
+//!
+//! Builders create the same [`Menu`], [`Submenu`] and item handles as the `add_*` methods,
+//! and the `menu!` macro of the `muda-macros` crate expands to them.
+
+use crate::{
+    accelerator::{Accelerator, AcceleratorParseError},
+    icon::Icon,
+    CheckMenuItem, Menu, MenuId, MenuItem, NativeMenuItem, Submenu,
+};
+
+/// The values the `accelerator` methods of the builders accept,
+/// an [`Accelerator`] or a string parsed with [`FromStr`](std::str::FromStr).
+pub trait IntoAccelerator {
+    /// Converts this value into an accelerator, fails if a string can't be parsed.
+    fn into_accelerator(self) -> Result<Accelerator, AcceleratorParseError>;
+}
+
+impl IntoAccelerator for Accelerator {
+    fn into_accelerator(self) -> Result<Accelerator, AcceleratorParseError> {
+        Ok(self)
+    }
+}
+
+impl IntoAccelerator for &str {
+    fn into_accelerator(self) -> Result<Accelerator, AcceleratorParseError> {
+        self.parse()
+    }
+}
+
+impl IntoAccelerator for String {
+    fn into_accelerator(self) -> Result<Accelerator, AcceleratorParseError> {
+        self.parse()
+    }
+}
+
+/// Builds a [`Menu`] from its submenus.
+#[derive(Default)]
+pub struct MenuBuilder {
+    submenus: Vec<SubmenuBuilder>,
+}
+
+impl MenuBuilder {
+    /// Creates a builder for a menu without submenus.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a submenu at the end of the menu.
+    pub fn submenu(mut self, submenu: SubmenuBuilder) -> Self {
+        self.submenus.push(submenu);
+        self
+    }
+
+    /// Creates the menu, fails if an accelerator of an item couldn't be parsed,
+    /// in which case nothing is created.
+    pub fn build(self) -> Result<Menu, AcceleratorParseError> {
+        for submenu in &self.submenus {
+            submenu.check()?;
+        }
+        let mut menu = Menu::new();
+        for builder in self.submenus {
+            let submenu = menu.add_submenu(&builder.label, builder.enabled);
+            builder.fill(submenu);
+        }
+        Ok(menu)
+    }
+}
+
+enum Child {
+    Item(MenuItemBuilder),
+    CheckItem(CheckMenuItemBuilder),
+    Submenu(SubmenuBuilder),
+    NativeItem(NativeMenuItem),
+}
+
+/// Builds a [`Submenu`] and its items.
+pub struct SubmenuBuilder {
+    label: String,
+    enabled: bool,
+    icon: Option<Icon>,
+    tooltip: Option<String>,
+    children: Vec<Child>,
+}
+
+impl SubmenuBuilder {
+    /// Creates a builder for an enabled submenu without items.
+    pub fn new<S: Into<String>>(label: S) -> Self {
+        Self {
+            label: label.into(),
+            enabled: true,
+            icon: None,
+            tooltip: None,
+            children: Vec::new(),
+        }
+    }
+
+    /// Sets whether the submenu is enabled, see [`Submenu::set_enabled`].
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Sets the icon of the submenu, see [`Submenu::set_icon`].
+    pub fn icon(mut self, icon: Icon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// Sets the tooltip of the submenu, see [`Submenu::set_tooltip`].
+    pub fn tooltip<S: Into<String>>(mut self, tooltip: S) -> Self {
+        self.tooltip = Some(tooltip.into());
+        self
+    }
+
+    /// Adds a menu item at the end of the submenu.
+    pub fn item(mut self, item: MenuItemBuilder) -> Self {
+        self.children.push(Child::Item(item));
+        self
+    }
+
+    /// Adds a check menu item at the end of the submenu.
+    pub fn check_item(mut self, item: CheckMenuItemBuilder) -> Self {
+        self.children.push(Child::CheckItem(item));
+        self
+    }
+
+    /// Adds a nested submenu at the end of the submenu.
+    pub fn submenu(mut self, submenu: SubmenuBuilder) -> Self {
+        self.children.push(Child::Submenu(submenu));
+        self
+    }
+
+    /// Adds a native menu item at the end of the submenu.
+    pub fn native_item(mut self, item: NativeMenuItem) -> Self {
+        self.children.push(Child::NativeItem(item));
+        self
+    }
+
+    /// Adds a separator at the end of the submenu.
+    pub fn separator(self) -> Self {
+        self.native_item(NativeMenuItem::Separator)
+    }
+
+    /// Creates the submenu at the end of `parent`, fails if an accelerator of an item
+    /// couldn't be parsed, in which case nothing is created.
+    pub fn build(self, parent: &mut Submenu) -> Result<Submenu, AcceleratorParseError> {
+        self.check()?;
+        let submenu = parent.add_submenu(&self.label, self.enabled);
+        Ok(self.fill(submenu))
+    }
+
+    fn check(&self) -> Result<(), AcceleratorParseError> {
+        self.children.iter().try_for_each(|child| match child {
+            Child::Item(item) => item.check(),
+            Child::CheckItem(item) => item.check(),
+            Child::Submenu(submenu) => submenu.check(),
+            Child::NativeItem(_) => Ok(()),
+        })
+    }
+
+    /// Sets the options of `submenu`, which was created with the label and enabled state
+    /// of this builder, and adds the items to it.
+    fn fill(self, mut submenu: Submenu) -> Submenu {
+        if self.icon.is_some() {
+            let _ = submenu.set_icon(self.icon);
+        }
+        if self.tooltip.is_some() {
+            let _ = submenu.set_tooltip(self.tooltip.as_deref());
+        }
+        for child in self.children {
+            match child {
+                Child::Item(item) => {
+                    item.add_to(&mut submenu);
+                }
+                Child::CheckItem(item) => {
+                    item.add_to(&mut submenu);
+                }
+                Child::Submenu(builder) => {
+                    let nested = submenu.add_submenu(&builder.label, builder.enabled);
+                    builder.fill(nested);
+                }
+                Child::NativeItem(item) => submenu.add_native_item(item),
+            }
+        }
+        submenu
+    }
+}
+
+/// Builds a [`MenuItem`].
+pub struct MenuItemBuilder {
+    label: String,
+    id: Option<MenuId>,
+    enabled: bool,
+    accelerator: Option<Result<Accelerator, AcceleratorParseError>>,
+    icon: Option<Icon>,
+    tooltip: Option<String>,
+    help_text: Option<String>,
+}
+
+impl MenuItemBuilder {
+    /// Creates a builder for an enabled menu item without an accelerator.
+    pub fn new<S: Into<String>>(label: S) -> Self {
+        Self {
+            label: label.into(),
+            id: None,
+            enabled: true,
+            accelerator: None,
+            icon: None,
+            tooltip: None,
+            help_text: None,
+        }
+    }
+
+    /// Sets a stable id for the menu item, see [`MenuId`].
+    pub fn id<I: Into<MenuId>>(mut self, id: I) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets whether the menu item is enabled, see [`MenuItem::set_enabled`].
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Sets the accelerator of the menu item, see [`IntoAccelerator`], for example `"CmdOrCtrl+S"`.
+    ///
+    /// A string that can't be parsed makes building the item fail.
+    pub fn accelerator<A: IntoAccelerator>(mut self, accelerator: A) -> Self {
+        self.accelerator = Some(accelerator.into_accelerator());
+        self
+    }
+
+    /// Sets the icon of the menu item, see [`MenuItem::set_icon`].
+    pub fn icon(mut self, icon: Icon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// Sets the tooltip of the menu item, see [`MenuItem::set_tooltip`].
+    pub fn tooltip<S: Into<String>>(mut self, tooltip: S) -> Self {
+        self.tooltip = Some(tooltip.into());
+        self
+    }
+
+    /// Sets the help text of the menu item, see [`MenuItem::set_help_text`].
+    pub fn help_text<S: Into<String>>(mut self, help_text: S) -> Self {
+        self.help_text = Some(help_text.into());
+        self
+    }
+
+    /// Creates the menu item at the end of `parent`, fails if its accelerator couldn't be parsed.
+    pub fn build(self, parent: &mut Submenu) -> Result<MenuItem, AcceleratorParseError> {
+        self.check()?;
+        Ok(self.add_to(parent))
+    }
+
+    fn check(&self) -> Result<(), AcceleratorParseError> {
+        match &self.accelerator {
+            Some(Err(e)) => Err(e.clone()),
+            _ => Ok(()),
+        }
+    }
+
+    fn add_to(self, parent: &mut Submenu) -> MenuItem {
+        let accelerator = self.accelerator.and_then(Result::ok);
+        let mut item = match self.id {
+            Some(id) => parent.add_item_with_id(id, &self.label, self.enabled, accelerator),
+            None => parent.add_item(&self.label, self.enabled, accelerator),
+        };
+        if self.icon.is_some() {
+            let _ = item.set_icon(self.icon);
+        }
+        if self.tooltip.is_some() {
+            let _ = item.set_tooltip(self.tooltip.as_deref());
+        }
+        if self.help_text.is_some() {
+            let _ = item.set_help_text(self.help_text.as_deref());
+        }
+        item
+    }
+}
+
+/// Builds a [`CheckMenuItem`].
+pub struct CheckMenuItemBuilder {
+    item: MenuItemBuilder,
+    checked: bool,
+}
+
+impl CheckMenuItemBuilder {
+    /// Creates a builder for an enabled and unchecked check menu item without an accelerator.
+    pub fn new<S: Into<String>>(label: S) -> Self {
+        Self {
+            item: MenuItemBuilder::new(label),
+            checked: false,
+        }
+    }
+
+    /// Sets whether the check menu item is checked, see [`CheckMenuItem::set_checked`].
+    pub fn checked(mut self, checked: bool) -> Self {
+        self.checked = checked;
+        self
+    }
+
+    /// Sets a stable id for the check menu item, see [`MenuId`].
+    pub fn id<I: Into<MenuId>>(mut self, id: I) -> Self {
+        self.item = self.item.id(id);
+        self
+    }
+
+    /// Sets whether the check menu item is enabled, see [`CheckMenuItem::set_enabled`].
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.item = self.item.enabled(enabled);
+        self
+    }
+
+    /// Sets the accelerator of the check menu item, see [`MenuItemBuilder::accelerator`].
+    pub fn accelerator<A: IntoAccelerator>(mut self, accelerator: A) -> Self {
+        self.item = self.item.accelerator(accelerator);
+        self
+    }
+
+    /// Sets the icon of the check menu item, see [`CheckMenuItem::set_icon`].
+    pub fn icon(mut self, icon: Icon) -> Self {
+        self.item = self.item.icon(icon);
+        self
+    }
+
+    /// Sets the tooltip of the check menu item, see [`CheckMenuItem::set_tooltip`].
+    pub fn tooltip<S: Into<String>>(mut self, tooltip: S) -> Self {
+        self.item = self.item.tooltip(tooltip);
+        self
+    }
+
+    /// Sets the help text of the check menu item, see [`CheckMenuItem::set_help_text`].
+    pub fn help_text<S: Into<String>>(mut self, help_text: S) -> Self {
+        self.item = self.item.help_text(help_text);
+        self
+    }
+
+    /// Creates the check menu item at the end of `parent`, fails if its accelerator couldn't be parsed.
+    pub fn build(self, parent: &mut Submenu) -> Result<CheckMenuItem, AcceleratorParseError> {
+        self.check()?;
+        Ok(self.add_to(parent))
+    }
+
+    fn check(&self) -> Result<(), AcceleratorParseError> {
+        self.item.check()
+    }
+
+    fn add_to(self, parent: &mut Submenu) -> CheckMenuItem {
+        let item = self.item;
+        let accelerator = item.accelerator.and_then(Result::ok);
+        let mut check_item = match item.id {
+            Some(id) => parent.add_check_item_with_id(
+                id,
+                &item.label,
+                item.enabled,
+                self.checked,
+                accelerator,
+            ),
+            None => parent.add_check_item(&item.label, item.enabled, self.checked, accelerator),
+        };
+        if item.icon.is_some() {
+            let _ = check_item.set_icon(item.icon);
+        }
+        if item.tooltip.is_some() {
+            let _ = check_item.set_tooltip(item.tooltip.as_deref());
+        }
+        if item.help_text.is_some() {
+            let _ = check_item.set_help_text(item.help_text.as_deref());
+        }
+        check_item
+    }
+}
+
+#[test]
+fn test_invalid_accelerator() {
+    use crate::accelerator::AcceleratorParseErrorKind;
+
+    let builder = MenuBuilder::new().submenu(
+        SubmenuBuilder::new("&File")
+            .item(MenuItemBuilder::new("&Open").accelerator("CmdOrCtrl+O"))
+            .submenu(
+                SubmenuBuilder::new("&Export")
+                    .check_item(CheckMenuItemBuilder::new("As &PNG").accelerator("Ctrl+Shft+KeyP")),
+            ),
+    );
+    // the accelerators are checked before anything is created
+    let error = builder.build().err().unwrap();
+    assert_eq!(error.kind(), AcceleratorParseErrorKind::UnknownKey);
+    assert_eq!(error.span(), 5..9);
+
+    let accelerator: Accelerator = "Ctrl+KeyS".parse().unwrap();
+    let item = MenuItemBuilder::new("&Save").accelerator(accelerator.clone());
+    assert!(matches!(item.accelerator, Some(Ok(a)) if a == accelerator));
+}
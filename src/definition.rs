@@ -0,0 +1,473 @@
+//! Menu definitions describe a menu as data, in TOML or JSON, so it can be edited without
+//! touching Rust, and are loaded using [`Menu::from_definition`].
+//!
+//! A definition has a list of `submenus`, each with a `label` and a list of `items`.
+//! Items are tables whose `type` is one of:
+//!
+//! - `item`: A [`MenuItem`](crate::MenuItem) with a `label`.
+//! - `check`: A [`CheckMenuItem`](crate::CheckMenuItem) with a `label` and a `checked` state, `false` by default.
+//! - `submenu`: A nested submenu with a `label` and its own `items`.
+//! - `native`: A [`NativeMenuItem`] in `item`, named by its variant, for example `"Quit"`.
+//! - `separator`: A separator.
+//!
+//! Submenus, items and check items have an optional `id` and an `enabled` state, `true` by default.
+//! Items and check items have an optional `accelerator`, parsed like [`Accelerator`]'s
+//! [`FromStr`](std::str::FromStr) implementation. Submenus don't have a [`MenuId`],
+//! their `id` is only the key of their handle.
+//!
+//! ```toml
+//! [[submenus]]
+//! label = "&File"
+//! items = [
+//!     { type = "item", label = "&Save", id = "file.save", accelerator = "CmdOrCtrl+S" },
+//!     { type = "check", label = "&Autosave", id = "file.autosave", checked = true },
+//!     { type = "submenu", label = "&Recent", id = "file.recent" },
+//!     { type = "separator" },
+//!     { type = "native", item = "Quit" },
+//! ]
+//!
+//! [[submenus]]
+//! label = "&Edit"
+//! enabled = false
+//! ```
+//!
+//! Definitions starting with `{` are JSON, anything else is TOML:
+//!
+//! ```json
+//! {
+//!     "submenus": [
+//!         {
+//!             "label": "&File",
+//!             "items": [
+//!                 { "type": "item", "label": "&Save", "id": "file.save", "accelerator": "CmdOrCtrl+S" },
+//!                 { "type": "separator" },
+//!                 { "type": "native", "item": "Quit" }
+//!             ]
+//!         }
+//!     ]
+//! }
+//! ```
+//!
+//! Errors point at the bad entry with its path in the definition, like `submenus[0].items[1]`.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use serde::Deserialize;
+
+use crate::{
+    accelerator::Accelerator, Menu, MenuId, MenuItemKind, NativeMenuItem, Submenu,
+    GENERATED_ID_PREFIX,
+};
+
+/// Loads a menu definition, see [`Menu::from_definition`].
+pub(crate) fn load(
+    definition: &str,
+) -> Result<(Menu, HashMap<MenuId, MenuItemKind>), MenuDefinitionError> {
+    let definition = parse(definition)?;
+    definition.check_ids()?;
+    Ok(definition.build())
+}
+
+fn parse(definition: &str) -> Result<MenuDefinition, MenuDefinitionError> {
+    if definition.trim_start().starts_with('{') {
+        let mut deserializer = serde_json::Deserializer::from_str(definition);
+        let menu = serde_path_to_error::deserialize(&mut deserializer)
+            .map_err(|e| MenuDefinitionError::invalid(e.path(), e.inner().to_string()))?;
+        deserializer
+            .end()
+            .map_err(|e| MenuDefinitionError::Invalid {
+                path: String::new(),
+                message: e.to_string(),
+            })?;
+        Ok(menu)
+    } else {
+        serde_path_to_error::deserialize(toml::Deserializer::new(definition)).map_err(|e| {
+            // toml points at the enclosing table or array of a bad entry,
+            // so only keep its line and column for errors which aren't inside an entry
+            let message = match e.path().iter().next() {
+                Some(_) => e.inner().message().to_string(),
+                None => e.inner().to_string(),
+            };
+            MenuDefinitionError::invalid(e.path(), message)
+        })
+    }
+}
+
+fn enabled() -> bool {
+    true
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MenuDefinition {
+    #[serde(default)]
+    submenus: Vec<SubmenuDefinition>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SubmenuDefinition {
+    label: String,
+    id: Option<MenuId>,
+    #[serde(default = "enabled")]
+    enabled: bool,
+    #[serde(default)]
+    items: Vec<ItemDefinition>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum ItemDefinition {
+    Item {
+        label: String,
+        id: Option<MenuId>,
+        #[serde(default = "enabled")]
+        enabled: bool,
+        accelerator: Option<Accelerator>,
+    },
+    Check {
+        label: String,
+        id: Option<MenuId>,
+        #[serde(default = "enabled")]
+        enabled: bool,
+        #[serde(default)]
+        checked: bool,
+        accelerator: Option<Accelerator>,
+    },
+    Submenu(SubmenuDefinition),
+    Native {
+        item: NativeMenuItem,
+    },
+    Separator,
+}
+
+impl MenuDefinition {
+    /// Fails with [`MenuDefinitionError::DuplicateId`] if two entries have the same id,
+    /// which is checked before creating the menu because the handles are keyed by id,
+    /// and with [`MenuDefinitionError::Invalid`] if an id uses the prefix of generated ids.
+    fn check_ids(&self) -> Result<(), MenuDefinitionError> {
+        let mut ids = HashSet::new();
+        for (i, submenu) in self.submenus.iter().enumerate() {
+            submenu.check_ids(format!("submenus[{}]", i), &mut ids)?;
+        }
+        Ok(())
+    }
+
+    fn build(self) -> (Menu, HashMap<MenuId, MenuItemKind>) {
+        let mut menu = Menu::new();
+        let mut items = HashMap::new();
+        for definition in self.submenus {
+            let submenu = menu.add_submenu(&definition.label, definition.enabled);
+            definition.fill(submenu, &mut items);
+        }
+        (menu, items)
+    }
+}
+
+impl SubmenuDefinition {
+    fn check_ids<'a>(
+        &'a self,
+        path: String,
+        ids: &mut HashSet<&'a MenuId>,
+    ) -> Result<(), MenuDefinitionError> {
+        check_id(self.id.as_ref(), &path, ids)?;
+        for (i, item) in self.items.iter().enumerate() {
+            let path = format!("{}.items[{}]", path, i);
+            match item {
+                ItemDefinition::Item { id, .. } | ItemDefinition::Check { id, .. } => {
+                    check_id(id.as_ref(), &path, ids)?
+                }
+                ItemDefinition::Submenu(submenu) => submenu.check_ids(path, ids)?,
+                ItemDefinition::Native { .. } | ItemDefinition::Separator => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the items to `submenu`, which was created with the label and enabled state
+    /// of this definition, and the handles of the entries with an id to `items`.
+    fn fill(self, mut submenu: Submenu, items: &mut HashMap<MenuId, MenuItemKind>) {
+        if let Some(id) = self.id {
+            items.insert(id, MenuItemKind::Submenu(submenu.clone()));
+        }
+        for item in self.items {
+            match item {
+                ItemDefinition::Item {
+                    label,
+                    id: Some(id),
+                    enabled,
+                    accelerator,
+                } => {
+                    let item = submenu.add_item_with_id(&id, label, enabled, accelerator);
+                    items.insert(id, MenuItemKind::MenuItem(item));
+                }
+                ItemDefinition::Item {
+                    label,
+                    id: None,
+                    enabled,
+                    accelerator,
+                } => {
+                    submenu.add_item(label, enabled, accelerator);
+                }
+                ItemDefinition::Check {
+                    label,
+                    id: Some(id),
+                    enabled,
+                    checked,
+                    accelerator,
+                } => {
+                    let item =
+                        submenu.add_check_item_with_id(&id, label, enabled, checked, accelerator);
+                    items.insert(id, MenuItemKind::CheckMenuItem(item));
+                }
+                ItemDefinition::Check {
+                    label,
+                    id: None,
+                    enabled,
+                    checked,
+                    accelerator,
+                } => {
+                    submenu.add_check_item(label, enabled, checked, accelerator);
+                }
+                ItemDefinition::Submenu(definition) => {
+                    let nested = submenu.add_submenu(&definition.label, definition.enabled);
+                    definition.fill(nested, items);
+                }
+                ItemDefinition::Native { item } => submenu.add_native_item(item),
+                ItemDefinition::Separator => submenu.add_native_item(NativeMenuItem::Separator),
+            }
+        }
+    }
+}
+
+fn check_id<'a>(
+    id: Option<&'a MenuId>,
+    path: &str,
+    ids: &mut HashSet<&'a MenuId>,
+) -> Result<(), MenuDefinitionError> {
+    match id {
+        Some(id) if id.as_str().starts_with(GENERATED_ID_PREFIX) => {
+            Err(MenuDefinitionError::Invalid {
+                path: path.to_string(),
+                message: format!(
+                    "ids starting with `{}` are reserved for generated ids",
+                    GENERATED_ID_PREFIX
+                ),
+            })
+        }
+        Some(id) if !ids.insert(id) => Err(MenuDefinitionError::DuplicateId {
+            path: path.to_string(),
+            id: id.clone(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// An error returned when loading a menu definition fails.
+///
+/// Paths are made of the keys and the indices of the entries leading to the bad entry,
+/// for example `submenus[0].items[1]`.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MenuDefinitionError {
+    /// The definition isn't valid TOML or JSON, or an entry doesn't match the format,
+    /// including accelerators which couldn't be parsed. `path` is empty when the error
+    /// isn't inside an entry.
+    Invalid { path: String, message: String },
+    /// An id is used by more than one entry, `path` is the path of the last one.
+    DuplicateId { path: String, id: MenuId },
+}
+
+impl MenuDefinitionError {
+    fn invalid(path: &serde_path_to_error::Path, message: String) -> Self {
+        MenuDefinitionError::Invalid {
+            // the root of the definition is displayed as `.`
+            path: match path.iter().next() {
+                Some(_) => path.to_string(),
+                None => String::new(),
+            },
+            message,
+        }
+    }
+}
+
+impl fmt::Display for MenuDefinitionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MenuDefinitionError::Invalid { path, message } if path.is_empty() => {
+                write!(f, "[MenuDefinitionError]: {}", message)
+            }
+            MenuDefinitionError::Invalid { path, message } => {
+                write!(
+                    f,
+                    "[MenuDefinitionError]: Invalid entry `{}`: {}",
+                    path, message
+                )
+            }
+            MenuDefinitionError::DuplicateId { path, id } => write!(
+                f,
+                "[MenuDefinitionError]: The id `{}` of `{}` is already used",
+                id, path
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MenuDefinitionError {}
+
+#[test]
+fn test_parse_definition() {
+    use crate::accelerator::{Code, Modifiers};
+
+    let toml = r#"
+        [[submenus]]
+        label = "&File"
+        items = [
+            { type = "item", label = "&Save", id = "file.save", accelerator = "Ctrl+S" },
+            { type = "check", label = "&Autosave", checked = true },
+            { type = "submenu", label = "&Recent", id = "file.recent", enabled = false },
+            { type = "separator" },
+            { type = "native", item = "Quit" },
+        ]
+
+        [[submenus]]
+        label = "&Edit"
+    "#;
+    let json = r#"
+        {
+            "submenus": [
+                {
+                    "label": "&File",
+                    "items": [
+                        { "type": "item", "label": "&Save", "id": "file.save", "accelerator": "Ctrl+S" },
+                        { "type": "check", "label": "&Autosave", "checked": true },
+                        { "type": "submenu", "label": "&Recent", "id": "file.recent", "enabled": false },
+                        { "type": "separator" },
+                        { "type": "native", "item": "Quit" }
+                    ]
+                },
+                { "label": "&Edit" }
+            ]
+        }
+    "#;
+
+    let definition = parse(toml).unwrap();
+    assert_eq!(
+        definition,
+        MenuDefinition {
+            submenus: vec![
+                SubmenuDefinition {
+                    label: "&File".into(),
+                    id: None,
+                    enabled: true,
+                    items: vec![
+                        ItemDefinition::Item {
+                            label: "&Save".into(),
+                            id: Some(MenuId::new("file.save")),
+                            enabled: true,
+                            accelerator: Some(Accelerator::new(Modifiers::CONTROL, Code::KeyS)),
+                        },
+                        ItemDefinition::Check {
+                            label: "&Autosave".into(),
+                            id: None,
+                            enabled: true,
+                            checked: true,
+                            accelerator: None,
+                        },
+                        ItemDefinition::Submenu(SubmenuDefinition {
+                            label: "&Recent".into(),
+                            id: Some(MenuId::new("file.recent")),
+                            enabled: false,
+                            items: vec![],
+                        }),
+                        ItemDefinition::Separator,
+                        ItemDefinition::Native {
+                            item: NativeMenuItem::Quit
+                        },
+                    ],
+                },
+                SubmenuDefinition {
+                    label: "&Edit".into(),
+                    id: None,
+                    enabled: true,
+                    items: vec![],
+                },
+            ],
+        }
+    );
+    assert_eq!(parse(json).unwrap(), definition);
+    assert_eq!(definition.check_ids(), Ok(()));
+}
+
+#[test]
+fn test_definition_errors() {
+    let error = |definition: &str| {
+        parse(definition)
+            .and_then(|definition| definition.check_ids())
+            .unwrap_err()
+    };
+
+    match error(
+        r#"
+        [[submenus]]
+        label = "&File"
+        items = [
+            { type = "item", label = "&Save" },
+            { type = "item", label = "&Quit", accelerator = "Ctrl+Shft+Q" },
+        ]
+        "#,
+    ) {
+        MenuDefinitionError::Invalid { path, message } => {
+            assert_eq!(path, "submenus[0].items[1]");
+            assert!(
+                message.starts_with("[AcceleratorParseError]"),
+                "{}",
+                message
+            );
+        }
+        e => panic!("unexpected error: {:?}", e),
+    }
+
+    match error(r#"{ "submenus": [{ "label": "&File", "items": [{ "type": "button" }] }] }"#) {
+        MenuDefinitionError::Invalid { path, message } => {
+            assert_eq!(path, "submenus[0].items[0].type");
+            assert!(message.contains("button"), "{}", message);
+        }
+        e => panic!("unexpected error: {:?}", e),
+    }
+
+    assert!(matches!(
+        error("{ \"submenus\": [] } trailing"),
+        MenuDefinitionError::Invalid { path, .. } if path.is_empty()
+    ));
+
+    assert_eq!(
+        error(
+            r#"
+            [[submenus]]
+            label = "&File"
+            id = "file"
+            items = [
+                { type = "submenu", label = "&Recent", items = [
+                    { type = "check", label = "&Pinned", id = "file" },
+                ] },
+            ]
+            "#,
+        ),
+        MenuDefinitionError::DuplicateId {
+            path: "submenus[0].items[0].items[0]".into(),
+            id: MenuId::new("file"),
+        }
+    );
+
+    match error(
+        r#"{ "submenus": [{ "label": "&File", "items": [{ "type": "item", "label": "&Save", "id": "__muda:3" }] }] }"#,
+    ) {
+        MenuDefinitionError::Invalid { path, message } => {
+            assert_eq!(path, "submenus[0].items[0]");
+            assert!(message.contains("reserved"), "{}", message);
+        }
+        e => panic!("unexpected error: {:?}", e),
+    }
+}
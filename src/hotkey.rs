@@ -0,0 +1,167 @@
+//! Global hotkeys are accelerators which are activated even when none of the windows
+//! of the application are focused.
+//!
+//! Hotkeys are registered using a [`GlobalHotKeyManager`] and their activations are delivered
+//! through [`menu_event_receiver`](crate::menu_event_receiver) as [`MenuEvent`](crate::MenuEvent)s
+//! carrying the id of the [`HotKey`], so they can be handled in the same place as menu items.
+//!
+//! ```no_run
+//! # use muda::{accelerator::Accelerator, hotkey::GlobalHotKeyManager};
+//! #
+//! let manager = GlobalHotKeyManager::new().unwrap();
+//! let hotkey = manager
+//!     .register("Control+Alt+Space".parse::<Accelerator>().unwrap())
+//!     .unwrap();
+//!
+//! if let Ok(event) = muda::menu_event_receiver().try_recv() {
+//!     if event.id == *hotkey.id() {
+//!         println!("Hotkey pressed");
+//!     }
+//! }
+//! ```
+//!
+//! ## Platform-specific:
+//!
+//! - **Linux**: Only X11 is supported, keys are grabbed on the root window of the default display.
+//!   Wayland compositors don't allow clients to grab keys.
+
+use std::fmt;
+
+use crate::{
+    accelerator::{Accelerator, UnsupportedAcceleratorError},
+    platform_impl, MenuId,
+};
+
+/// A hotkey registered with a [`GlobalHotKeyManager`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HotKey {
+    pub(crate) id: MenuId,
+    pub(crate) accelerator: Accelerator,
+}
+
+impl HotKey {
+    /// Returns the id of this hotkey, which is the id of the [`MenuEvent`](crate::MenuEvent)s
+    /// sent when it is activated. Hotkey ids never collide with the generated ids of menu items.
+    pub fn id(&self) -> &MenuId {
+        &self.id
+    }
+
+    /// Returns the accelerator of this hotkey.
+    pub fn accelerator(&self) -> &Accelerator {
+        &self.accelerator
+    }
+}
+
+/// Registers system-wide hotkeys, hotkeys are unregistered when the manager is dropped.
+pub struct GlobalHotKeyManager(platform_impl::GlobalHotKeyManager);
+
+impl GlobalHotKeyManager {
+    /// Connects to the display server, fails with [`HotKeyError::NoDisplay`] if there is none.
+    pub fn new() -> Result<Self, HotKeyError> {
+        platform_impl::GlobalHotKeyManager::new().map(Self)
+    }
+
+    /// Registers `accelerator` as a global hotkey.
+    ///
+    /// Fails with [`HotKeyError::AlreadyGrabbed`] if another application already uses the same key
+    /// combination and with [`HotKeyError::AlreadyRegistered`] if this manager does.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Linux**: Accelerators which restrict modifiers to one side or use `Hyper` are not supported.
+    ///   The hotkey is activated regardless of the state of Caps Lock and Num Lock.
+    pub fn register(&self, accelerator: Accelerator) -> Result<HotKey, HotKeyError> {
+        self.0.register(accelerator)
+    }
+
+    /// Unregisters `hotkey`, fails with [`HotKeyError::NotRegistered`] if it was already unregistered
+    /// or registered by another manager.
+    pub fn unregister(&self, hotkey: &HotKey) -> Result<(), HotKeyError> {
+        self.0.unregister(hotkey)
+    }
+}
+
+/// An error returned by [`GlobalHotKeyManager`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HotKeyError {
+    /// Couldn't connect to the display server.
+    NoDisplay,
+    /// The key or modifiers of the accelerator can't be grabbed.
+    Unsupported(UnsupportedAcceleratorError),
+    /// Another application already grabbed this key combination.
+    AlreadyGrabbed(Accelerator),
+    /// This key combination is already registered by the same manager.
+    AlreadyRegistered(Accelerator),
+    /// The hotkey isn't registered by this manager.
+    NotRegistered(HotKey),
+}
+
+impl fmt::Display for HotKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HotKeyError::NoDisplay => write!(f, "[HotKeyError]: Couldn't connect to the display"),
+            HotKeyError::Unsupported(e) => write!(f, "[HotKeyError]: {}", e),
+            HotKeyError::AlreadyGrabbed(accelerator) => write!(
+                f,
+                "[HotKeyError]: `{}` is already grabbed by another application",
+                accelerator
+            ),
+            HotKeyError::AlreadyRegistered(accelerator) => {
+                write!(f, "[HotKeyError]: `{}` is already registered", accelerator)
+            }
+            HotKeyError::NotRegistered(hotkey) => write!(
+                f,
+                "[HotKeyError]: `{}` is not registered",
+                hotkey.accelerator
+            ),
+        }
+    }
+}
+
+impl std::error::Error for HotKeyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HotKeyError::Unsupported(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn test_hotkeys() {
+    // needs an X server, for example `xvfb-run cargo test`
+    if std::env::var_os("DISPLAY").is_none() {
+        return;
+    }
+
+    let accelerator: Accelerator = "Control+Alt+Shift+F12".parse().unwrap();
+    let manager = GlobalHotKeyManager::new().unwrap();
+    let hotkey = manager.register(accelerator.clone()).unwrap();
+    assert_eq!(hotkey.accelerator(), &accelerator);
+    assert_eq!(
+        manager.register(accelerator.clone()),
+        Err(HotKeyError::AlreadyRegistered(accelerator.clone()))
+    );
+
+    // a second manager has its own connection so it behaves like another application
+    let other = GlobalHotKeyManager::new().unwrap();
+    assert_eq!(
+        other.register(accelerator.clone()),
+        Err(HotKeyError::AlreadyGrabbed(accelerator.clone()))
+    );
+    assert_eq!(
+        other.unregister(&hotkey),
+        Err(HotKeyError::NotRegistered(hotkey.clone()))
+    );
+
+    manager.unregister(&hotkey).unwrap();
+    assert_eq!(
+        manager.unregister(&hotkey),
+        Err(HotKeyError::NotRegistered(hotkey.clone()))
+    );
+    let hotkey = other.register(accelerator.clone()).unwrap();
+
+    drop(other);
+    manager.register(hotkey.accelerator.clone()).unwrap();
+}
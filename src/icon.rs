@@ -0,0 +1,191 @@
+//! Icons displayed next to the label of menu items and submenus.
+//!
+//! ```no_run
+//! # use muda::{icon::Icon, Menu};
+//! let mut menu = Menu::new();
+//! let mut file_menu = menu.add_submenu("&File", true);
+//!
+//! let icon = Icon::from_buffer(&std::fs::read("open.png").unwrap()).unwrap();
+//! file_menu.add_icon_item("&Open", true, icon, None);
+//! ```
+
+/// The size of menu icons, larger icons are scaled down to it.
+pub(crate) const ICON_SIZE: i32 = 16;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+const ICO_SIGNATURE: &[u8] = &[0, 0, 1, 0];
+
+/// An error returned when creating an [`Icon`] fails.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BadIcon {
+    /// The length of the rgba buffer isn't a multiple of 4.
+    ByteCountNotDivisibleBy4 { byte_count: usize },
+    /// The number of pixels of the rgba buffer doesn't match the dimensions.
+    DimensionsVsPixelCount {
+        width: u32,
+        height: u32,
+        width_x_height: usize,
+        pixel_count: usize,
+    },
+    /// The width or the height is zero.
+    ZeroDimensions,
+    /// The buffer isn't a PNG or ICO image.
+    UnsupportedFormat,
+}
+
+impl std::fmt::Display for BadIcon {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BadIcon::ByteCountNotDivisibleBy4 { byte_count } => write!(
+                f,
+                "[BadIcon]: The length of the rgba buffer ({}) isn't a multiple of 4",
+                byte_count
+            ),
+            BadIcon::DimensionsVsPixelCount {
+                width,
+                height,
+                width_x_height,
+                pixel_count,
+            } => write!(
+                f,
+                "[BadIcon]: The dimensions ({}x{} = {}) don't match the number of pixels ({})",
+                width, height, width_x_height, pixel_count
+            ),
+            BadIcon::ZeroDimensions => write!(f, "[BadIcon]: The icon is empty"),
+            BadIcon::UnsupportedFormat => {
+                write!(f, "[BadIcon]: The buffer isn't a PNG or ICO image")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BadIcon {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum IconSource {
+    Rgba {
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+    },
+    /// A PNG or ICO image, decoded by the platform when the icon is displayed.
+    Buffer(Vec<u8>),
+    #[cfg(target_os = "linux")]
+    Name(String),
+}
+
+/// An icon displayed next to the label of a menu item or submenu,
+/// see [`Submenu::add_icon_item`](crate::Submenu::add_icon_item) and [`MenuItem::set_icon`](crate::MenuItem::set_icon).
+///
+/// Icons are scaled down to the size of menu icons, 16x16 pixels, if they are larger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Icon(pub(crate) IconSource);
+
+impl Icon {
+    /// Creates an icon from 32bpp RGBA pixels, row by row from the top left corner.
+    pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Result<Self, BadIcon> {
+        let pixel_count = rgba.len() / 4;
+        if pixel_count * 4 != rgba.len() {
+            return Err(BadIcon::ByteCountNotDivisibleBy4 {
+                byte_count: rgba.len(),
+            });
+        }
+        if width == 0 || height == 0 {
+            return Err(BadIcon::ZeroDimensions);
+        }
+        let width_x_height = width as usize * height as usize;
+        if pixel_count != width_x_height {
+            return Err(BadIcon::DimensionsVsPixelCount {
+                width,
+                height,
+                width_x_height,
+                pixel_count,
+            });
+        }
+
+        Ok(Self(IconSource::Rgba {
+            rgba,
+            width,
+            height,
+        }))
+    }
+
+    /// Creates an icon from the content of a PNG or ICO file.
+    ///
+    /// Only the signature of the image is checked here, an image that the platform
+    /// fails to decode isn't displayed.
+    pub fn from_buffer(buffer: &[u8]) -> Result<Self, BadIcon> {
+        if buffer.starts_with(PNG_SIGNATURE) || buffer.starts_with(ICO_SIGNATURE) {
+            Ok(Self(IconSource::Buffer(buffer.to_vec())))
+        } else {
+            Err(BadIcon::UnsupportedFormat)
+        }
+    }
+
+    /// Creates an icon from the name of an icon of the current icon theme,
+    /// see the [freedesktop icon naming specification](https://specifications.freedesktop.org/icon-naming-spec/latest/).
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    #[cfg(target_os = "linux")]
+    pub fn from_name<S: Into<String>>(name: S) -> Self {
+        Self(IconSource::Name(name.into()))
+    }
+}
+
+/// Returns the size of an icon scaled down to fit [`ICON_SIZE`] keeping its aspect ratio.
+pub(crate) fn scaled_size(width: i32, height: i32) -> (i32, i32) {
+    let largest = width.max(height);
+    if largest <= ICON_SIZE {
+        (width, height)
+    } else {
+        (
+            (width * ICON_SIZE / largest).max(1),
+            (height * ICON_SIZE / largest).max(1),
+        )
+    }
+}
+
+#[test]
+fn test_from_rgba() {
+    assert!(Icon::from_rgba(vec![0; 16], 2, 2).is_ok());
+    assert_eq!(
+        Icon::from_rgba(vec![0; 15], 2, 2),
+        Err(BadIcon::ByteCountNotDivisibleBy4 { byte_count: 15 })
+    );
+    assert_eq!(
+        Icon::from_rgba(vec![0; 16], 2, 3),
+        Err(BadIcon::DimensionsVsPixelCount {
+            width: 2,
+            height: 3,
+            width_x_height: 6,
+            pixel_count: 4
+        })
+    );
+    assert_eq!(
+        Icon::from_rgba(Vec::new(), 0, 2),
+        Err(BadIcon::ZeroDimensions)
+    );
+}
+
+#[test]
+fn test_from_buffer() {
+    assert!(Icon::from_buffer(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").is_ok());
+    assert!(Icon::from_buffer(&[0, 0, 1, 0, 1, 0]).is_ok());
+    assert_eq!(
+        Icon::from_buffer(b"GIF89a"),
+        Err(BadIcon::UnsupportedFormat)
+    );
+    assert_eq!(Icon::from_buffer(&[]), Err(BadIcon::UnsupportedFormat));
+}
+
+#[test]
+fn test_scaled_size() {
+    assert_eq!(scaled_size(16, 16), (16, 16));
+    assert_eq!(scaled_size(12, 8), (12, 8));
+    assert_eq!(scaled_size(32, 32), (16, 16));
+    assert_eq!(scaled_size(64, 32), (16, 8));
+    assert_eq!(scaled_size(256, 1), (16, 1));
+}
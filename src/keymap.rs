@@ -0,0 +1,430 @@
+//! Keymaps let users override the accelerators of menu items without rebuilding the application.
+//!
+//! A keymap file has one entry per line, mapping a menu item to an accelerator.
+//! Items are identified either by their path, which is the labels of their parent submenus
+//! and their own label joined by `/` with mnemonics removed, or by their [`MenuId`](crate::MenuId),
+//! anything that doesn't contain a `/` is treated as an id. An entry without an accelerator
+//! removes the accelerator of the item. Empty lines and lines starting with `#` are ignored.
+//! Generated ids change between runs, so only items with a stable id can be identified by their id.
+//!
+//! Ids in double quotes are always treated as ids, even if they contain a `/`.
+//! A `\` escapes the next character, which is needed for `=`, `"` and `\` in items
+//! and for a `#` at the start of an item.
+//!
+//! ```text
+//! # Use Ctrl+Shift+S to save and don't use any accelerator to quit
+//! File/Save = Ctrl+Shift+KeyS
+//! File/Quit =
+//! view.reload = F5
+//! "view/zoom-in" = Ctrl+Equal
+//! View/Zoom \= 100% = Ctrl+Digit0
+//! ```
+//!
+//! Keymaps are loaded using [`Keymap::load`], usually from [`Keymap::default_path`],
+//! and applied using [`Menu::set_keymap`](crate::Menu::set_keymap).
+//!
+//! ```no_run
+//! # use muda::keymap::Keymap;
+//! #
+//! let path = Keymap::default_path("my-app").unwrap();
+//! let keymap = Keymap::load(&path).unwrap();
+//! ```
+//!
+//! ## Platform-specific:
+//!
+//! - **Windows / macOS**: Unsupported, keymaps are only available on Linux.
+
+use std::{
+    env, fmt, fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{
+    accelerator::{Accelerator, AcceleratorConflict, AcceleratorParseError},
+    MenuId,
+};
+
+/// Identifies the menu item a [`KeymapEntry`] applies to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum KeymapItem {
+    /// The id of a menu item.
+    Id(MenuId),
+    /// The path of a menu item, for example `File/Save`.
+    Path(String),
+}
+
+/// Parses anything containing a `/` as [`KeymapItem::Path`] and anything else as [`KeymapItem::Id`].
+impl FromStr for KeymapItem {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.contains('/') {
+            true => KeymapItem::Path(s.to_string()),
+            false => KeymapItem::Id(MenuId::new(s)),
+        })
+    }
+}
+
+/// Writes the item as it appears in a keymap file, quoting ids and escaping characters as needed.
+impl fmt::Display for KeymapItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeymapItem::Id(id) if is_plain_id(id.as_str()) => write!(f, "{}", id),
+            KeymapItem::Id(id) => {
+                f.write_str("\"")?;
+                for c in id.as_str().chars() {
+                    if matches!(c, '"' | '\\') {
+                        f.write_str("\\")?;
+                    }
+                    write!(f, "{}", c)?;
+                }
+                f.write_str("\"")
+            }
+            KeymapItem::Path(path) => {
+                for (i, c) in path.chars().enumerate() {
+                    if matches!(c, '=' | '"' | '\\') || (i == 0 && c == '#') {
+                        f.write_str("\\")?;
+                    }
+                    write!(f, "{}", c)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Returns whether `id` is read back as the same id without quotes or escapes.
+fn is_plain_id(id: &str) -> bool {
+    !id.is_empty() && id.trim() == id && !id.starts_with('#') && !id.contains(['/', '=', '"', '\\'])
+}
+
+/// Splits a line at the first `=` that isn't escaped or quoted.
+fn split_entry(line: &str) -> Option<(&str, &str)> {
+    let mut escaped = false;
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => quoted = !quoted,
+            '=' if !quoted => return Some((&line[..i], &line[i + 1..])),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Parses the item of a line, removing its quotes and escapes,
+/// returns `None` if a quote or an escape isn't closed.
+fn parse_item(item: &str) -> Option<KeymapItem> {
+    let (quoted, text) = match item.strip_prefix('"') {
+        Some(text) => (true, text.strip_suffix('"')?),
+        None => (false, item),
+    };
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unescaped.push(chars.next()?),
+            '"' if quoted => return None,
+            c => unescaped.push(c),
+        }
+    }
+    Some(match quoted {
+        true => KeymapItem::Id(MenuId::new(unescaped)),
+        false => unescaped.parse().unwrap(),
+    })
+}
+
+/// A single entry of a [`Keymap`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeymapEntry {
+    /// The menu item this entry applies to.
+    pub item: KeymapItem,
+    /// The accelerator of the item, `None` removes the accelerator of the item.
+    pub accelerator: Option<Accelerator>,
+}
+
+/// A set of user overrides for the accelerators of menu items, see the [module docs](self).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Keymap {
+    entries: Vec<KeymapEntry>,
+}
+
+impl Keymap {
+    /// Creates an empty keymap.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the default path of the keymap file of an application, which is
+    /// `$XDG_CONFIG_HOME/<app_name>/keymap` or `$HOME/.config/<app_name>/keymap`
+    /// if `XDG_CONFIG_HOME` isn't set.
+    pub fn default_path(app_name: &str) -> Option<PathBuf> {
+        let config_dir = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_dir.join(app_name).join("keymap"))
+    }
+
+    /// Loads a keymap file, a missing file is loaded as an empty keymap.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, KeymapError> {
+        match fs::read_to_string(path) {
+            Ok(keymap) => keymap.parse(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(e) => Err(KeymapError::Io(e)),
+        }
+    }
+
+    /// Writes this keymap to a file, creating its parent directories if needed.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), KeymapError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(KeymapError::Io)?;
+        }
+        fs::write(path, self.to_string()).map_err(KeymapError::Io)
+    }
+
+    /// Returns the entries of this keymap.
+    pub fn entries(&self) -> &[KeymapEntry] {
+        &self.entries
+    }
+
+    /// Sets the accelerator of `item`, replacing any previous entry for it.
+    pub fn set(&mut self, item: KeymapItem, accelerator: Option<Accelerator>) {
+        match self.entries.iter_mut().find(|entry| entry.item == item) {
+            Some(entry) => entry.accelerator = accelerator,
+            None => self.entries.push(KeymapEntry { item, accelerator }),
+        }
+    }
+
+    /// Removes the entry for `item`.
+    pub fn remove(&mut self, item: &KeymapItem) {
+        self.entries.retain(|entry| entry.item != *item);
+    }
+
+    /// Finds the index of the entry for the menu item with `id` and `path`,
+    /// entries using the id take precedence over entries using the path.
+    pub(crate) fn find(&self, id: &MenuId, path: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| matches!(&entry.item, KeymapItem::Id(i) if i == id))
+            .or_else(|| {
+                self.entries
+                    .iter()
+                    .position(|entry| matches!(&entry.item, KeymapItem::Path(p) if p == path))
+            })
+    }
+}
+
+/// Parses a keymap file, see the [module docs](self) for the format.
+impl FromStr for Keymap {
+    type Err = KeymapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut keymap = Keymap::new();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (item, accelerator) =
+                split_entry(line).ok_or(KeymapError::MissingSeparator { line: i + 1 })?;
+            let item = item.trim();
+            if item.is_empty() {
+                return Err(KeymapError::MissingItem { line: i + 1 });
+            }
+            let item = parse_item(item).ok_or(KeymapError::InvalidItem { line: i + 1 })?;
+            let accelerator = accelerator.trim();
+            let accelerator = if accelerator.is_empty() {
+                None
+            } else {
+                Some(
+                    accelerator
+                        .parse()
+                        .map_err(|error| KeymapError::Accelerator { line: i + 1, error })?,
+                )
+            };
+            keymap.set(item, accelerator);
+        }
+        Ok(keymap)
+    }
+}
+
+/// Writes the keymap in the format of a keymap file.
+impl fmt::Display for Keymap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            match &entry.accelerator {
+                Some(accelerator) => writeln!(f, "{} = {}", entry.item, accelerator)?,
+                None => writeln!(f, "{} =", entry.item)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An error returned when loading or saving a [`Keymap`] fails, lines start at 1.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum KeymapError {
+    /// Reading or writing the keymap file failed.
+    Io(io::Error),
+    /// A line doesn't contain a `=`.
+    MissingSeparator { line: usize },
+    /// A line doesn't name a menu item before the `=`.
+    MissingItem { line: usize },
+    /// The menu item of a line has an unclosed quote or ends with a `\`.
+    InvalidItem { line: usize },
+    /// The accelerator of a line couldn't be parsed,
+    /// the span of the error is relative to the accelerator.
+    Accelerator {
+        line: usize,
+        error: AcceleratorParseError,
+    },
+}
+
+impl fmt::Display for KeymapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeymapError::Io(e) => write!(f, "[KeymapError]: {}", e),
+            KeymapError::MissingSeparator { line } => {
+                write!(f, "[KeymapError]: Expected `=` on line {}", line)
+            }
+            KeymapError::MissingItem { line } => {
+                write!(f, "[KeymapError]: Expected a menu item on line {}", line)
+            }
+            KeymapError::InvalidItem { line } => {
+                write!(f, "[KeymapError]: Invalid menu item on line {}", line)
+            }
+            KeymapError::Accelerator { line, error } => {
+                write!(
+                    f,
+                    "[KeymapError]: Invalid accelerator on line {}: {}",
+                    line, error
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for KeymapError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            KeymapError::Io(e) => Some(e),
+            KeymapError::Accelerator { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// A problem found when applying a [`Keymap`] to a menu.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeymapIssue {
+    /// The entry for this item doesn't match any menu item.
+    MissingItem(KeymapItem),
+    /// An item whose accelerator was set by the keymap conflicts with another accelerator.
+    Conflict(AcceleratorConflict),
+}
+
+#[test]
+fn test_parse_keymap() {
+    use crate::accelerator::{AcceleratorParseErrorKind, Code, Modifiers};
+
+    let keymap: Keymap = "
+        # comment
+        File/Save = Ctrl+Shift+KeyS
+
+        File/Quit =
+        view.reload = F5
+        File/Save = Ctrl+KeyS
+    "
+    .parse()
+    .unwrap();
+
+    assert_eq!(
+        keymap.entries(),
+        &[
+            KeymapEntry {
+                item: KeymapItem::Path("File/Save".into()),
+                accelerator: Some(Accelerator::new(Modifiers::CONTROL, Code::KeyS)),
+            },
+            KeymapEntry {
+                item: KeymapItem::Path("File/Quit".into()),
+                accelerator: None,
+            },
+            KeymapEntry {
+                item: KeymapItem::Id(MenuId::new("view.reload")),
+                accelerator: Some(Accelerator::new(None, Code::F5)),
+            },
+        ]
+    );
+    assert_eq!(keymap.to_string().parse::<Keymap>().unwrap(), keymap);
+
+    assert!(matches!(
+        "File/Save".parse::<Keymap>(),
+        Err(KeymapError::MissingSeparator { line: 1 })
+    ));
+    assert!(matches!(
+        "\n = F5".parse::<Keymap>(),
+        Err(KeymapError::MissingItem { line: 2 })
+    ));
+    assert!(matches!(
+        "\"file.save = F5".parse::<Keymap>(),
+        Err(KeymapError::MissingSeparator { line: 1 })
+    ));
+    assert!(matches!(
+        "\"file\"save = F5".parse::<Keymap>(),
+        Err(KeymapError::InvalidItem { line: 1 })
+    ));
+    match "File/Save = Ctrl+Foo".parse::<Keymap>() {
+        Err(KeymapError::Accelerator { line: 1, error }) => {
+            assert_eq!(error.kind(), AcceleratorParseErrorKind::UnknownKey);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_keymap_round_trip() {
+    use crate::accelerator::Code;
+
+    let mut keymap = Keymap::new();
+    let items = [
+        KeymapItem::Id(MenuId::new("file/save")),
+        KeymapItem::Id(MenuId::new("zoom=100")),
+        KeymapItem::Id(MenuId::new("say \"hi\" \\o/")),
+        KeymapItem::Id(MenuId::new("#tag")),
+        KeymapItem::Id(MenuId::new(" padded ")),
+        KeymapItem::Path("View/Zoom = 100%".into()),
+        KeymapItem::Path("#Tags/C:\\\"Docs\"".into()),
+    ];
+    for item in &items {
+        keymap.set(item.clone(), Some(Accelerator::new(None, Code::F5)));
+    }
+    keymap.set(KeymapItem::Id(MenuId::new("a=b")), None);
+
+    let text = keymap.to_string();
+    assert!(text.starts_with("\"file/save\" = F5\n\"zoom=100\" = F5\n"));
+    assert!(text.contains("\nView/Zoom \\= 100% = F5\n"));
+    assert_eq!(text.parse::<Keymap>().unwrap(), keymap);
+}
+
+#[test]
+fn test_find_keymap_entry() {
+    let keymap: Keymap = "File/Save = Ctrl+KeyS\n7 = F5\nFile/Open = Ctrl+KeyO"
+        .parse()
+        .unwrap();
+    assert_eq!(keymap.find(&MenuId::from(1), "File/Save"), Some(0));
+    assert_eq!(keymap.find(&MenuId::from(7), "File/Open"), Some(1));
+    assert_eq!(keymap.find(&MenuId::from(2), "File/Open"), Some(2));
+    assert_eq!(keymap.find(&MenuId::from(2), "File/Close"), None);
+
+    let keymap: Keymap = "__muda:3 = F5".parse().unwrap();
+    assert_eq!(keymap.find(&MenuId::new("__muda:3"), ""), Some(0));
+    assert_eq!(keymap.find(&MenuId::generated(3), ""), None);
+}
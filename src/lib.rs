@@ -58,14 +58,40 @@
 //!     }
 //! }
 //! ```
+//!
+//! # Cargo features
+//!
+//! - `serde`: Implements `Serialize` and `Deserialize` for [`MenuEvent`], [`MenuHighlightEvent`], [`NativeMenuItem`],
+//!   [`AboutMetadata`] and the [`accelerator`] types, accelerators are serialized as strings, for example `"Control+KeyS"`.
+//! - `definition`: Enables `Menu::from_definition` to load menus from TOML or JSON, see the `definition` module, and implies `serde`.
 
-use accelerator::Accelerator;
+use accelerator::AcceleratorConflict;
+#[cfg(target_os = "linux")]
+use accelerator::Chord;
+use accelerator::{
+    conflicts, Accelerator, Code, Key, KeyPress, Modifiers, UnsupportedAcceleratorError,
+};
 use crossbeam_channel::{unbounded, Receiver, Sender};
+use icon::Icon;
+#[cfg(target_os = "linux")]
+use keymap::{Keymap, KeymapIssue};
 use once_cell::sync::Lazy;
+use tree::MenuTree;
 
 pub mod accelerator;
+pub mod builder;
 mod counter;
+#[cfg(feature = "definition")]
+pub mod definition;
+#[cfg(target_os = "linux")]
+pub mod hotkey;
+pub mod icon;
+#[cfg(target_os = "linux")]
+pub mod keymap;
 mod platform_impl;
+#[cfg(target_os = "linux")]
+pub mod tray;
+mod tree;
 
 static MENU_CHANNEL: Lazy<(Sender<MenuEvent>, Receiver<MenuEvent>)> = Lazy::new(|| unbounded());
 
@@ -76,14 +102,363 @@ pub fn menu_event_receiver<'a>() -> &'a Receiver<MenuEvent> {
 }
 
 /// Describes a menu event emitted when a menu item is activated
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MenuEvent {
     /// Id of the menu item which triggered this event
-    pub id: u64,
+    pub id: MenuId,
+}
+
+static MENU_HIGHLIGHT_CHANNEL: Lazy<(Sender<MenuHighlightEvent>, Receiver<MenuHighlightEvent>)> =
+    Lazy::new(unbounded);
+
+/// Gets a reference to the event channel's [Receiver<MenuHighlightEvent>]
+/// which can be used to listen for menu items being highlighted.
+///
+/// ## Platform-specific:
+///
+/// - **macOS:** Unsupported, no events are emitted.
+pub fn menu_highlight_event_receiver<'a>() -> &'a Receiver<MenuHighlightEvent> {
+    &MENU_HIGHLIGHT_CHANNEL.1
+}
+
+/// Describes a menu event emitted when a menu item is highlighted, by hovering it
+/// or moving to it with the keyboard, and when the highlight leaves it.
+///
+/// Apps can use it to display the help text of the highlighted item in a status bar,
+/// see [`MenuItem::set_help_text`].
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MenuHighlightEvent {
+    /// Id of the menu item which is highlighted or was unhighlighted
+    pub id: MenuId,
+    /// The help text of the menu item
+    pub help_text: Option<String>,
+    /// Whether the menu item is highlighted, `false` when the highlight left it
+    pub highlighted: bool,
+}
+
+static CONTEXT_MENU_CHANNEL: Lazy<(
+    Sender<ContextMenuClosedEvent>,
+    Receiver<ContextMenuClosedEvent>,
+)> = Lazy::new(unbounded);
+
+/// Gets a reference to the event channel's [Receiver<ContextMenuClosedEvent>]
+/// which can be used to listen for context menus being closed.
+pub fn context_menu_closed_event_receiver<'a>() -> &'a Receiver<ContextMenuClosedEvent> {
+    &CONTEXT_MENU_CHANNEL.1
+}
+
+/// Describes an event emitted when a submenu shown as a context menu is closed,
+/// see [`Submenu::show_context_menu_for_gtk_window`].
+///
+/// When an item of the menu is activated, its [`MenuEvent`] is emitted before this event.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContextMenuClosedEvent {
+    /// Whether the menu was closed by activating one of its items, `false` when it was
+    /// dismissed by pressing `Escape` or clicking outside of it.
+    pub item_activated: bool,
+}
+
+/// The id of a menu item, which is carried by the [`MenuEvent`]s it emits.
+///
+/// Menu items get a unique id when they are created, which may change between runs and platforms.
+/// To persist ids or reference them from configuration, give items a stable id using
+/// [`Submenu::add_item_with_id`] or [`Submenu::add_check_item_with_id`]. Ids can be built
+/// from strings or integers, integers are stored as their decimal representation.
+///
+/// Generated ids are displayed starting with `__muda:`, but never equal an id built from a string
+/// or an integer, even one with the same text, so they never collide with stable ids.
+///
+/// ```no_run
+/// # use muda::{Menu, MenuId};
+/// let mut menu = Menu::new();
+/// let mut file_menu = menu.add_submenu("&File", true);
+/// file_menu.add_item_with_id("file.save", "&Save", true, None);
+///
+/// if let Ok(event) = muda::menu_event_receiver().try_recv() {
+///     match event.id.as_str() {
+///         "file.save" => println!("Save menu item activated"),
+///         _ => {}
+///     }
+/// }
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MenuId {
+    id: String,
+    generated: bool,
+}
+
+const GENERATED_ID_PREFIX: &str = "__muda:";
+
+impl MenuId {
+    /// Creates a new id from a string.
+    ///
+    /// ```
+    /// # use muda::MenuId;
+    /// assert_eq!(MenuId::new("file.save"), "file.save");
+    /// assert_eq!(MenuId::from(42), MenuId::new("42"));
+    /// ```
+    pub fn new<S: Into<String>>(id: S) -> Self {
+        Self {
+            id: id.into(),
+            generated: false,
+        }
+    }
+
+    /// Creates the id of a menu item that wasn't given one.
+    pub(crate) fn generated(id: u64) -> Self {
+        Self {
+            id: format!("{}{}", GENERATED_ID_PREFIX, id),
+            generated: true,
+        }
+    }
+
+    /// Returns the id as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.id
+    }
+}
+
+impl AsRef<str> for MenuId {
+    fn as_ref(&self) -> &str {
+        &self.id
+    }
+}
+
+impl std::fmt::Display for MenuId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.id)
+    }
+}
+
+/// Serializes the id as its string, see [`MenuId::as_str`].
+#[cfg(feature = "serde")]
+impl serde::Serialize for MenuId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.id)
+    }
+}
+
+/// Deserialized ids are always stable ids, see [`MenuId`].
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for MenuId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <String as serde::Deserialize>::deserialize(deserializer).map(Self::new)
+    }
+}
+
+impl From<&str> for MenuId {
+    fn from(id: &str) -> Self {
+        Self::new(id)
+    }
+}
+
+impl From<String> for MenuId {
+    fn from(id: String) -> Self {
+        Self::new(id)
+    }
+}
+
+impl From<&MenuId> for MenuId {
+    fn from(id: &MenuId) -> Self {
+        id.clone()
+    }
+}
+
+macro_rules! menu_id_from_integer {
+    ($($integer:ty),*) => {
+        $(
+            impl From<$integer> for MenuId {
+                fn from(id: $integer) -> Self {
+                    Self::new(id.to_string())
+                }
+            }
+        )*
+    };
+}
+
+menu_id_from_integer!(i32, i64, u32, u64, usize);
+
+impl PartialEq<str> for MenuId {
+    fn eq(&self, other: &str) -> bool {
+        self.id == other
+    }
+}
+
+impl PartialEq<&str> for MenuId {
+    fn eq(&self, other: &&str) -> bool {
+        self.id == *other
+    }
+}
+
+impl PartialEq<String> for MenuId {
+    fn eq(&self, other: &String) -> bool {
+        &self.id == other
+    }
+}
+
+/// An error returned when inserting, removing or moving the items of a [`Menu`] or [`Submenu`],
+/// or when changing an item that was removed.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MenuError {
+    /// The item, or the submenu the operation was called on, was removed from its menu.
+    ItemRemoved,
+    /// The item isn't a child of the menu or submenu the operation was called on.
+    NotAChild,
+    /// The position is past the end of the menu, which has `len` items.
+    InvalidPosition { position: usize, len: usize },
+}
+
+impl std::fmt::Display for MenuError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MenuError::ItemRemoved => write!(f, "[MenuError]: The menu item was removed"),
+            MenuError::NotAChild => {
+                write!(f, "[MenuError]: The menu item isn't a child of this menu")
+            }
+            MenuError::InvalidPosition { position, len } => write!(
+                f,
+                "[MenuError]: Position {} is out of bounds for a menu with {} items",
+                position, len
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MenuError {}
+
+/// Fails with [`MenuError::InvalidPosition`] if `position` isn't the position of an item in a menu with `len` items.
+pub(crate) fn check_position(position: usize, len: usize) -> Result<(), MenuError> {
+    if position < len {
+        Ok(())
+    } else {
+        Err(MenuError::InvalidPosition { position, len })
+    }
+}
+
+/// Like [`check_position`] but also accepts inserting after the last item.
+pub(crate) fn check_insert_position(position: usize, len: usize) -> Result<(), MenuError> {
+    if position <= len {
+        Ok(())
+    } else {
+        Err(MenuError::InvalidPosition { position, len })
+    }
+}
+
+/// A menu item that can be removed from or moved within its parent menu,
+/// see [`Submenu::remove`] and [`Submenu::move_item`].
+///
+/// This trait is sealed and implemented by [`Submenu`], [`MenuItem`], [`CheckMenuItem`] and [`RadioMenuItem`].
+pub trait IsMenuItem: sealed::Sealed {}
+
+mod sealed {
+    use crate::platform_impl;
+
+    pub enum MenuChild<'a> {
+        Submenu(&'a platform_impl::Submenu),
+        MenuItem(&'a platform_impl::MenuItem),
+        CheckMenuItem(&'a platform_impl::CheckMenuItem),
+        RadioMenuItem(&'a platform_impl::RadioMenuItem),
+    }
+
+    pub trait Sealed {
+        fn as_child(&self) -> MenuChild<'_>;
+    }
+}
+
+impl sealed::Sealed for Submenu {
+    fn as_child(&self) -> sealed::MenuChild<'_> {
+        sealed::MenuChild::Submenu(&self.0)
+    }
+}
+
+impl sealed::Sealed for MenuItem {
+    fn as_child(&self) -> sealed::MenuChild<'_> {
+        sealed::MenuChild::MenuItem(&self.0)
+    }
+}
+
+impl sealed::Sealed for CheckMenuItem {
+    fn as_child(&self) -> sealed::MenuChild<'_> {
+        sealed::MenuChild::CheckMenuItem(&self.0)
+    }
+}
+
+impl sealed::Sealed for RadioMenuItem {
+    fn as_child(&self) -> sealed::MenuChild<'_> {
+        sealed::MenuChild::RadioMenuItem(&self.0)
+    }
+}
+
+impl IsMenuItem for Submenu {}
+impl IsMenuItem for MenuItem {}
+impl IsMenuItem for CheckMenuItem {}
+impl IsMenuItem for RadioMenuItem {}
+
+/// An item of a [`Menu`] or [`Submenu`], as returned by [`Menu::items`] and [`Menu::find_by_id`].
+#[derive(Clone)]
+pub enum MenuItemKind {
+    Submenu(Submenu),
+    MenuItem(MenuItem),
+    CheckMenuItem(CheckMenuItem),
+    RadioMenuItem(RadioMenuItem),
+    NativeMenuItem(NativeMenuItem),
+}
+
+impl MenuItemKind {
+    /// Returns the [`MenuId`] of a [`MenuItem`], [`CheckMenuItem`] or [`RadioMenuItem`].
+    pub fn id(&self) -> Option<MenuId> {
+        match self {
+            MenuItemKind::MenuItem(item) => Some(item.id()),
+            MenuItemKind::CheckMenuItem(item) => Some(item.id()),
+            MenuItemKind::RadioMenuItem(item) => Some(item.id()),
+            _ => None,
+        }
+    }
+
+    pub fn as_submenu(&self) -> Option<&Submenu> {
+        match self {
+            MenuItemKind::Submenu(submenu) => Some(submenu),
+            _ => None,
+        }
+    }
+
+    pub fn as_menu_item(&self) -> Option<&MenuItem> {
+        match self {
+            MenuItemKind::MenuItem(item) => Some(item),
+            _ => None,
+        }
+    }
+
+    pub fn as_check_menu_item(&self) -> Option<&CheckMenuItem> {
+        match self {
+            MenuItemKind::CheckMenuItem(item) => Some(item),
+            _ => None,
+        }
+    }
+
+    pub fn as_radio_menu_item(&self) -> Option<&RadioMenuItem> {
+        match self {
+            MenuItemKind::RadioMenuItem(item) => Some(item),
+            _ => None,
+        }
+    }
+
+    /// Activates a menu item like clicking it, does nothing for submenus and [`NativeMenuItem`]s.
+    pub(crate) fn activate(&self) {
+        match self {
+            MenuItemKind::MenuItem(item) => item.0.activate(),
+            MenuItemKind::CheckMenuItem(item) => item.0.activate(),
+            MenuItemKind::RadioMenuItem(item) => item.0.activate(),
+            MenuItemKind::Submenu(_) | MenuItemKind::NativeMenuItem(_) => {}
+        }
+    }
 }
 
 /// This is the root menu type to which you can add
 /// more submenus and later be add to the top of a window (on Windows and Linux)
-/// or used as the menubar menu (on macOS) or displayed as a popup menu.
+/// or used as the menubar menu (on macOS), and its submenus can be displayed as popup menus,
+/// see [`Submenu::show_context_menu_for_gtk_window`].
 ///
 /// # Example
 ///
@@ -93,12 +468,44 @@ pub struct MenuEvent {
 /// let edit_menu = menu.add_submenu("Edit", true);
 /// ```
 #[derive(Clone)]
-pub struct Menu(platform_impl::Menu);
+pub struct Menu(platform_impl::Menu, MenuTree);
 
 impl Menu {
     /// Creates a new root menu.
     pub fn new() -> Self {
-        Self(platform_impl::Menu::new())
+        Self(platform_impl::Menu::new(), MenuTree::default())
+    }
+
+    /// Creates a menu from a TOML or JSON definition, see the [`definition`] module for the format,
+    /// and returns it with the handles of the submenus and items which have an id, keyed by id.
+    ///
+    /// Fails if the definition is invalid, in which case nothing is created.
+    ///
+    /// ```no_run
+    /// # use muda::{Menu, MenuId};
+    /// #
+    /// let (menu, items) = Menu::from_definition(
+    ///     r#"
+    ///     [[submenus]]
+    ///     label = "&File"
+    ///     items = [
+    ///         { type = "item", label = "&Save", id = "file.save", accelerator = "CmdOrCtrl+S" },
+    ///         { type = "separator" },
+    ///         { type = "native", item = "Quit" },
+    ///     ]
+    ///     "#,
+    /// )
+    /// .unwrap();
+    /// let save = items[&MenuId::new("file.save")].as_menu_item().unwrap();
+    /// ```
+    #[cfg(feature = "definition")]
+    pub fn from_definition(
+        definition: &str,
+    ) -> Result<
+        (Menu, std::collections::HashMap<MenuId, MenuItemKind>),
+        definition::MenuDefinitionError,
+    > {
+        definition::load(definition)
     }
 
     /// Creates a new [`Submenu`] whithin this menu.
@@ -110,7 +517,56 @@ impl Menu {
     /// and the `&` character is not displayed on menu label.
     /// Then the menu can be activated by press `Alt+F`.
     pub fn add_submenu<S: AsRef<str>>(&mut self, label: S, enabled: bool) -> Submenu {
-        Submenu(self.0.add_submenu(label, enabled))
+        let submenu = Submenu(self.0.add_submenu(label, enabled), self.1.submenu());
+        self.1.push(MenuItemKind::Submenu(submenu.clone()));
+        submenu
+    }
+
+    /// Creates a new [`Submenu`] at `position` whithin this menu, see [`Menu::add_submenu`].
+    ///
+    /// Fails with [`MenuError::InvalidPosition`] if `position` is greater than the number of submenus.
+    pub fn insert_submenu<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        position: usize,
+    ) -> Result<Submenu, MenuError> {
+        let submenu = self.0.insert_submenu(label, enabled, position)?;
+        let submenu = Submenu(submenu, self.1.submenu());
+        self.1
+            .insert(position, MenuItemKind::Submenu(submenu.clone()));
+        Ok(submenu)
+    }
+
+    /// Removes a [`Submenu`] from this menu, with all of its items and their accelerators.
+    ///
+    /// The removed submenu and its items can't be added back, and changing them,
+    /// or inserting, removing or moving their items fails with [`MenuError::ItemRemoved`].
+    /// Items added to a removed submenu are removed right away.
+    pub fn remove<I: IsMenuItem>(&mut self, item: &I) -> Result<(), MenuError> {
+        let position = self.0.remove(item.as_child())?;
+        self.1.remove(position);
+        Ok(())
+    }
+
+    /// Removes the submenu at `position`, see [`Menu::remove`].
+    pub fn remove_at(&mut self, position: usize) -> Result<(), MenuError> {
+        self.0.remove_at(position)?;
+        self.1.remove(position);
+        Ok(())
+    }
+
+    /// Moves a [`Submenu`] of this menu to `position`, which is its position once moved.
+    pub fn move_item<I: IsMenuItem>(&mut self, item: &I, position: usize) -> Result<(), MenuError> {
+        let from = self.0.move_item(item.as_child(), position)?;
+        self.1.move_item(from, position);
+        Ok(())
+    }
+
+    /// Removes all the submenus of this menu, see [`Menu::remove`].
+    pub fn clear(&mut self) {
+        self.0.clear();
+        self.1.clear();
     }
 
     /// Adds this menu to a [`gtk::ApplicationWindow`]
@@ -133,7 +589,7 @@ impl Menu {
         W: gtk::prelude::IsA<gtk::Container>,
         W: gtk::prelude::IsA<gtk::Window>,
     {
-        self.0.init_for_gtk_window(w)
+        self.0.init_for_gtk_window(w, &self.1)
     }
 
     /// Adds this menu to a win32 window.
@@ -188,6 +644,165 @@ impl Menu {
         self.0.remove_for_hwnd(hwnd)
     }
 
+    /// Walks this menu and all of its submenus and returns the conflicts between the accelerators
+    /// of its menu items, the accelerators of its [`NativeMenuItem`]s and the accelerators reserved
+    /// by the platform, like `F10` which activates the menubar on Windows and Linux.
+    /// Accelerators that can't be registered with the platform, and thus won't work, are reported as well.
+    ///
+    /// This is meant to be used in debug builds and tests.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use muda::{accelerator::AcceleratorConflict, Menu};
+    /// let mut menu = Menu::new();
+    /// let mut file_menu = menu.add_submenu("&File", true);
+    /// file_menu.add_item("&Save", true, "Ctrl+S".parse().ok());
+    /// file_menu.add_item("Save &All", true, "Ctrl+S".parse().ok());
+    ///
+    /// assert!(matches!(
+    ///     menu.accelerator_conflicts().as_slice(),
+    ///     [AcceleratorConflict::Duplicate { items, .. }] if items == &["File/Save", "File/Save All"]
+    /// ));
+    /// ```
+    pub fn accelerator_conflicts(&self) -> Vec<AcceleratorConflict> {
+        let mut bindings = Vec::new();
+        let mut native_items = Vec::new();
+        self.1
+            .collect_bindings("", &mut bindings, &mut native_items);
+        conflicts::find_conflicts(
+            &bindings,
+            &native_items,
+            &conflicts::reserved_accelerators(),
+            platform_impl::is_accelerator_supported,
+        )
+    }
+
+    /// Returns the submenus of this menu, in order.
+    pub fn items(&self) -> Vec<MenuItemKind> {
+        self.1.items()
+    }
+
+    /// Finds the [`MenuItem`], [`CheckMenuItem`] or [`RadioMenuItem`] with the given id in this menu or any of its submenus,
+    /// without walking the menu tree.
+    ///
+    /// If several items share the id, the one added first is returned.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use muda::Menu;
+    /// let mut menu = Menu::new();
+    /// let mut file_menu = menu.add_submenu("&File", true);
+    /// file_menu.add_item_with_id("file.save", "&Save", true, None);
+    ///
+    /// let save_item = menu.find_by_id("file.save").unwrap();
+    /// assert_eq!(save_item.as_menu_item().unwrap().label(), "&Save");
+    /// ```
+    pub fn find_by_id<I: Into<MenuId>>(&self, id: I) -> Option<MenuItemKind> {
+        self.1.find_by_id(&id.into())
+    }
+
+    /// Finds an item by the labels leading to it joined with `/`, without the `&` mnemonic markers,
+    /// for example `"File/Export/PNG"`. [`NativeMenuItem`]s are found by their displayed label.
+    ///
+    /// If several items have the same path, the first one is returned.
+    pub fn find_by_path(&self, path: &str) -> Option<MenuItemKind> {
+        self.1.find_by_path(path)
+    }
+
+    /// Applies a user [`Keymap`] over the accelerators the menu items were created with,
+    /// and returns the keymap entries that don't match any menu item and the conflicts
+    /// involving menu items whose accelerator was set by the keymap.
+    ///
+    /// Calling this again, for example after the keymap file changed, replaces the previous keymap,
+    /// items that are no longer in the keymap get back the accelerator they were created with.
+    /// Only the items that exist when this is called are affected, so call it after building the menu.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use muda::{keymap::Keymap, Menu};
+    /// let mut menu = Menu::new();
+    /// let mut file_menu = menu.add_submenu("&File", true);
+    /// file_menu.add_item("&Save", true, "Ctrl+S".parse().ok());
+    ///
+    /// let keymap = Keymap::load(Keymap::default_path("my-app").unwrap()).unwrap();
+    /// for issue in menu.set_keymap(&keymap) {
+    ///     eprintln!("keymap: {:?}", issue);
+    /// }
+    /// ```
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    #[cfg(target_os = "linux")]
+    pub fn set_keymap(&self, keymap: &Keymap) -> Vec<KeymapIssue> {
+        let (mut issues, overridden) = self.0.set_keymap(keymap);
+        issues.extend(
+            self.accelerator_conflicts()
+                .into_iter()
+                .filter(|conflict| {
+                    conflict
+                        .items()
+                        .iter()
+                        .any(|item| overridden.iter().any(|path| path == item))
+                })
+                .map(KeymapIssue::Conflict),
+        );
+        issues
+    }
+
+    /// Activates the enabled menu item whose accelerator matches a key press,
+    /// and returns whether a menu item was activated, in which case the key press should be consumed.
+    ///
+    /// This is meant for applications that receive key events the menu doesn't see,
+    /// for example when using winit, and should be called with every key press.
+    /// Disabled items and items inside a disabled submenu are never activated,
+    /// activating an item behaves like clicking it and emits its [`MenuEvent`].
+    /// Only accelerators are matched, not chords, and modifier sides, see [`Accelerator::with_sides`], are ignored.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use muda::{accelerator::{Code, Modifiers}, Menu};
+    /// let mut menu = Menu::new();
+    /// let mut file_menu = menu.add_submenu("&File", true);
+    /// file_menu.add_item("&Save", true, "Ctrl+S".parse().ok());
+    ///
+    /// // in the key press handler of the window
+    /// let consumed = menu.dispatch_key(Modifiers::CONTROL, Code::KeyS);
+    /// ```
+    pub fn dispatch_key(&self, modifiers: Modifiers, code: Code) -> bool {
+        self.dispatch(&KeyPress {
+            mods: modifiers,
+            code,
+            key: None,
+            sides: None,
+        })
+    }
+
+    /// Like [`Menu::dispatch_key`], but also matches accelerators using a logical [`Key`]
+    /// against the key produced by the key press.
+    pub fn dispatch_logical_key(&self, modifiers: Modifiers, code: Code, key: Key) -> bool {
+        self.dispatch(&KeyPress {
+            mods: modifiers,
+            code,
+            key: Some(key),
+            sides: None,
+        })
+    }
+
+    fn dispatch(&self, key: &KeyPress) -> bool {
+        match self.1.find_by_accelerator(key) {
+            Some(item) => {
+                item.activate();
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Hides this menu from a [`gtk::ApplicationWindow`]
     #[cfg(target_os = "linux")]
     pub fn hide_for_gtk_window<W>(&self, w: &W)
@@ -233,7 +848,7 @@ impl Menu {
 
 /// This is a Submenu within another [`Submenu`] or [`Menu`].
 #[derive(Clone)]
-pub struct Submenu(platform_impl::Submenu);
+pub struct Submenu(platform_impl::Submenu, MenuTree);
 
 impl Submenu {
     /// Gets the submenus's current label.
@@ -242,7 +857,7 @@ impl Submenu {
     }
 
     /// Sets a new label for the submenu.
-    pub fn set_label<S: AsRef<str>>(&mut self, label: S) {
+    pub fn set_label<S: AsRef<str>>(&mut self, label: S) -> Result<(), MenuError> {
         self.0.set_label(label)
     }
 
@@ -252,10 +867,109 @@ impl Submenu {
     }
 
     /// Enables or disables the submenu
-    pub fn set_enabled(&mut self, enabled: bool) {
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<(), MenuError> {
         self.0.set_enabled(enabled)
     }
 
+    /// Sets the icon displayed next to the submenu label, `None` removes it.
+    pub fn set_icon(&mut self, icon: Option<Icon>) -> Result<(), MenuError> {
+        self.0.set_icon(icon)
+    }
+
+    /// Gets the submenu's current tooltip.
+    pub fn tooltip(&self) -> Option<String> {
+        self.0.tooltip()
+    }
+
+    /// Sets the tooltip displayed when hovering the submenu, `None` removes it.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Windows:** Unsupported, the tooltip is only stored.
+    pub fn set_tooltip(&mut self, tooltip: Option<&str>) -> Result<(), MenuError> {
+        self.0.set_tooltip(tooltip)
+    }
+
+    /// Shows this submenu as a context menu at `x` and `y`, relative to the top left corner of `w`,
+    /// which can be any realized widget of a window.
+    ///
+    /// Activating an item of the menu emits its [`MenuEvent`] as usual, and a
+    /// [`ContextMenuClosedEvent`] is emitted once the menu is closed.
+    #[cfg(target_os = "linux")]
+    pub fn show_context_menu_for_gtk_window<W>(&self, w: &W, x: f64, y: f64)
+    where
+        W: gtk::prelude::IsA<gtk::Widget>,
+    {
+        self.0.show_context_menu_for_gtk_window(w, x, y)
+    }
+
+    /// Shows this submenu as a context menu anchored to `rect`, relative to the top left corner of `w`.
+    ///
+    /// The `menu_anchor` point of the menu is placed on the `rect_anchor` point of `rect`, for example
+    /// [`NorthWest`](gdk::Gravity::NorthWest) and [`SouthWest`](gdk::Gravity::SouthWest) place
+    /// the menu below `rect`. The menu is flipped or moved when it doesn't fit on the screen.
+    ///
+    /// See [`Submenu::show_context_menu_for_gtk_window`] for the emitted events.
+    #[cfg(target_os = "linux")]
+    pub fn show_context_menu_for_gtk_window_at_rect<W>(
+        &self,
+        w: &W,
+        rect: gdk::Rectangle,
+        rect_anchor: gdk::Gravity,
+        menu_anchor: gdk::Gravity,
+    ) where
+        W: gtk::prelude::IsA<gtk::Widget>,
+    {
+        self.0
+            .show_context_menu_for_gtk_window_at_rect(w, rect, rect_anchor, menu_anchor)
+    }
+
+    /// Attaches this submenu to `w` as its context menu, which is then shown when `w` is right clicked,
+    /// long pressed on a touchscreen, or when the Menu key or Shift+F10 is pressed while `w` has the focus.
+    ///
+    /// The menu is shown at the pointer or touch position, or at the center of `w` for keyboard shortcuts,
+    /// and emits the same events as [`Submenu::show_context_menu_for_gtk_window`].
+    /// Attaching a submenu to a widget it is already attached to does nothing.
+    ///
+    /// `w` needs its own [`gdk::Window`] to receive clicks, widgets that have none like `gtk::Label`
+    /// can be wrapped in a `gtk::EventBox`, and it needs to be focusable to receive key presses.
+    #[cfg(target_os = "linux")]
+    pub fn attach_context_menu<W>(&self, w: &W)
+    where
+        W: gtk::prelude::IsA<gtk::Widget>,
+    {
+        self.0.attach_context_menu(w)
+    }
+
+    /// Detaches this submenu from `w`, see [`Submenu::attach_context_menu`].
+    ///
+    /// Removing the submenu from its parent also detaches it from all its widgets.
+    #[cfg(target_os = "linux")]
+    pub fn detach_context_menu<W>(&self, w: &W)
+    where
+        W: gtk::prelude::IsA<gtk::Widget>,
+    {
+        self.0.detach_context_menu(w)
+    }
+
+    /// Shows this submenu as a context menu at `x` and `y`, relative to the client area of a win32 window.
+    ///
+    /// Activating an item of the menu emits its [`MenuEvent`] as usual, and a
+    /// [`ContextMenuClosedEvent`] is emitted once the menu is closed.
+    #[cfg(target_os = "windows")]
+    pub fn show_context_menu_for_hwnd(&self, hwnd: isize, x: f64, y: f64) {
+        self.0.show_context_menu_for_hwnd(hwnd, x, y)
+    }
+
+    /// Shows this submenu as a context menu at `x` and `y`, in the coordinate system of an `NSView`.
+    ///
+    /// Activating an item of the menu emits its [`MenuEvent`] as usual, and a
+    /// [`ContextMenuClosedEvent`] is emitted once the menu is closed.
+    #[cfg(target_os = "macos")]
+    pub fn show_context_menu_for_nsview(&self, view: cocoa::base::id, x: f64, y: f64) {
+        self.0.show_context_menu_for_nsview(view, x, y)
+    }
+
     /// Creates a new [`Submenu`] whithin this submenu.
     ///
     /// ## Platform-specific:
@@ -265,7 +979,26 @@ impl Submenu {
     /// and the `&` character is not displayed on menu label.
     /// Then the menu can be activated by press `F` when its parent menu is active.
     pub fn add_submenu<S: AsRef<str>>(&mut self, label: S, enabled: bool) -> Submenu {
-        Submenu(self.0.add_submenu(label, enabled))
+        let submenu = Submenu(self.0.add_submenu(label, enabled), self.1.submenu());
+        self.1.push(MenuItemKind::Submenu(submenu.clone()));
+        submenu
+    }
+
+    /// Creates a new [`Submenu`] at `position` whithin this submenu, see [`Submenu::add_submenu`].
+    ///
+    /// Fails with [`MenuError::InvalidPosition`] if `position` is greater than the number of items
+    /// and with [`MenuError::ItemRemoved`] if this submenu was removed.
+    pub fn insert_submenu<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        position: usize,
+    ) -> Result<Submenu, MenuError> {
+        let submenu = self.0.insert_submenu(label, enabled, position)?;
+        let submenu = Submenu(submenu, self.1.submenu());
+        self.1
+            .insert(position, MenuItemKind::Submenu(submenu.clone()));
+        Ok(submenu)
     }
 
     /// Creates a new [`MenuItem`] whithin this submenu.
@@ -282,12 +1015,156 @@ impl Submenu {
         enabled: bool,
         accelerator: Option<Accelerator>,
     ) -> MenuItem {
-        MenuItem(self.0.add_item(label, enabled, accelerator))
+        let item = MenuItem(self.0.add_item(label, enabled, accelerator, None));
+        self.1.push(MenuItemKind::MenuItem(item.clone()));
+        item
+    }
+
+    /// Creates a new [`MenuItem`] whithin this submenu with a stable id, see [`MenuId`].
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use muda::Menu;
+    /// let mut menu = Menu::new();
+    /// let mut file_menu = menu.add_submenu("&File", true);
+    /// let save_item = file_menu.add_item_with_id("file.save", "&Save", true, "Ctrl+S".parse().ok());
+    /// assert_eq!(save_item.id(), "file.save");
+    /// ```
+    pub fn add_item_with_id<I: Into<MenuId>, S: AsRef<str>>(
+        &mut self,
+        id: I,
+        label: S,
+        enabled: bool,
+        accelerator: Option<Accelerator>,
+    ) -> MenuItem {
+        let item = MenuItem(
+            self.0
+                .add_item(label, enabled, accelerator, Some(id.into())),
+        );
+        self.1.push(MenuItemKind::MenuItem(item.clone()));
+        item
+    }
+
+    /// Creates a new [`MenuItem`] at `position` whithin this submenu, see [`Submenu::add_item`].
+    ///
+    /// Positions count every item of the submenu, including [`NativeMenuItem`]s and separators.
+    /// Fails with [`MenuError::InvalidPosition`] if `position` is greater than the number of items
+    /// and with [`MenuError::ItemRemoved`] if this submenu was removed.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Windows:** [`NativeMenuItem`]s that aren't supported by the platform
+    ///   aren't added to the menu and thus don't count.
+    pub fn insert_item<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        accelerator: Option<Accelerator>,
+        position: usize,
+    ) -> Result<MenuItem, MenuError> {
+        let item = self
+            .0
+            .insert_item(label, enabled, accelerator, position, None)
+            .map(MenuItem)?;
+        self.1
+            .insert(position, MenuItemKind::MenuItem(item.clone()));
+        Ok(item)
+    }
+
+    /// Creates a new [`MenuItem`] at `position` whithin this submenu with a stable id,
+    /// see [`Submenu::insert_item`] and [`MenuId`].
+    pub fn insert_item_with_id<I: Into<MenuId>, S: AsRef<str>>(
+        &mut self,
+        id: I,
+        label: S,
+        enabled: bool,
+        accelerator: Option<Accelerator>,
+        position: usize,
+    ) -> Result<MenuItem, MenuError> {
+        let item = self
+            .0
+            .insert_item(label, enabled, accelerator, position, Some(id.into()))
+            .map(MenuItem)?;
+        self.1
+            .insert(position, MenuItemKind::MenuItem(item.clone()));
+        Ok(item)
+    }
+
+    /// Creates a new [`MenuItem`] whithin this submenu that is activated by a multi-stroke [`Chord`],
+    /// for example `Ctrl+KeyK Ctrl+KeyC`.
+    ///
+    /// Pressing the first stroke arms the chord and the next stroke activates the menu item,
+    /// pressing `Escape` or waiting longer than [`ChordMatcher::DEFAULT_TIMEOUT`](crate::accelerator::ChordMatcher::DEFAULT_TIMEOUT)
+    /// cancels it. A [`MenuEvent`] is emitted once the chord is completed.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Linux:** Only the first stroke of the chord is displayed next to the menu item label
+    ///   because gtk can't display multi-stroke accelerators.
+    /// - **Windows / macOS:** Unavailable, native menus only dispatch single accelerators.
+    ///   Use [`ChordMatcher`](crate::accelerator::ChordMatcher) with the key events of the window instead.
+    #[cfg(target_os = "linux")]
+    pub fn add_chord_item<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        chord: Chord,
+    ) -> MenuItem {
+        let item = MenuItem(self.0.add_chord_item(label, enabled, chord, None));
+        self.1.push(MenuItemKind::MenuItem(item.clone()));
+        item
+    }
+
+    /// Creates a new [`MenuItem`] whithin this submenu with an [`Icon`] next to its label,
+    /// see [`Submenu::add_item`] and [`MenuItem::set_icon`].
+    pub fn add_icon_item<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        icon: Icon,
+        accelerator: Option<Accelerator>,
+    ) -> MenuItem {
+        let mut item = self.add_item(label, enabled, accelerator);
+        let _ = item.set_icon(Some(icon));
+        item
+    }
+
+    /// Creates a new [`MenuItem`] whithin this submenu that is activated by a multi-stroke [`Chord`]
+    /// and has a stable id, see [`Submenu::add_chord_item`] and [`MenuId`].
+    #[cfg(target_os = "linux")]
+    pub fn add_chord_item_with_id<I: Into<MenuId>, S: AsRef<str>>(
+        &mut self,
+        id: I,
+        label: S,
+        enabled: bool,
+        chord: Chord,
+    ) -> MenuItem {
+        let item = MenuItem(
+            self.0
+                .add_chord_item(label, enabled, chord, Some(id.into())),
+        );
+        self.1.push(MenuItemKind::MenuItem(item.clone()));
+        item
     }
 
     /// Creates a new [`NativeMenuItem`] within this submenu.
     pub fn add_native_item(&mut self, item: NativeMenuItem) {
-        self.0.add_native_item(item)
+        if self.0.add_native_item(item.clone()) {
+            self.1.push(MenuItemKind::NativeMenuItem(item));
+        }
+    }
+
+    /// Creates a new [`NativeMenuItem`] at `position` within this submenu, see [`Submenu::insert_item`].
+    pub fn insert_native_item(
+        &mut self,
+        item: NativeMenuItem,
+        position: usize,
+    ) -> Result<(), MenuError> {
+        if self.0.insert_native_item(item.clone(), position)? {
+            self.1.insert(position, MenuItemKind::NativeMenuItem(item));
+        }
+        Ok(())
     }
 
     /// Creates a new [`CheckMenuItem`] within this submenu.
@@ -298,7 +1175,259 @@ impl Submenu {
         checked: bool,
         accelerator: Option<Accelerator>,
     ) -> CheckMenuItem {
-        CheckMenuItem(self.0.add_check_item(label, enabled, checked, accelerator))
+        let item = CheckMenuItem(
+            self.0
+                .add_check_item(label, enabled, checked, accelerator, None),
+        );
+        self.1.push(MenuItemKind::CheckMenuItem(item.clone()));
+        item
+    }
+
+    /// Creates a new [`CheckMenuItem`] within this submenu with a stable id, see [`MenuId`].
+    pub fn add_check_item_with_id<I: Into<MenuId>, S: AsRef<str>>(
+        &mut self,
+        id: I,
+        label: S,
+        enabled: bool,
+        checked: bool,
+        accelerator: Option<Accelerator>,
+    ) -> CheckMenuItem {
+        let item = CheckMenuItem(self.0.add_check_item(
+            label,
+            enabled,
+            checked,
+            accelerator,
+            Some(id.into()),
+        ));
+        self.1.push(MenuItemKind::CheckMenuItem(item.clone()));
+        item
+    }
+
+    /// Creates a new [`CheckMenuItem`] at `position` within this submenu, see [`Submenu::insert_item`].
+    pub fn insert_check_item<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        checked: bool,
+        accelerator: Option<Accelerator>,
+        position: usize,
+    ) -> Result<CheckMenuItem, MenuError> {
+        let item = self
+            .0
+            .insert_check_item(label, enabled, checked, accelerator, position, None)
+            .map(CheckMenuItem)?;
+        self.1
+            .insert(position, MenuItemKind::CheckMenuItem(item.clone()));
+        Ok(item)
+    }
+
+    /// Creates a new [`CheckMenuItem`] at `position` within this submenu with a stable id,
+    /// see [`Submenu::insert_item`] and [`MenuId`].
+    pub fn insert_check_item_with_id<I: Into<MenuId>, S: AsRef<str>>(
+        &mut self,
+        id: I,
+        label: S,
+        enabled: bool,
+        checked: bool,
+        accelerator: Option<Accelerator>,
+        position: usize,
+    ) -> Result<CheckMenuItem, MenuError> {
+        let item = self
+            .0
+            .insert_check_item(
+                label,
+                enabled,
+                checked,
+                accelerator,
+                position,
+                Some(id.into()),
+            )
+            .map(CheckMenuItem)?;
+        self.1
+            .insert(position, MenuItemKind::CheckMenuItem(item.clone()));
+        Ok(item)
+    }
+
+    /// Creates a new [`RadioMenuItem`] within this submenu that belongs to `group`.
+    ///
+    /// The item is checked, and the previously checked item of the group unchecked, if `checked`
+    /// is `true` or if the group has no checked item, for example when it is the first item of the group.
+    /// The items of a group don't need to be in the same submenu.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use muda::{Menu, RadioGroup};
+    /// let mut menu = Menu::new();
+    /// let mut view_menu = menu.add_submenu("&View", true);
+    /// let mut sort_menu = view_menu.add_submenu("&Sort by", true);
+    ///
+    /// let sort_by = RadioGroup::new();
+    /// let by_name = sort_menu.add_radio_item("&Name", true, false, &sort_by, None);
+    /// let by_date = sort_menu.add_radio_item("&Date", true, false, &sort_by, None);
+    /// let by_size = sort_menu.add_radio_item("&Size", true, false, &sort_by, None);
+    ///
+    /// assert!(by_name.checked());
+    /// assert_eq!(sort_by.checked().unwrap().id(), by_name.id());
+    /// ```
+    pub fn add_radio_item<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        checked: bool,
+        group: &RadioGroup,
+        accelerator: Option<Accelerator>,
+    ) -> RadioMenuItem {
+        let item = RadioMenuItem(self.0.add_radio_item(
+            label,
+            enabled,
+            checked,
+            &group.0,
+            accelerator,
+            None,
+        ));
+        self.1.push(MenuItemKind::RadioMenuItem(item.clone()));
+        item
+    }
+
+    /// Creates a new [`RadioMenuItem`] within this submenu with a stable id,
+    /// see [`Submenu::add_radio_item`] and [`MenuId`].
+    pub fn add_radio_item_with_id<I: Into<MenuId>, S: AsRef<str>>(
+        &mut self,
+        id: I,
+        label: S,
+        enabled: bool,
+        checked: bool,
+        group: &RadioGroup,
+        accelerator: Option<Accelerator>,
+    ) -> RadioMenuItem {
+        let item = RadioMenuItem(self.0.add_radio_item(
+            label,
+            enabled,
+            checked,
+            &group.0,
+            accelerator,
+            Some(id.into()),
+        ));
+        self.1.push(MenuItemKind::RadioMenuItem(item.clone()));
+        item
+    }
+
+    /// Creates a new [`RadioMenuItem`] at `position` within this submenu,
+    /// see [`Submenu::add_radio_item`] and [`Submenu::insert_item`].
+    pub fn insert_radio_item<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        checked: bool,
+        group: &RadioGroup,
+        accelerator: Option<Accelerator>,
+        position: usize,
+    ) -> Result<RadioMenuItem, MenuError> {
+        let item = self
+            .0
+            .insert_radio_item(
+                label,
+                enabled,
+                checked,
+                &group.0,
+                accelerator,
+                position,
+                None,
+            )
+            .map(RadioMenuItem)?;
+        self.1
+            .insert(position, MenuItemKind::RadioMenuItem(item.clone()));
+        Ok(item)
+    }
+
+    /// Creates a new [`RadioMenuItem`] at `position` within this submenu with a stable id,
+    /// see [`Submenu::insert_radio_item`] and [`MenuId`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn insert_radio_item_with_id<I: Into<MenuId>, S: AsRef<str>>(
+        &mut self,
+        id: I,
+        label: S,
+        enabled: bool,
+        checked: bool,
+        group: &RadioGroup,
+        accelerator: Option<Accelerator>,
+        position: usize,
+    ) -> Result<RadioMenuItem, MenuError> {
+        let item = self
+            .0
+            .insert_radio_item(
+                label,
+                enabled,
+                checked,
+                &group.0,
+                accelerator,
+                position,
+                Some(id.into()),
+            )
+            .map(RadioMenuItem)?;
+        self.1
+            .insert(position, MenuItemKind::RadioMenuItem(item.clone()));
+        Ok(item)
+    }
+
+    /// Removes an item from this submenu, removing a [`Submenu`] removes all of its items too.
+    ///
+    /// The accelerators and chords of the removed items stop working. Removed items can't be
+    /// added back, and changing them, or inserting, removing or moving them or their items
+    /// fails with [`MenuError::ItemRemoved`]. Items added to a removed submenu are removed right away.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use muda::Menu;
+    /// let mut menu = Menu::new();
+    /// let mut file_menu = menu.add_submenu("&File", true);
+    /// let mut recent_item = file_menu.add_item("Recent.txt", true, None);
+    ///
+    /// file_menu.remove(&recent_item).unwrap();
+    /// assert!(file_menu.remove(&recent_item).is_err());
+    /// assert!(recent_item.set_enabled(false).is_err());
+    /// ```
+    pub fn remove<I: IsMenuItem>(&mut self, item: &I) -> Result<(), MenuError> {
+        let position = self.0.remove(item.as_child())?;
+        self.1.remove(position);
+        Ok(())
+    }
+
+    /// Removes the item at `position`, including [`NativeMenuItem`]s, see [`Submenu::remove`].
+    pub fn remove_at(&mut self, position: usize) -> Result<(), MenuError> {
+        self.0.remove_at(position)?;
+        self.1.remove(position);
+        Ok(())
+    }
+
+    /// Moves an item of this submenu to `position`, which is its position once moved.
+    ///
+    /// Fails with [`MenuError::InvalidPosition`] if `position` isn't the position of an item.
+    pub fn move_item<I: IsMenuItem>(&mut self, item: &I, position: usize) -> Result<(), MenuError> {
+        let from = self.0.move_item(item.as_child(), position)?;
+        self.1.move_item(from, position);
+        Ok(())
+    }
+
+    /// Removes all the items of this submenu, see [`Submenu::remove`].
+    ///
+    /// Fails with [`MenuError::ItemRemoved`] if this submenu was removed.
+    pub fn clear(&mut self) -> Result<(), MenuError> {
+        self.0.clear()?;
+        self.1.clear();
+        Ok(())
+    }
+
+    /// Returns the items of this submenu, in order.
+    pub fn items(&self) -> Vec<MenuItemKind> {
+        self.1.items()
+    }
+
+    /// Finds an item by its path relative to this submenu, see [`Menu::find_by_path`].
+    pub fn find_by_path(&self, path: &str) -> Option<MenuItemKind> {
+        self.1.find_by_path(path)
     }
 }
 
@@ -313,7 +1442,7 @@ impl MenuItem {
     }
 
     /// Sets a new label for the menu item.
-    pub fn set_label<S: AsRef<str>>(&mut self, label: S) {
+    pub fn set_label<S: AsRef<str>>(&mut self, label: S) -> Result<(), MenuError> {
         self.0.set_label(label)
     }
 
@@ -323,14 +1452,72 @@ impl MenuItem {
     }
 
     /// Enables or disables the menu item.
-    pub fn set_enabled(&mut self, enabled: bool) {
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<(), MenuError> {
         self.0.set_enabled(enabled)
     }
 
-    /// Gets the unique id for this menu item.
-    pub fn id(&self) -> u64 {
+    /// Sets the icon displayed next to the menu item label, `None` removes it.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Linux:** The icon is packed with the label in a `gtk::Box` as `gtk::ImageMenuItem` is deprecated.
+    pub fn set_icon(&mut self, icon: Option<Icon>) -> Result<(), MenuError> {
+        self.0.set_icon(icon)
+    }
+
+    /// Gets the menu item's current tooltip.
+    pub fn tooltip(&self) -> Option<String> {
+        self.0.tooltip()
+    }
+
+    /// Sets the tooltip displayed when hovering the menu item, `None` removes it.
+    ///
+    /// Tooltips are also displayed on disabled menu items, which makes them a good place
+    /// to explain why an item is disabled.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Windows:** Unsupported, the tooltip is only stored.
+    pub fn set_tooltip(&mut self, tooltip: Option<&str>) -> Result<(), MenuError> {
+        self.0.set_tooltip(tooltip)
+    }
+
+    /// Gets the menu item's current help text.
+    pub fn help_text(&self) -> Option<String> {
+        self.0.help_text()
+    }
+
+    /// Sets the help text of the menu item, `None` removes it.
+    ///
+    /// The help text isn't displayed by the menu, it is carried by the
+    /// [`MenuHighlightEvent`]s of the item so apps can display it elsewhere, in a status bar for example.
+    pub fn set_help_text(&mut self, help_text: Option<&str>) -> Result<(), MenuError> {
+        self.0.set_help_text(help_text)
+    }
+
+    /// Gets the id of this menu item, see [`MenuId`].
+    pub fn id(&self) -> MenuId {
         self.0.id()
     }
+
+    /// Gets the menu item's current accelerator.
+    pub fn accelerator(&self) -> Option<Accelerator> {
+        self.0.accelerator()
+    }
+
+    /// Fails with [`UnsupportedAcceleratorError`] if the accelerator of the menu item can't be
+    /// registered on this platform, the menu item is then displayed without it and only activated by clicking it.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Linux:** Once the menu item is added to a menu this reports whether registering it with gtk failed,
+    ///   which depends on the current keyboard layout. Before that, physical keys are checked against a US layout
+    ///   if gtk isn't initialized yet.
+    ///   [`Modifiers::HYPER`](accelerator::Modifiers::HYPER) is only supported when it is bound to a modifier
+    ///   of its own, default xkb setups bind it to the same modifier as Super.
+    pub fn check_accelerator(&self) -> Result<(), UnsupportedAcceleratorError> {
+        self.0.check_accelerator()
+    }
 }
 
 /// This is a menu item with a checkmark icon within a [`Submenu`].
@@ -344,7 +1531,7 @@ impl CheckMenuItem {
     }
 
     /// Sets a new label for the menu item.
-    pub fn set_label<S: AsRef<str>>(&mut self, label: S) {
+    pub fn set_label<S: AsRef<str>>(&mut self, label: S) -> Result<(), MenuError> {
         self.0.set_label(label)
     }
 
@@ -354,7 +1541,7 @@ impl CheckMenuItem {
     }
 
     /// Enables or disables the menu item.
-    pub fn set_enabled(&mut self, enabled: bool) {
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<(), MenuError> {
         self.0.set_enabled(enabled)
     }
 
@@ -364,19 +1551,219 @@ impl CheckMenuItem {
     }
 
     /// Enables or disables the menu item.
-    pub fn set_checked(&mut self, checked: bool) {
+    pub fn set_checked(&mut self, checked: bool) -> Result<(), MenuError> {
         self.0.set_checked(checked)
     }
 
-    /// Gets the unique id for this menu item.
-    pub fn id(&self) -> u64 {
+    /// Sets the icon displayed next to the menu item label, `None` removes it.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Windows:** The icon is displayed instead of the checkmark,
+    ///   with a highlighted background when the menu item is checked.
+    pub fn set_icon(&mut self, icon: Option<Icon>) -> Result<(), MenuError> {
+        self.0.set_icon(icon)
+    }
+
+    /// Gets the menu item's current tooltip.
+    pub fn tooltip(&self) -> Option<String> {
+        self.0.tooltip()
+    }
+
+    /// Sets the tooltip displayed when hovering the menu item, `None` removes it.
+    ///
+    /// Tooltips are also displayed on disabled menu items, which makes them a good place
+    /// to explain why an item is disabled.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Windows:** Unsupported, the tooltip is only stored.
+    pub fn set_tooltip(&mut self, tooltip: Option<&str>) -> Result<(), MenuError> {
+        self.0.set_tooltip(tooltip)
+    }
+
+    /// Gets the menu item's current help text.
+    pub fn help_text(&self) -> Option<String> {
+        self.0.help_text()
+    }
+
+    /// Sets the help text of the menu item, `None` removes it.
+    ///
+    /// The help text isn't displayed by the menu, it is carried by the
+    /// [`MenuHighlightEvent`]s of the item so apps can display it elsewhere, in a status bar for example.
+    pub fn set_help_text(&mut self, help_text: Option<&str>) -> Result<(), MenuError> {
+        self.0.set_help_text(help_text)
+    }
+
+    /// Gets the id of this menu item, see [`MenuId`].
+    pub fn id(&self) -> MenuId {
         self.0.id()
     }
+
+    /// Gets the menu item's current accelerator.
+    pub fn accelerator(&self) -> Option<Accelerator> {
+        self.0.accelerator()
+    }
+
+    /// Fails with [`UnsupportedAcceleratorError`] if the accelerator of the menu item can't be
+    /// registered on this platform, the menu item is then displayed without it and only activated by clicking it.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Linux:** Once the menu item is added to a menu this reports whether registering it with gtk failed,
+    ///   which depends on the current keyboard layout. Before that, physical keys are checked against a US layout
+    ///   if gtk isn't initialized yet.
+    ///   [`Modifiers::HYPER`](accelerator::Modifiers::HYPER) is only supported when it is bound to a modifier
+    ///   of its own, default xkb setups bind it to the same modifier as Super.
+    pub fn check_accelerator(&self) -> Result<(), UnsupportedAcceleratorError> {
+        self.0.check_accelerator()
+    }
+}
+
+/// A group of [`RadioMenuItem`]s of which only one is checked at a time,
+/// checking one of them unchecks the others.
+///
+/// Clones of a group refer to the same group.
+#[derive(Clone, Default)]
+pub struct RadioGroup(platform_impl::RadioGroup);
+
+impl RadioGroup {
+    /// Creates a new empty group, see [`Submenu::add_radio_item`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the checked item of the group, `None` if the group has no items.
+    pub fn checked(&self) -> Option<RadioMenuItem> {
+        self.0.checked().map(RadioMenuItem)
+    }
+}
+
+/// This is a menu item within a [`Submenu`] that belongs to a [`RadioGroup`],
+/// only one item of a group is checked at a time.
+///
+/// Activating an item that isn't checked checks it, unchecks the other items of its group
+/// and emits its [`MenuEvent`], activating the checked item does nothing.
+#[derive(Clone)]
+pub struct RadioMenuItem(platform_impl::RadioMenuItem);
+
+impl RadioMenuItem {
+    /// Gets the menu item's current label.
+    pub fn label(&self) -> String {
+        self.0.label()
+    }
+
+    /// Sets a new label for the menu item.
+    pub fn set_label<S: AsRef<str>>(&mut self, label: S) -> Result<(), MenuError> {
+        self.0.set_label(label)
+    }
+
+    /// Gets the menu item's current state, whether enabled or not.
+    pub fn enabled(&self) -> bool {
+        self.0.enabled()
+    }
+
+    /// Enables or disables the menu item.
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<(), MenuError> {
+        self.0.set_enabled(enabled)
+    }
+
+    /// Gets the menu item's current state, whether checked or not.
+    pub fn checked(&self) -> bool {
+        self.0.checked()
+    }
+
+    /// Sets the icon displayed next to the menu item label, `None` removes it.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Windows:** The icon is displayed instead of the radio mark,
+    ///   with a highlighted background when the menu item is checked.
+    pub fn set_icon(&mut self, icon: Option<Icon>) -> Result<(), MenuError> {
+        self.0.set_icon(icon)
+    }
+
+    /// Gets the menu item's current tooltip.
+    pub fn tooltip(&self) -> Option<String> {
+        self.0.tooltip()
+    }
+
+    /// Sets the tooltip displayed when hovering the menu item, `None` removes it.
+    ///
+    /// Tooltips are also displayed on disabled menu items, which makes them a good place
+    /// to explain why an item is disabled.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Windows:** Unsupported, the tooltip is only stored.
+    pub fn set_tooltip(&mut self, tooltip: Option<&str>) -> Result<(), MenuError> {
+        self.0.set_tooltip(tooltip)
+    }
+
+    /// Gets the menu item's current help text.
+    pub fn help_text(&self) -> Option<String> {
+        self.0.help_text()
+    }
+
+    /// Sets the help text of the menu item, `None` removes it.
+    ///
+    /// The help text isn't displayed by the menu, it is carried by the
+    /// [`MenuHighlightEvent`]s of the item so apps can display it elsewhere, in a status bar for example.
+    pub fn set_help_text(&mut self, help_text: Option<&str>) -> Result<(), MenuError> {
+        self.0.set_help_text(help_text)
+    }
+
+    /// Checks the menu item and unchecks the other items of its group, without emitting a [`MenuEvent`].
+    pub fn check(&mut self) -> Result<(), MenuError> {
+        self.0.check()
+    }
+
+    /// Gets the group of this menu item.
+    pub fn group(&self) -> RadioGroup {
+        RadioGroup(self.0.group())
+    }
+
+    /// Gets the id of this menu item, see [`MenuId`].
+    pub fn id(&self) -> MenuId {
+        self.0.id()
+    }
+
+    /// Gets the menu item's current accelerator.
+    pub fn accelerator(&self) -> Option<Accelerator> {
+        self.0.accelerator()
+    }
+
+    /// Fails with [`UnsupportedAcceleratorError`] if the accelerator of the menu item can't be
+    /// registered on this platform, the menu item is then displayed without it and only activated by clicking it.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Linux:** Once the menu item is added to a menu this reports whether registering it with gtk failed,
+    ///   which depends on the current keyboard layout. Before that, physical keys are checked against a US layout
+    ///   if gtk isn't initialized yet.
+    ///   [`Modifiers::HYPER`](accelerator::Modifiers::HYPER) is only supported when it is bound to a modifier
+    ///   of its own, default xkb setups bind it to the same modifier as Super.
+    pub fn check_accelerator(&self) -> Result<(), UnsupportedAcceleratorError> {
+        self.0.check_accelerator()
+    }
+}
+
+/// Fails if `accelerator` can't be registered on this platform.
+pub(crate) fn check_accelerator(
+    accelerator: Option<Accelerator>,
+) -> Result<(), UnsupportedAcceleratorError> {
+    match accelerator {
+        Some(accelerator) if !platform_impl::is_accelerator_supported(&accelerator) => {
+            Err(UnsupportedAcceleratorError::new(accelerator))
+        }
+        _ => Ok(()),
+    }
 }
 
 /// This is a Native menu item within a [`Submenu`] with a predefined behavior.
 #[non_exhaustive]
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum NativeMenuItem {
     /// A native “About” menu item.
     ///
@@ -480,6 +1867,7 @@ pub enum NativeMenuItem {
 ///
 /// - **macOS:** The metadata is ignored.
 #[derive(PartialEq, Eq, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AboutMetadata {
     /// The application name.
     pub version: Option<String>,
@@ -1,7 +1,11 @@
 use gtk::{prelude::*, AccelGroup};
-use keyboard_types::{Code, Modifiers};
+use keyboard_types::{Code, Key, Modifiers};
 
-use crate::accelerator::Accelerator;
+use super::icon::gtk_label;
+use crate::accelerator::{
+    keys::{code_to_char, code_to_keysym_name, code_to_named_key, named_key_to_code},
+    Accelerator, AcceleratorKey, UnsupportedAcceleratorError, ALL_CODES,
+};
 
 pub fn to_gtk_menemenoic<S: AsRef<str>>(string: S) -> String {
     string
@@ -15,73 +19,149 @@ pub fn register_accelerator<M: IsA<gtk::Widget>>(
     item: &M,
     accel_group: &AccelGroup,
     menu_key: &Accelerator,
+) -> Result<(), UnsupportedAcceleratorError> {
+    let (accel_key, accel_mods) = to_gtk_accelerator(menu_key)?;
+    if is_matched_by_gtk(menu_key) {
+        item.add_accelerator(
+            "activate",
+            accel_group,
+            accel_key,
+            accel_mods,
+            gtk::AccelFlags::VISIBLE,
+        );
+    } else {
+        set_accel_label(item, accel_key, accel_mods);
+    }
+    Ok(())
+}
+
+pub fn unregister_accelerator<M: IsA<gtk::Widget>>(
+    item: &M,
+    accel_group: &AccelGroup,
+    menu_key: &Accelerator,
 ) {
-    let accel_key = match &menu_key.key {
-        Code::KeyA => 'A' as u32,
-        Code::KeyB => 'B' as u32,
-        Code::KeyC => 'C' as u32,
-        Code::KeyD => 'D' as u32,
-        Code::KeyE => 'E' as u32,
-        Code::KeyF => 'F' as u32,
-        Code::KeyG => 'G' as u32,
-        Code::KeyH => 'H' as u32,
-        Code::KeyI => 'I' as u32,
-        Code::KeyJ => 'J' as u32,
-        Code::KeyK => 'K' as u32,
-        Code::KeyL => 'L' as u32,
-        Code::KeyM => 'M' as u32,
-        Code::KeyN => 'N' as u32,
-        Code::KeyO => 'O' as u32,
-        Code::KeyP => 'P' as u32,
-        Code::KeyQ => 'Q' as u32,
-        Code::KeyR => 'R' as u32,
-        Code::KeyS => 'S' as u32,
-        Code::KeyT => 'T' as u32,
-        Code::KeyU => 'U' as u32,
-        Code::KeyV => 'V' as u32,
-        Code::KeyW => 'W' as u32,
-        Code::KeyX => 'X' as u32,
-        Code::KeyY => 'Y' as u32,
-        Code::KeyZ => 'Z' as u32,
-        Code::Digit0 => '0' as u32,
-        Code::Digit1 => '1' as u32,
-        Code::Digit2 => '2' as u32,
-        Code::Digit3 => '3' as u32,
-        Code::Digit4 => '4' as u32,
-        Code::Digit5 => '5' as u32,
-        Code::Digit6 => '6' as u32,
-        Code::Digit7 => '7' as u32,
-        Code::Digit8 => '8' as u32,
-        Code::Digit9 => '9' as u32,
-        Code::Comma => ',' as u32,
-        Code::Minus => '-' as u32,
-        Code::Period => '.' as u32,
-        Code::Space => ' ' as u32,
-        Code::Equal => '=' as u32,
-        Code::Semicolon => ';' as u32,
-        Code::Slash => '/' as u32,
-        Code::Backslash => '\\' as u32,
-        Code::Quote => '\'' as u32,
-        Code::Backquote => '`' as u32,
-        Code::BracketLeft => '[' as u32,
-        Code::BracketRight => ']' as u32,
-        k => {
-            if let Some(gdk_key) = key_to_raw_key(k) {
-                *gdk_key
-            } else {
-                dbg!("Cannot map key {:?}", k);
-                return;
-            }
+    if let Ok((accel_key, accel_mods)) = to_gtk_accelerator(menu_key) {
+        if is_matched_by_gtk(menu_key) {
+            item.remove_accelerator(accel_group, accel_key, accel_mods);
+        } else {
+            set_accel_label(item, 0, gdk::ModifierType::empty());
         }
+    }
+}
+
+/// Returns whether gtk can match `accelerator`, gtk accel groups can't tell the left and right
+/// modifier keys apart and ignore `MOD5_MASK` which AltGr sets, so those accelerators are matched
+/// by the `key-press-event` handler of the menu instead.
+pub fn is_matched_by_gtk(accelerator: &Accelerator) -> bool {
+    accelerator.sides().is_empty() && !accelerator.mods.contains(Modifiers::ALT_GRAPH)
+}
+
+/// Displays an accelerator on a menu item without registering it with gtk.
+pub fn set_accel_label<M: IsA<gtk::Widget>>(item: &M, key: u32, modifiers: gdk::ModifierType) {
+    if let Some(label) = gtk_label(item) {
+        label.set_accel(key, modifiers);
+    }
+}
+
+/// Converts an [`Accelerator`] to a gdk keyval and modifiers pair,
+/// which can be used with [`gtk::AccelGroup`] and [`gtk::AccelLabel`].
+pub fn to_gtk_accelerator(
+    menu_key: &Accelerator,
+) -> Result<(u32, gdk::ModifierType), UnsupportedAcceleratorError> {
+    let accel_key = match &*menu_key.key() {
+        AcceleratorKey::Physical(code) => physical_keyval(code),
+        AcceleratorKey::Logical(key) => logical_keyval(key),
     };
+    // gtk matches Hyper by the real modifier it is bound to, which it can only tell apart
+    // from Super when it has its own
+    if menu_key.mods().contains(Modifiers::HYPER) && hyper_real_modifier().is_none() {
+        return Err(UnsupportedAcceleratorError::new(menu_key.clone()));
+    }
 
-    item.add_accelerator(
-        "activate",
-        accel_group,
-        accel_key,
-        modifiers_to_gdk_modifier_type(menu_key.mods),
-        gtk::AccelFlags::VISIBLE,
-    );
+    match accel_key {
+        Some(accel_key) => Ok((
+            accel_key,
+            modifiers_to_gdk_modifier_type(menu_key.canonical_mods()),
+        )),
+        None => Err(UnsupportedAcceleratorError::new(menu_key.clone())),
+    }
+}
+
+/// gtk matches accelerators by keyval, so physical keys are registered using the keyval
+/// they produce in the current keyboard layout, falling back to the one they produce on a US layout.
+fn physical_keyval(code: &Code) -> Option<u32> {
+    let layout_keyval = code_to_hardware_keycode(code).and_then(|keycode| {
+        gdk::Keymap::for_display(&gdk::Display::default()?)?
+            .translate_keyboard_state(keycode as u32, gdk::ModifierType::empty(), 0)
+            .map(|(keyval, ..)| keyval)
+    });
+
+    layout_keyval.or_else(|| us_layout_keyval(code))
+}
+
+/// Returns the keyval `code` produces on a US layout, which doesn't need gtk to be initialized.
+fn us_layout_keyval(code: &Code) -> Option<u32> {
+    if let Some(c) = code_to_char(code) {
+        Some(c.to_ascii_uppercase() as u32)
+    } else {
+        code_to_keysym_name(code).map(|name| *gdk::keys::Key::from_name(name))
+    }
+}
+
+/// Returns whether `menu_key` can be registered with gtk, physical keys are checked against
+/// the current keyboard layout like [`to_gtk_accelerator`] does, or a US layout before gtk is initialized.
+/// Hyper is only supported once gtk is initialized and it is bound to a real modifier of its own,
+/// see [`hyper_real_modifier`].
+pub fn is_gtk_accelerator_supported(menu_key: &Accelerator) -> bool {
+    if menu_key.mods().contains(Modifiers::HYPER)
+        && !(gtk::is_initialized() && hyper_real_modifier().is_some())
+    {
+        return false;
+    }
+    match &*menu_key.key() {
+        AcceleratorKey::Physical(code) if gtk::is_initialized() => physical_keyval(code).is_some(),
+        AcceleratorKey::Physical(code) => us_layout_keyval(code).is_some(),
+        AcceleratorKey::Logical(key) => logical_keyval(key).is_some(),
+    }
+}
+
+/// Converts a [`Code`] to the X11 keycode of the key at that position.
+pub fn code_to_hardware_keycode(code: &Code) -> Option<u16> {
+    (8..=u8::MAX as u16).find(|keycode| hardware_keycode_to_code(*keycode) == *code)
+}
+
+pub fn logical_keyval(key: &Key) -> Option<u32> {
+    match key {
+        Key::Character(s) => character_keyval(s),
+        key => named_key_to_code(key)
+            .as_ref()
+            .and_then(code_to_keysym_name)
+            .map(|name| *gdk::keys::Key::from_name(name)),
+    }
+}
+
+pub fn character_keyval(s: &str) -> Option<u32> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(*gdk::keys::Key::from_unicode(c)),
+        _ => None,
+    }
+}
+
+/// Converts the keyval of a gdk key event to the logical [`Key`] it represents.
+pub fn keyval_to_key(keyval: gdk::keys::Key) -> Key {
+    match keyval.to_unicode() {
+        Some(c) if !c.is_control() => Key::Character(c.to_string()),
+        _ => keyval
+            .name()
+            .and_then(|name| {
+                ALL_CODES
+                    .iter()
+                    .find(|code| code_to_keysym_name(code) == Some(name.as_str()))
+            })
+            .and_then(code_to_named_key)
+            .unwrap_or(Key::Unidentified),
+    }
 }
 
 fn modifiers_to_gdk_modifier_type(modifiers: Modifiers) -> gdk::ModifierType {
@@ -103,76 +183,248 @@ fn modifiers_to_gdk_modifier_type(modifiers: Modifiers) -> gdk::ModifierType {
         gdk::ModifierType::META_MASK,
         modifiers.contains(Modifiers::SUPER),
     );
+    // AltGr is mapped to ISO_Level3_Shift which sets MOD5 on common xkb layouts
+    result.set(
+        gdk::ModifierType::MOD5_MASK,
+        modifiers.contains(Modifiers::ALT_GRAPH),
+    );
+    result.set(
+        gdk::ModifierType::HYPER_MASK,
+        modifiers.contains(Modifiers::HYPER),
+    );
 
     result
 }
 
-fn key_to_raw_key(src: &Code) -> Option<gdk::keys::Key> {
-    use gdk::keys::constants::*;
-    Some(match src {
-        Code::Escape => Escape,
-        Code::Backspace => BackSpace,
-
-        Code::Tab => Tab,
-        Code::Enter => Return,
-
-        Code::ControlLeft => Control_L,
-        Code::AltLeft => Alt_L,
-        Code::ShiftLeft => Shift_L,
-        Code::MetaLeft => Super_L,
-
-        Code::ControlRight => Control_R,
-        Code::AltRight => Alt_R,
-        Code::ShiftRight => Shift_R,
-        Code::MetaRight => Super_R,
-
-        Code::CapsLock => Caps_Lock,
-        Code::F1 => F1,
-        Code::F2 => F2,
-        Code::F3 => F3,
-        Code::F4 => F4,
-        Code::F5 => F5,
-        Code::F6 => F6,
-        Code::F7 => F7,
-        Code::F8 => F8,
-        Code::F9 => F9,
-        Code::F10 => F10,
-        Code::F11 => F11,
-        Code::F12 => F12,
-        Code::F13 => F13,
-        Code::F14 => F14,
-        Code::F15 => F15,
-        Code::F16 => F16,
-        Code::F17 => F17,
-        Code::F18 => F18,
-        Code::F19 => F19,
-        Code::F20 => F20,
-        Code::F21 => F21,
-        Code::F22 => F22,
-        Code::F23 => F23,
-        Code::F24 => F24,
-
-        Code::PrintScreen => Print,
-        Code::ScrollLock => Scroll_Lock,
-        // Pause/Break not audio.
-        Code::Pause => Pause,
-
-        Code::Insert => Insert,
-        Code::Delete => Delete,
-        Code::Home => Home,
-        Code::End => End,
-        Code::PageUp => Page_Up,
-        Code::PageDown => Page_Down,
-
-        Code::NumLock => Num_Lock,
-
-        Code::ArrowUp => Up,
-        Code::ArrowDown => Down,
-        Code::ArrowLeft => Left,
-        Code::ArrowRight => Right,
-
-        Code::ContextMenu => Menu,
-        Code::WakeUp => WakeUp,
-        _ => return None,
+/// Returns the real modifier Hyper is bound to in the current keymap, if no other virtual
+/// modifier is bound to it. Default xkb setups bind Hyper to Mod4 together with Super.
+pub fn hyper_real_modifier() -> Option<gdk::ModifierType> {
+    let keymap = gdk::Keymap::for_display(&gdk::Display::default()?)?;
+    [
+        gdk::ModifierType::MOD2_MASK,
+        gdk::ModifierType::MOD3_MASK,
+        gdk::ModifierType::MOD4_MASK,
+        gdk::ModifierType::MOD5_MASK,
+    ]
+    .into_iter()
+    .find(|real| {
+        let mut state = *real;
+        keymap.add_virtual_modifiers(&mut state);
+        state.contains(gdk::ModifierType::HYPER_MASK)
+            && !state.intersects(gdk::ModifierType::SUPER_MASK | gdk::ModifierType::META_MASK)
     })
 }
+
+/// Converts the modifiers state of a gdk key event to [`Modifiers`].
+///
+/// Only the real modifier bits are translated, gdk also sets the virtual `SUPER_MASK`, `HYPER_MASK`
+/// and `META_MASK` bits of the real modifiers they are mapped to, so on default xkb setups
+/// Super would also be reported as Hyper and Alt as Meta.
+pub fn gdk_modifier_type_to_modifiers(state: gdk::ModifierType) -> Modifiers {
+    real_modifiers_to_modifiers(state, hyper_real_modifier())
+}
+
+/// Converts the real modifier bits of `state` to [`Modifiers`], `hyper` being the real modifier
+/// Hyper is bound to, see [`hyper_real_modifier`].
+fn real_modifiers_to_modifiers(
+    state: gdk::ModifierType,
+    hyper: Option<gdk::ModifierType>,
+) -> Modifiers {
+    let mut result = Modifiers::empty();
+    let mut state = state;
+    if let Some(hyper) = hyper {
+        result.set(Modifiers::HYPER, state.contains(hyper));
+        state.remove(hyper);
+    }
+
+    // the default mask leaves out Mod4 and Mod5, which Super and AltGr are mapped to
+    let state = state
+        & (gtk::accelerator_get_default_mod_mask()
+            | gdk::ModifierType::MOD4_MASK
+            | gdk::ModifierType::MOD5_MASK);
+
+    result.set(Modifiers::ALT, state.contains(gdk::ModifierType::MOD1_MASK));
+    result.set(
+        Modifiers::CONTROL,
+        state.contains(gdk::ModifierType::CONTROL_MASK),
+    );
+    result.set(
+        Modifiers::SHIFT,
+        state.contains(gdk::ModifierType::SHIFT_MASK),
+    );
+    result.set(
+        Modifiers::SUPER,
+        state.contains(gdk::ModifierType::MOD4_MASK),
+    );
+    result.set(
+        Modifiers::ALT_GRAPH,
+        state.contains(gdk::ModifierType::MOD5_MASK),
+    );
+
+    result
+}
+
+/// Converts the hardware keycode of a gdk key event to the [`Code`] of the physical key.
+///
+/// Both X11 and Wayland report XKB keycodes, which are the linux evdev scancodes offset by 8.
+pub fn hardware_keycode_to_code(keycode: u16) -> Code {
+    match keycode.wrapping_sub(8) {
+        1 => Code::Escape,
+        2 => Code::Digit1,
+        3 => Code::Digit2,
+        4 => Code::Digit3,
+        5 => Code::Digit4,
+        6 => Code::Digit5,
+        7 => Code::Digit6,
+        8 => Code::Digit7,
+        9 => Code::Digit8,
+        10 => Code::Digit9,
+        11 => Code::Digit0,
+        12 => Code::Minus,
+        13 => Code::Equal,
+        14 => Code::Backspace,
+        15 => Code::Tab,
+        16 => Code::KeyQ,
+        17 => Code::KeyW,
+        18 => Code::KeyE,
+        19 => Code::KeyR,
+        20 => Code::KeyT,
+        21 => Code::KeyY,
+        22 => Code::KeyU,
+        23 => Code::KeyI,
+        24 => Code::KeyO,
+        25 => Code::KeyP,
+        26 => Code::BracketLeft,
+        27 => Code::BracketRight,
+        28 => Code::Enter,
+        29 => Code::ControlLeft,
+        30 => Code::KeyA,
+        31 => Code::KeyS,
+        32 => Code::KeyD,
+        33 => Code::KeyF,
+        34 => Code::KeyG,
+        35 => Code::KeyH,
+        36 => Code::KeyJ,
+        37 => Code::KeyK,
+        38 => Code::KeyL,
+        39 => Code::Semicolon,
+        40 => Code::Quote,
+        41 => Code::Backquote,
+        42 => Code::ShiftLeft,
+        43 => Code::Backslash,
+        44 => Code::KeyZ,
+        45 => Code::KeyX,
+        46 => Code::KeyC,
+        47 => Code::KeyV,
+        48 => Code::KeyB,
+        49 => Code::KeyN,
+        50 => Code::KeyM,
+        51 => Code::Comma,
+        52 => Code::Period,
+        53 => Code::Slash,
+        54 => Code::ShiftRight,
+        55 => Code::NumpadMultiply,
+        56 => Code::AltLeft,
+        57 => Code::Space,
+        58 => Code::CapsLock,
+        59 => Code::F1,
+        60 => Code::F2,
+        61 => Code::F3,
+        62 => Code::F4,
+        63 => Code::F5,
+        64 => Code::F6,
+        65 => Code::F7,
+        66 => Code::F8,
+        67 => Code::F9,
+        68 => Code::F10,
+        69 => Code::NumLock,
+        70 => Code::ScrollLock,
+        71 => Code::Numpad7,
+        72 => Code::Numpad8,
+        73 => Code::Numpad9,
+        74 => Code::NumpadSubtract,
+        75 => Code::Numpad4,
+        76 => Code::Numpad5,
+        77 => Code::Numpad6,
+        78 => Code::NumpadAdd,
+        79 => Code::Numpad1,
+        80 => Code::Numpad2,
+        81 => Code::Numpad3,
+        82 => Code::Numpad0,
+        83 => Code::NumpadDecimal,
+        86 => Code::IntlBackslash,
+        87 => Code::F11,
+        88 => Code::F12,
+        89 => Code::IntlRo,
+        90 => Code::Katakana,
+        91 => Code::Hiragana,
+        92 => Code::Convert,
+        93 => Code::KanaMode,
+        94 => Code::NonConvert,
+        96 => Code::NumpadEnter,
+        97 => Code::ControlRight,
+        98 => Code::NumpadDivide,
+        99 => Code::PrintScreen,
+        100 => Code::AltRight,
+        102 => Code::Home,
+        103 => Code::ArrowUp,
+        104 => Code::PageUp,
+        105 => Code::ArrowLeft,
+        106 => Code::ArrowRight,
+        107 => Code::End,
+        108 => Code::ArrowDown,
+        109 => Code::PageDown,
+        110 => Code::Insert,
+        111 => Code::Delete,
+        113 => Code::AudioVolumeMute,
+        114 => Code::AudioVolumeDown,
+        115 => Code::AudioVolumeUp,
+        116 => Code::Power,
+        117 => Code::NumpadEqual,
+        119 => Code::Pause,
+        121 => Code::NumpadComma,
+        122 => Code::Lang1,
+        123 => Code::Lang2,
+        124 => Code::IntlYen,
+        125 => Code::MetaLeft,
+        126 => Code::MetaRight,
+        127 => Code::ContextMenu,
+        128 => Code::BrowserStop,
+        129 => Code::Again,
+        130 => Code::Props,
+        131 => Code::Undo,
+        133 => Code::Copy,
+        134 => Code::Open,
+        135 => Code::Paste,
+        136 => Code::Find,
+        137 => Code::Cut,
+        138 => Code::Help,
+        142 => Code::Sleep,
+        143 => Code::WakeUp,
+        155 => Code::LaunchMail,
+        156 => Code::BrowserFavorites,
+        158 => Code::BrowserBack,
+        159 => Code::BrowserForward,
+        161 => Code::Eject,
+        163 => Code::MediaTrackNext,
+        164 => Code::MediaPlayPause,
+        165 => Code::MediaTrackPrevious,
+        166 => Code::MediaStop,
+        172 => Code::BrowserHome,
+        173 => Code::BrowserRefresh,
+        183 => Code::F13,
+        184 => Code::F14,
+        185 => Code::F15,
+        186 => Code::F16,
+        187 => Code::F17,
+        188 => Code::F18,
+        189 => Code::F19,
+        190 => Code::F20,
+        191 => Code::F21,
+        192 => Code::F22,
+        193 => Code::F23,
+        194 => Code::F24,
+        217 => Code::BrowserSearch,
+        _ => Code::Unidentified,
+    }
+}
@@ -0,0 +1,278 @@
+use gtk::{
+    glib::{self, SignalHandlerId},
+    prelude::*,
+};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
+
+/// A context menu attached to a widget, see [`ContextMenuAttachments`].
+pub trait Attachment {
+    /// Returns whether the widget is still alive, a destroyed widget
+    /// can't be told apart from a new widget at the same address otherwise.
+    fn is_alive(&self) -> bool;
+
+    /// Disconnects the handlers from the widget.
+    fn detach(self);
+}
+
+/// The widgets a submenu is attached to as a context menu, by their address.
+#[derive(Debug)]
+pub struct ContextMenuAttachments<A = ContextMenuAttachment>(HashMap<isize, A>);
+
+impl<A> Default for ContextMenuAttachments<A> {
+    fn default() -> Self {
+        Self(HashMap::new())
+    }
+}
+
+impl<A: Attachment> ContextMenuAttachments<A> {
+    /// Attaches the context menu to the widget at `key` with `attach`, unless it is already attached,
+    /// returns whether it was attached.
+    pub fn attach<F: FnOnce() -> A>(&mut self, key: isize, attach: F) -> bool {
+        self.0.retain(|_, attachment| attachment.is_alive());
+        if self.0.contains_key(&key) {
+            return false;
+        }
+        self.0.insert(key, attach());
+        true
+    }
+
+    /// Detaches the context menu from the widget at `key`, returns whether it was attached.
+    pub fn detach(&mut self, key: isize) -> bool {
+        match self.0.remove(&key) {
+            Some(attachment) => {
+                attachment.detach();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Detaches the context menu from every widget, when the submenu is removed.
+    pub fn detach_all(&mut self) {
+        for (_, attachment) in self.0.drain() {
+            attachment.detach();
+        }
+    }
+}
+
+/// The handlers showing a context menu for a widget, see [`ContextMenuAttachment::new`].
+#[derive(Debug)]
+pub struct ContextMenuAttachment {
+    widget: glib::WeakRef<gtk::Widget>,
+    handlers: Vec<SignalHandlerId>,
+    // gestures aren't owned by their widget in gtk3, they stop handling its events once dropped
+    long_press: gtk::GestureLongPress,
+}
+
+impl ContextMenuAttachment {
+    /// Shows the menu returned by `menu` when `widget` is right clicked, long pressed
+    /// on a touchscreen, or when the Menu key or Shift+F10 is pressed while it has the focus.
+    pub fn new<F: Fn() -> Option<gtk::Menu> + 'static>(widget: &gtk::Widget, menu: F) -> Self {
+        let menu = Rc::new(menu);
+        widget.add_events(gdk::EventMask::BUTTON_PRESS_MASK | gdk::EventMask::TOUCH_MASK);
+
+        let menu_ = Rc::clone(&menu);
+        let press_handler = widget.connect_button_press_event(move |_, event| {
+            // double clicks emit another press event of a different type
+            if event.event_type() != gdk::EventType::ButtonPress || !event.triggers_context_menu() {
+                return gtk::Inhibit(false);
+            }
+            match menu_() {
+                Some(menu) => {
+                    popup_context_menu_at_pointer(&menu, event);
+                    gtk::Inhibit(true)
+                }
+                None => gtk::Inhibit(false),
+            }
+        });
+
+        // gtk emits `popup-menu` when the Menu key or Shift+F10 is pressed,
+        // the menu is shown at the center of the widget as there is no pointer position
+        let menu_ = Rc::clone(&menu);
+        let key_handler = widget.connect_popup_menu(move |widget| match menu_() {
+            Some(menu) => {
+                let (x, y) = (widget.allocated_width() / 2, widget.allocated_height() / 2);
+                popup_context_menu(
+                    &menu,
+                    widget,
+                    gdk::Rectangle::new(x, y, 1, 1),
+                    gdk::Gravity::NorthWest,
+                    gdk::Gravity::NorthWest,
+                    gtk::current_event().as_ref(),
+                );
+                true
+            }
+            None => false,
+        });
+
+        let long_press = gtk::GestureLongPress::new(widget);
+        long_press.set_touch_only(true);
+        long_press.connect_pressed(move |gesture, x, y| {
+            if let (Some(menu), Some(widget)) = (menu(), gesture.widget()) {
+                // `pressed` is emitted from a timeout, where there is no current event,
+                // so the menu is triggered by the last event of the touch instead
+                let event = gesture.last_event(gesture.last_updated_sequence().as_ref());
+                gesture.set_state(gtk::EventSequenceState::Claimed);
+                popup_context_menu(
+                    &menu,
+                    &widget,
+                    gdk::Rectangle::new(x as i32, y as i32, 1, 1),
+                    gdk::Gravity::NorthWest,
+                    gdk::Gravity::NorthWest,
+                    event.as_ref(),
+                );
+            }
+        });
+
+        Self {
+            widget: widget.downgrade(),
+            handlers: vec![press_handler, key_handler],
+            long_press,
+        }
+    }
+}
+
+impl Attachment for ContextMenuAttachment {
+    fn is_alive(&self) -> bool {
+        self.widget.upgrade().is_some()
+    }
+
+    fn detach(self) {
+        if let Some(widget) = self.widget.upgrade() {
+            for handler in self.handlers {
+                widget.disconnect(handler);
+            }
+        }
+        self.long_press
+            .set_propagation_phase(gtk::PropagationPhase::None);
+    }
+}
+
+/// Pops up `menu` anchored to `rect`, relative to `widget`, and emits
+/// a [`crate::ContextMenuClosedEvent`] once it is closed.
+///
+/// `trigger_event` is the event that triggered the menu, gtk uses it to tell
+/// which device and seat the menu is grabbed on.
+pub fn popup_context_menu<W: IsA<gtk::Widget>>(
+    menu: &gtk::Menu,
+    widget: &W,
+    rect: gdk::Rectangle,
+    rect_anchor: gdk::Gravity,
+    menu_anchor: gdk::Gravity,
+    trigger_event: Option<&gdk::Event>,
+) {
+    let window = match widget.window() {
+        Some(window) => window,
+        None => {
+            #[cfg(debug_assertions)]
+            eprintln!("Failed to show the context menu: the widget isn't realized");
+            return;
+        }
+    };
+    // widgets without their own gdk window are positioned relative to the one of their parent
+    let rect = if widget.has_window() {
+        rect
+    } else {
+        let allocation = widget.allocation();
+        gdk::Rectangle::new(
+            rect.x() + allocation.x(),
+            rect.y() + allocation.y(),
+            rect.width(),
+            rect.height(),
+        )
+    };
+
+    emit_closed_event(menu);
+    menu.popup_at_rect(&window, &rect, rect_anchor, menu_anchor, trigger_event);
+}
+
+/// Pops up `menu` at the position of the pointer in `event`, see [`popup_context_menu`].
+pub fn popup_context_menu_at_pointer(menu: &gtk::Menu, event: &gdk::Event) {
+    emit_closed_event(menu);
+    menu.popup_at_pointer(Some(event));
+}
+
+/// Emits a [`crate::ContextMenuClosedEvent`] once `menu` is closed.
+fn emit_closed_event(menu: &gtk::Menu) {
+    // gtk deactivates the menu before activating the chosen item and emits `selection-done`
+    // afterwards, whether an item was chosen or not, so the items are watched until then
+    let emitter = Rc::new(ClosedEventEmitter::default());
+    watch_activations(menu.upcast_ref(), &emitter);
+
+    let emitter_ = Rc::clone(&emitter);
+    let handler = menu.connect_selection_done(move |_| emitter_.selection_done());
+    let menu = menu.downgrade();
+    emitter.on_done(move || {
+        if let Some(menu) = menu.upgrade() {
+            menu.disconnect(handler);
+        }
+    });
+}
+
+/// Tracks whether an item of a shown context menu is activated until
+/// the menu emits `selection-done`, see [`emit_closed_event`].
+#[derive(Default)]
+pub struct ClosedEventEmitter {
+    activated: Cell<bool>,
+    done: Cell<bool>,
+    // disconnect the handlers of the menu and its items
+    disconnect: RefCell<Vec<Box<dyn FnOnce()>>>,
+}
+
+impl ClosedEventEmitter {
+    /// Adds a function called once the menu is done, to disconnect a handler.
+    pub fn on_done<F: FnOnce() + 'static>(&self, f: F) {
+        self.disconnect.borrow_mut().push(Box::new(f));
+    }
+
+    pub fn item_activated(&self) {
+        if !self.done.get() {
+            self.activated.set(true);
+        }
+    }
+
+    /// Disconnects the handlers and emits the event, only the first time it is called.
+    pub fn selection_done(&self) {
+        if self.done.replace(true) {
+            return;
+        }
+        for disconnect in self.disconnect.take() {
+            disconnect();
+        }
+        let _ = crate::CONTEXT_MENU_CHANNEL
+            .0
+            .send(crate::ContextMenuClosedEvent {
+                item_activated: self.activated.get(),
+            });
+    }
+}
+
+/// Calls [`ClosedEventEmitter::item_activated`] when an item of `shell` or of its submenus is activated.
+fn watch_activations(shell: &gtk::MenuShell, emitter: &Rc<ClosedEventEmitter>) {
+    for child in shell.children() {
+        let item = match child.downcast::<gtk::MenuItem>() {
+            Ok(item) => item,
+            Err(_) => continue,
+        };
+        // items with a submenu open it instead of being chosen
+        if let Some(submenu) = item.submenu() {
+            if let Some(submenu) = submenu.downcast_ref::<gtk::MenuShell>() {
+                watch_activations(submenu, emitter);
+            }
+            continue;
+        }
+        let emitter_ = Rc::clone(emitter);
+        let handler = item.connect_activate(move |_| emitter_.item_activated());
+        // the items keep the emitter alive until they are disconnected, so it only holds weak references to them
+        let item = item.downgrade();
+        emitter.on_done(move || {
+            if let Some(item) = item.upgrade() {
+                item.disconnect(handler);
+            }
+        });
+    }
+}
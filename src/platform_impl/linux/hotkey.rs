@@ -0,0 +1,368 @@
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+    os::unix::{io::AsRawFd, net::UnixStream},
+    thread,
+};
+
+use crossbeam_channel::{bounded, unbounded, Receiver, Sender, TryRecvError};
+use keyboard_types::Modifiers;
+use x11rb::{
+    connection::Connection,
+    errors::ReplyError,
+    protocol::{
+        xkb::{self, ConnectionExt as _},
+        xproto::{ConnectionExt as _, GrabMode, Keycode, Keysym, ModMask, Window},
+        ErrorKind, Event,
+    },
+    rust_connection::RustConnection,
+};
+
+use super::{
+    accelerator::{code_to_hardware_keycode, logical_keyval},
+    COUNTER,
+};
+use crate::{
+    accelerator::{Accelerator, AcceleratorKey, UnsupportedAcceleratorError},
+    hotkey::{HotKey, HotKeyError},
+    MenuId,
+};
+
+/// Caps Lock and Num Lock are modifiers for X11, grabs match the exact modifiers state
+/// so every hotkey is grabbed once for each combination of them.
+fn lock_masks() -> [ModMask; 4] {
+    [
+        ModMask::from(0u16),
+        ModMask::LOCK,
+        ModMask::M2,
+        ModMask::LOCK | ModMask::M2,
+    ]
+}
+
+fn modifiers_mask() -> ModMask {
+    ModMask::SHIFT | ModMask::CONTROL | ModMask::M1 | ModMask::M4 | ModMask::M5
+}
+
+enum Command {
+    Register(HotKey, Sender<Result<(), HotKeyError>>),
+    Unregister(HotKey, Sender<Result<(), HotKeyError>>),
+    Quit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Grab {
+    keycode: Keycode,
+    modifiers: ModMask,
+}
+
+// The hotkeys thread owns its own X connection, which is separate from the one gtk uses,
+// and waits for either X events or a byte on `wake`, which is written after every command.
+pub struct GlobalHotKeyManager {
+    commands: Sender<Command>,
+    wake: UnixStream,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl GlobalHotKeyManager {
+    pub fn new() -> Result<Self, HotKeyError> {
+        let (commands, receiver) = unbounded();
+        let (wake, wakeup) = UnixStream::pair().map_err(|_| HotKeyError::NoDisplay)?;
+        wakeup
+            .set_nonblocking(true)
+            .map_err(|_| HotKeyError::NoDisplay)?;
+        let (conn, screen) = RustConnection::connect(None).map_err(|_| HotKeyError::NoDisplay)?;
+        let root = conn.setup().roots[screen].root;
+
+        let thread = thread::spawn(move || {
+            let _ = run(&conn, root, receiver, wakeup);
+        });
+
+        Ok(Self {
+            commands,
+            wake,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn register(&self, accelerator: Accelerator) -> Result<HotKey, HotKeyError> {
+        let hotkey = HotKey {
+            id: MenuId::generated(COUNTER.next()),
+            accelerator,
+        };
+        self.send(|reply| Command::Register(hotkey.clone(), reply))?;
+        Ok(hotkey)
+    }
+
+    pub fn unregister(&self, hotkey: &HotKey) -> Result<(), HotKeyError> {
+        self.send(|reply| Command::Unregister(hotkey.clone(), reply))
+    }
+
+    fn send<F: FnOnce(Sender<Result<(), HotKeyError>>) -> Command>(
+        &self,
+        command: F,
+    ) -> Result<(), HotKeyError> {
+        let (reply, response) = bounded(1);
+        self.commands
+            .send(command(reply))
+            .map_err(|_| HotKeyError::NoDisplay)?;
+        (&self.wake)
+            .write_all(&[0])
+            .map_err(|_| HotKeyError::NoDisplay)?;
+        response.recv().unwrap_or(Err(HotKeyError::NoDisplay))
+    }
+}
+
+impl Drop for GlobalHotKeyManager {
+    fn drop(&mut self) {
+        let _ = self.commands.send(Command::Quit);
+        let _ = (&self.wake).write_all(&[0]);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn run(
+    conn: &RustConnection,
+    root: Window,
+    commands: Receiver<Command>,
+    mut wakeup: UnixStream,
+) -> Result<(), ReplyError> {
+    let mut hotkeys: HashMap<MenuId, Grab> = HashMap::new();
+    let mut pressed = None;
+
+    // without detectable auto repeat, holding a key sends a release before every repeated press
+    if conn.xkb_use_extension(1, 0)?.reply()?.supported {
+        conn.xkb_per_client_flags(
+            xkb::ID::USE_CORE_KBD.into(),
+            xkb::PerClientFlag::DETECTABLE_AUTO_REPEAT,
+            xkb::PerClientFlag::DETECTABLE_AUTO_REPEAT,
+            0u32.into(),
+            0u32.into(),
+            0u32.into(),
+        )?
+        .reply()?;
+    }
+
+    loop {
+        let mut quit = false;
+        loop {
+            match commands.try_recv() {
+                Ok(Command::Register(hotkey, reply)) => {
+                    let result = to_grab(conn, &hotkey.accelerator).and_then(|grab| {
+                        if hotkeys.values().any(|g| *g == grab) {
+                            Err(HotKeyError::AlreadyRegistered(hotkey.accelerator.clone()))
+                        } else {
+                            match grab_key(conn, root, grab) {
+                                Ok(()) => {
+                                    hotkeys.insert(hotkey.id.clone(), grab);
+                                    Ok(())
+                                }
+                                Err(ReplyError::X11Error(e))
+                                    if e.error_kind == ErrorKind::Access =>
+                                {
+                                    Err(HotKeyError::AlreadyGrabbed(hotkey.accelerator.clone()))
+                                }
+                                Err(_) => Err(HotKeyError::NoDisplay),
+                            }
+                        }
+                    });
+                    let _ = reply.send(result);
+                }
+                Ok(Command::Unregister(hotkey, reply)) => {
+                    let result = match hotkeys.remove(&hotkey.id) {
+                        Some(grab) => {
+                            ungrab_key(conn, root, grab).map_err(|_| HotKeyError::NoDisplay)
+                        }
+                        None => Err(HotKeyError::NotRegistered(hotkey)),
+                    };
+                    let _ = reply.send(result);
+                }
+                Ok(Command::Quit) | Err(TryRecvError::Disconnected) => {
+                    quit = true;
+                    break;
+                }
+                Err(TryRecvError::Empty) => break,
+            }
+        }
+        if quit {
+            break;
+        }
+
+        while let Some(event) = conn.poll_for_event()? {
+            match event {
+                Event::KeyPress(key) if pressed != Some(key.detail) => {
+                    pressed = Some(key.detail);
+                    let grab = Grab {
+                        keycode: key.detail,
+                        modifiers: ModMask::from(u16::from(key.state)) & modifiers_mask(),
+                    };
+                    if let Some((id, _)) = hotkeys.iter().find(|(_, g)| **g == grab) {
+                        let _ = crate::MENU_CHANNEL
+                            .0
+                            .send(crate::MenuEvent { id: id.clone() });
+                    }
+                }
+                Event::KeyRelease(_) => pressed = None,
+                _ => {}
+            }
+        }
+        conn.flush()?;
+
+        wait(conn, &wakeup)?;
+        while wakeup.read(&mut [0; 64]).is_ok_and(|read| read > 0) {}
+    }
+
+    for grab in hotkeys.values() {
+        ungrab_key(conn, root, *grab)?;
+    }
+    Ok(())
+}
+
+/// Blocks until the X connection or `wakeup` has data to read.
+fn wait(conn: &RustConnection, wakeup: &UnixStream) -> std::io::Result<()> {
+    let mut fds = [
+        libc::pollfd {
+            fd: conn.stream().as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        },
+        libc::pollfd {
+            fd: wakeup.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        },
+    ];
+    loop {
+        if unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, -1) } >= 0 {
+            return Ok(());
+        }
+        let error = std::io::Error::last_os_error();
+        if error.kind() != std::io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+}
+
+fn to_grab(conn: &RustConnection, accelerator: &Accelerator) -> Result<Grab, HotKeyError> {
+    let unsupported =
+        || HotKeyError::Unsupported(UnsupportedAcceleratorError::new(accelerator.clone()));
+
+    // X11 reports which modifiers are active but not which key activated them
+    if !accelerator.sides().is_empty() {
+        return Err(unsupported());
+    }
+
+    let keycode = match &*accelerator.key() {
+        AcceleratorKey::Physical(code) => {
+            code_to_hardware_keycode(code).and_then(|keycode| Keycode::try_from(keycode).ok())
+        }
+        AcceleratorKey::Logical(key) => {
+            logical_keyval(key).and_then(|keysym| keysym_to_keycode(conn, keysym))
+        }
+    };
+
+    match (keycode, modifiers_to_x_mask(accelerator.canonical_mods())) {
+        (Some(keycode), Some(modifiers)) => Ok(Grab { keycode, modifiers }),
+        _ => Err(unsupported()),
+    }
+}
+
+/// Returns the first keycode producing `keysym` in the current keyboard mapping.
+fn keysym_to_keycode(conn: &RustConnection, keysym: Keysym) -> Option<Keycode> {
+    let setup = conn.setup();
+    let mapping = conn
+        .get_keyboard_mapping(setup.min_keycode, setup.max_keycode - setup.min_keycode + 1)
+        .ok()?
+        .reply()
+        .ok()?;
+    let per_keycode = mapping.keysyms_per_keycode as usize;
+    if per_keycode == 0 {
+        return None;
+    }
+    mapping
+        .keysyms
+        .chunks(per_keycode)
+        .position(|keysyms| keysyms.contains(&keysym))
+        .map(|i| setup.min_keycode + i as Keycode)
+}
+
+/// Converts [`Modifiers`] to an X11 modifiers mask, using the modifiers
+/// the default xkb configuration maps the modifier keys to.
+fn modifiers_to_x_mask(modifiers: Modifiers) -> Option<ModMask> {
+    // Hyper shares Mod4 with Super in the default xkb configuration
+    if modifiers.contains(Modifiers::HYPER) {
+        return None;
+    }
+
+    let mut mask = ModMask::from(0u16);
+    for (modifier, x_mask) in [
+        (Modifiers::SHIFT, ModMask::SHIFT),
+        (Modifiers::CONTROL, ModMask::CONTROL),
+        (Modifiers::ALT, ModMask::M1),
+        (Modifiers::SUPER, ModMask::M4),
+        (Modifiers::ALT_GRAPH, ModMask::M5),
+    ] {
+        if modifiers.contains(modifier) {
+            mask |= x_mask;
+        }
+    }
+    Some(mask)
+}
+
+/// Grabs `grab` with every combination of lock modifiers, the grabs are checked requests
+/// so a grab refused by the server fails with [`ErrorKind::Access`] on this connection only.
+/// Releases the grabs again if any of them failed.
+fn grab_key(conn: &RustConnection, root: Window, grab: Grab) -> Result<(), ReplyError> {
+    let cookies = lock_masks()
+        .into_iter()
+        .map(|lock| {
+            conn.grab_key(
+                false,
+                root,
+                grab.modifiers | lock,
+                grab.keycode,
+                GrabMode::ASYNC,
+                GrabMode::ASYNC,
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let result = cookies
+        .into_iter()
+        .map(|cookie| cookie.check())
+        .fold(Ok(()), Result::and);
+    if result.is_err() {
+        let _ = ungrab_key(conn, root, grab);
+    }
+    result
+}
+
+fn ungrab_key(conn: &RustConnection, root: Window, grab: Grab) -> Result<(), ReplyError> {
+    for lock in lock_masks() {
+        conn.ungrab_key(grab.keycode, root, grab.modifiers | lock)?;
+    }
+    conn.flush()?;
+    Ok(())
+}
+
+#[test]
+fn test_modifiers_to_x_mask() {
+    assert_eq!(
+        modifiers_to_x_mask(Modifiers::CONTROL | Modifiers::ALT),
+        Some(ModMask::CONTROL | ModMask::M1)
+    );
+    assert_eq!(
+        modifiers_to_x_mask(Modifiers::SHIFT | Modifiers::SUPER | Modifiers::ALT_GRAPH),
+        Some(ModMask::SHIFT | ModMask::M4 | ModMask::M5)
+    );
+    assert_eq!(
+        modifiers_to_x_mask(Modifiers::empty()),
+        Some(ModMask::from(0u16))
+    );
+    assert_eq!(modifiers_to_x_mask(Modifiers::HYPER), None);
+
+    // lock modifiers are never part of a hotkey, every variant differs only by them
+    for lock in lock_masks() {
+        assert_eq!(u16::from(lock & modifiers_mask()), 0);
+    }
+}
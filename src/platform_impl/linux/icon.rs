@@ -0,0 +1,103 @@
+use crate::icon::{scaled_size, Icon, IconSource};
+use gtk::{gdk_pixbuf, prelude::*, Orientation};
+
+/// Returns the label of a menu item, which is either its child
+/// or packed with its icon in a box, see [`set_gtk_icon`].
+pub fn gtk_label<M: IsA<gtk::Widget>>(item: &M) -> Option<gtk::AccelLabel> {
+    let child = item.dynamic_cast_ref::<gtk::Bin>()?.child()?;
+    match child.downcast::<gtk::Box>() {
+        Ok(icon_box) => icon_box
+            .children()
+            .into_iter()
+            .find_map(|c| c.downcast::<gtk::AccelLabel>().ok()),
+        Err(child) => child.downcast::<gtk::AccelLabel>().ok(),
+    }
+}
+
+/// Sets the label of a menu item whether it has an icon or not,
+/// `gtk_menu_item_set_label` only works when the label is the child of the menu item.
+pub fn set_gtk_label(item: &gtk::MenuItem, label: &str) {
+    match gtk_label(item) {
+        Some(accel_label) => accel_label.set_text_with_mnemonic(label),
+        None => item.set_label(label),
+    }
+}
+
+/// Packs an image of `icon` before the label of a menu item, or removes it if `icon` is `None`.
+pub fn set_gtk_icon(item: &gtk::MenuItem, icon: Option<&Icon>) {
+    let label = match gtk_label(item) {
+        Some(label) => label,
+        None => return,
+    };
+    // the label is kept alive by `label` while it is moved
+    if let Some(child) = item.child() {
+        if let Some(icon_box) = child.downcast_ref::<gtk::Box>() {
+            icon_box.remove(&label);
+        }
+        item.remove(&child);
+    }
+
+    match icon.and_then(to_gtk_image) {
+        Some(image) => {
+            let icon_box = gtk::Box::new(Orientation::Horizontal, 6);
+            icon_box.pack_start(&image, false, false, 0);
+            icon_box.pack_start(&label, true, true, 0);
+            icon_box.show_all();
+            item.add(&icon_box);
+        }
+        None => item.add(&label),
+    }
+}
+
+fn to_gtk_image(icon: &Icon) -> Option<gtk::Image> {
+    if let IconSource::Name(name) = &icon.0 {
+        return Some(gtk::Image::from_icon_name(Some(name), gtk::IconSize::Menu));
+    }
+    let pixbuf = scale_to_icon_size(to_pixbuf(icon)?)?;
+    Some(gtk::Image::from_pixbuf(Some(&pixbuf)))
+}
+
+/// Decodes `icon` at its original size, icons from a theme aren't decoded and return `None`.
+pub fn to_pixbuf(icon: &Icon) -> Option<gdk_pixbuf::Pixbuf> {
+    match &icon.0 {
+        IconSource::Name(_) => None,
+        IconSource::Rgba {
+            rgba,
+            width,
+            height,
+        } => Some(gdk_pixbuf::Pixbuf::from_mut_slice(
+            rgba.clone(),
+            gdk_pixbuf::Colorspace::Rgb,
+            true,
+            8,
+            *width as i32,
+            *height as i32,
+            *width as i32 * 4,
+        )),
+        IconSource::Buffer(buffer) => {
+            let loader = gdk_pixbuf::PixbufLoader::new();
+            let pixbuf = loader
+                .write(buffer)
+                .and_then(|_| loader.close())
+                .map(|_| loader.pixbuf());
+            match pixbuf {
+                Ok(pixbuf) => pixbuf,
+                Err(_e) => {
+                    #[cfg(debug_assertions)]
+                    eprintln!("Failed to decode the menu icon: {}", _e);
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// Scales `pixbuf` down to the size of menu icons if it is larger.
+pub fn scale_to_icon_size(pixbuf: gdk_pixbuf::Pixbuf) -> Option<gdk_pixbuf::Pixbuf> {
+    let (width, height) = scaled_size(pixbuf.width(), pixbuf.height());
+    if (width, height) == (pixbuf.width(), pixbuf.height()) {
+        Some(pixbuf)
+    } else {
+        pixbuf.scale_simple(width, height, gdk_pixbuf::InterpType::Bilinear)
+    }
+}
@@ -1,22 +1,220 @@
 mod accelerator;
-
-use crate::{accelerator::Accelerator, counter::Counter, NativeMenuItem};
-use accelerator::{register_accelerator, to_gtk_menemenoic};
-use gtk::{prelude::*, Orientation};
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+mod context_menu;
+mod hotkey;
+mod icon;
+mod tray;
+
+use crate::{
+    accelerator::{
+        conflicts::item_path, Accelerator, Chord, ChordMatcher, ChordState, KeyPress,
+        ModifierSides, UnsupportedAcceleratorError,
+    },
+    check_insert_position, check_position,
+    counter::Counter,
+    icon::Icon,
+    keymap::{Keymap, KeymapIssue},
+    sealed::MenuChild,
+    tree::{item_accelerator, MenuTree},
+    MenuError, MenuId, MenuItemKind, NativeMenuItem,
+};
+use accelerator::{
+    gdk_modifier_type_to_modifiers, hardware_keycode_to_code, is_gtk_accelerator_supported,
+    is_matched_by_gtk, keyval_to_key, register_accelerator, set_accel_label, to_gtk_accelerator,
+    to_gtk_menemenoic, unregister_accelerator,
+};
+use context_menu::{popup_context_menu, ContextMenuAttachment, ContextMenuAttachments};
+use gtk::{glib, prelude::*, Orientation};
+pub use hotkey::GlobalHotKeyManager;
+use icon::{set_gtk_icon, set_gtk_label};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::{Rc, Weak},
+};
+pub use tray::TrayIcon;
 
 static COUNTER: Counter = Counter::new();
 
+/// Returns whether `accelerator` can be registered with gtk.
+pub(crate) fn is_accelerator_supported(accelerator: &Accelerator) -> bool {
+    is_gtk_accelerator_supported(accelerator)
+}
+
+/// Shared chord state machine of a [`Menu`], completed chords resolve to the entry they activate.
+type MenuChords = Rc<RefCell<ChordMatcher<ChordEntry>>>;
+
+/// A menu entry registered in [`MenuChords`], compared by identity so it can be unregistered.
+#[derive(Clone)]
+struct ChordEntry(Rc<RefCell<MenuEntry>>);
+
+impl PartialEq for ChordEntry {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
 /// Generic shared type describing a menu entry. It can be one of [`MenuEntryType`]
 #[derive(Debug, Default)]
 struct MenuEntry {
     label: String,
     enabled: bool,
     checked: bool,
-    id: u64,
+    id: MenuId,
     accelerator: Option<Accelerator>,
+    // the accelerator the item was created with, `accelerator` can be overridden by a keymap
+    default_accelerator: Option<Accelerator>,
+    // the outcome of registering `accelerator` with gtk, `None` until the widgets of the entry are created
+    accelerator_registration: Option<Result<(), UnsupportedAcceleratorError>>,
+    chord: Option<Chord>,
+    icon: Option<Icon>,
+    tooltip: Option<String>,
+    help_text: Option<String>,
+    radio_group: Option<RadioGroup>,
+    // the gtk menu of a submenu shown as a context menu, created the first time it is shown
+    context_menu: Option<gtk::Menu>,
+    // the widgets a submenu is attached to as their context menu, by their address
+    context_menu_attachments: ContextMenuAttachments,
     r#type: MenuEntryType,
     entries: Option<Vec<Rc<RefCell<MenuEntry>>>>,
+    // removed entries keep their children so handles to them stay usable,
+    // but operations that change them fail once this is set
+    removed: bool,
+}
+
+impl MenuEntry {
+    /// Fails with [`MenuError::ItemRemoved`] if this entry was removed from its menu.
+    fn check_removed(&self) -> Result<(), MenuError> {
+        if self.removed {
+            Err(MenuError::ItemRemoved)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the outcome of registering the accelerator of this entry with gtk,
+    /// or whether it can be registered if its widgets weren't created yet.
+    fn check_accelerator(&self) -> Result<(), UnsupportedAcceleratorError> {
+        match &self.accelerator_registration {
+            Some(registration) => registration.clone(),
+            None => crate::check_accelerator(self.accelerator.clone()),
+        }
+    }
+
+    fn new_submenu(label: &str, enabled: bool) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(MenuEntry {
+            label: label.to_string(),
+            enabled,
+            entries: Some(Vec::new()),
+            r#type: MenuEntryType::Submenu(Vec::new()),
+            ..Default::default()
+        }))
+    }
+
+    fn new_item(
+        label: &str,
+        enabled: bool,
+        accelerator: Option<Accelerator>,
+        id: Option<MenuId>,
+    ) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(MenuEntry {
+            label: label.to_string(),
+            enabled,
+            r#type: MenuEntryType::MenuItem(Vec::new()),
+            id: id.unwrap_or_else(|| MenuId::generated(COUNTER.next())),
+            accelerator: accelerator.clone(),
+            default_accelerator: accelerator,
+            ..Default::default()
+        }))
+    }
+
+    fn new_check_item(
+        label: &str,
+        enabled: bool,
+        checked: bool,
+        accelerator: Option<Accelerator>,
+        id: Option<MenuId>,
+    ) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(MenuEntry {
+            label: label.to_string(),
+            enabled,
+            checked,
+            r#type: MenuEntryType::CheckMenuItem(Vec::new()),
+            id: id.unwrap_or_else(|| MenuId::generated(COUNTER.next())),
+            accelerator: accelerator.clone(),
+            default_accelerator: accelerator,
+            ..Default::default()
+        }))
+    }
+
+    fn new_radio_item(
+        label: &str,
+        enabled: bool,
+        group: &RadioGroup,
+        accelerator: Option<Accelerator>,
+        id: Option<MenuId>,
+    ) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(MenuEntry {
+            label: label.to_string(),
+            enabled,
+            radio_group: Some(group.clone()),
+            r#type: MenuEntryType::RadioMenuItem(Vec::new()),
+            id: id.unwrap_or_else(|| MenuId::generated(COUNTER.next())),
+            accelerator: accelerator.clone(),
+            default_accelerator: accelerator,
+            ..Default::default()
+        }))
+    }
+
+    /// Returns whether this entry is displayed by a gtk widget,
+    /// native menu items that are unsupported on Linux aren't.
+    fn has_gtk_widget(&self) -> bool {
+        match &self.r#type {
+            MenuEntryType::NativeMenuItem(item) => item.has_gtk_widget(),
+            _ => true,
+        }
+    }
+
+    /// Returns the gtk widgets of a menu item, a check menu item or a radio menu item, one for each window.
+    fn gtk_items(&self) -> Vec<gtk::MenuItem> {
+        match &self.r#type {
+            MenuEntryType::MenuItem(native_items) => native_items.clone(),
+            MenuEntryType::CheckMenuItem(native_items) => {
+                native_items.iter().map(|i| i.clone().upcast()).collect()
+            }
+            MenuEntryType::RadioMenuItem(native_items) => {
+                native_items.iter().map(|i| i.clone().upcast()).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the gtk menus of a submenu, one for each window and its context menu.
+    fn gtk_menus(&self) -> Vec<gtk::MenuShell> {
+        match &self.r#type {
+            MenuEntryType::Submenu(native_menus) => native_menus
+                .iter()
+                .map(|(_, menu)| menu)
+                .chain(&self.context_menu)
+                .map(|menu| menu.clone().upcast())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Moves `child` to `position` within this submenu entry and its gtk menus.
+    fn move_child(
+        &mut self,
+        child: &Rc<RefCell<MenuEntry>>,
+        position: usize,
+    ) -> Result<usize, MenuError> {
+        self.check_removed()?;
+        let menus = self.gtk_menus();
+        let entries = self.entries.as_mut().unwrap();
+        let from = child_position(entries, child)?;
+        check_position(position, entries.len())?;
+        move_entry(entries, &menus, from, position);
+        Ok(from)
+    }
 }
 
 #[derive(PartialEq, Eq, Debug)]
@@ -28,6 +226,7 @@ enum MenuEntryType {
     Submenu(Vec<(gtk::MenuItem, gtk::Menu)>),
     MenuItem(Vec<gtk::MenuItem>),
     CheckMenuItem(Vec<gtk::CheckMenuItem>),
+    RadioMenuItem(Vec<gtk::RadioMenuItem>),
     NativeMenuItem(NativeMenuItem),
 }
 
@@ -45,6 +244,11 @@ struct InnerMenu {
     // and push to it every time `Menu::init_for_gtk_window` is called.
     native_menus: HashMap<isize, (Option<gtk::MenuBar>, Rc<gtk::Box>)>,
     accel_group: Rc<gtk::AccelGroup>,
+    // gtk accel groups can't handle multi-stroke accelerators nor tell left and right modifiers apart,
+    // so the key events of each window this menu is added to are matched against these too
+    chords: MenuChords,
+    modifier_sides: Rc<Cell<ModifierSides>>,
+    key_handlers: HashMap<isize, Vec<glib::SignalHandlerId>>,
 }
 
 #[derive(Clone)]
@@ -56,37 +260,95 @@ impl Menu {
             entries: Vec::new(),
             native_menus: HashMap::new(),
             accel_group: Rc::new(gtk::AccelGroup::new()),
+            chords: Rc::new(RefCell::new(ChordMatcher::new())),
+            modifier_sides: Rc::new(Cell::new(ModifierSides::default())),
+            key_handlers: HashMap::new(),
         })))
     }
 
     pub fn add_submenu<S: AsRef<str>>(&mut self, label: S, enabled: bool) -> Submenu {
-        let label = label.as_ref().to_string();
+        let position = self.0.borrow().entries.len();
+        self.insert_submenu_at(label.as_ref(), enabled, position)
+    }
 
-        let entry = Rc::new(RefCell::new(MenuEntry {
-            label: label.clone(),
-            enabled,
-            entries: Some(Vec::new()),
-            r#type: MenuEntryType::Submenu(Vec::new()),
-            ..Default::default()
-        }));
+    pub fn insert_submenu<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        position: usize,
+    ) -> Result<Submenu, MenuError> {
+        check_insert_position(position, self.0.borrow().entries.len())?;
+        Ok(self.insert_submenu_at(label.as_ref(), enabled, position))
+    }
+
+    fn insert_submenu_at(&mut self, label: &str, enabled: bool, position: usize) -> Submenu {
+        let entry = MenuEntry::new_submenu(label, enabled);
 
         let mut inner = self.0.borrow_mut();
-        for (_, (menu_bar, _)) in inner.native_menus.iter() {
-            if let Some(menu_bar) = menu_bar {
-                let (item, submenu) = create_gtk_submenu(&label, enabled);
-                menu_bar.append(&item);
-                let mut native_menus = entry.borrow_mut();
-                if let MenuEntryType::Submenu(m) = &mut native_menus.r#type {
-                    m.push((item, submenu));
-                }
-            }
-        }
+        let inner = &mut *inner;
+        let menu_bars = gtk_menu_bars(&inner.native_menus);
+        insert_entry(
+            &mut inner.entries,
+            &menu_bars,
+            position,
+            Rc::clone(&entry),
+            &inner.accel_group,
+        );
 
-        inner.entries.push(entry.clone());
-        Submenu(entry, Rc::clone(&inner.accel_group))
+        Submenu(
+            entry,
+            Rc::clone(&inner.accel_group),
+            Rc::clone(&inner.chords),
+        )
     }
 
-    pub fn init_for_gtk_window<W>(&self, window: &W) -> Rc<gtk::Box>
+    pub fn remove(&mut self, child: MenuChild) -> Result<usize, MenuError> {
+        let position = child_position(&self.0.borrow().entries, child_entry(&child))?;
+        self.remove_at(position)?;
+        Ok(position)
+    }
+
+    pub fn remove_at(&mut self, position: usize) -> Result<(), MenuError> {
+        let mut inner = self.0.borrow_mut();
+        let inner = &mut *inner;
+        check_position(position, inner.entries.len())?;
+        let menu_bars = gtk_menu_bars(&inner.native_menus);
+        remove_entry(
+            &mut inner.entries,
+            &menu_bars,
+            position,
+            &inner.accel_group,
+            &inner.chords,
+        );
+        Ok(())
+    }
+
+    pub fn move_item(&mut self, child: MenuChild, position: usize) -> Result<usize, MenuError> {
+        let mut inner = self.0.borrow_mut();
+        let inner = &mut *inner;
+        let from = child_position(&inner.entries, child_entry(&child))?;
+        check_position(position, inner.entries.len())?;
+        let menu_bars = gtk_menu_bars(&inner.native_menus);
+        move_entry(&mut inner.entries, &menu_bars, from, position);
+        Ok(from)
+    }
+
+    pub fn clear(&mut self) {
+        let mut inner = self.0.borrow_mut();
+        let inner = &mut *inner;
+        let menu_bars = gtk_menu_bars(&inner.native_menus);
+        for position in (0..inner.entries.len()).rev() {
+            remove_entry(
+                &mut inner.entries,
+                &menu_bars,
+                position,
+                &inner.accel_group,
+                &inner.chords,
+            );
+        }
+    }
+
+    pub fn init_for_gtk_window<W>(&self, window: &W, tree: &MenuTree) -> Rc<gtk::Box>
     where
         W: IsA<gtk::ApplicationWindow>,
         W: IsA<gtk::Container>,
@@ -119,6 +381,54 @@ impl Menu {
             }
         }
 
+        if !inner.key_handlers.contains_key(&(window.as_ptr() as _)) {
+            let window = window.upcast_ref::<gtk::Window>();
+
+            let tree = tree.clone();
+            let chords = Rc::clone(&inner.chords);
+            let sides = Rc::clone(&inner.modifier_sides);
+            let press_handler = window.connect_key_press_event(move |_, event| {
+                let modifiers = gdk_modifier_type_to_modifiers(event.state());
+                let code = hardware_keycode_to_code(event.hardware_keycode());
+                let key = keyval_to_key(event.keyval());
+                let held = update_modifier_sides(&sides, modifiers, code, true);
+
+                let key_press = KeyPress {
+                    mods: modifiers,
+                    code,
+                    key: Some(key.clone()),
+                    sides: Some(held),
+                };
+                if dispatch_unregistered_accelerator(&tree, &key_press) {
+                    return gtk::Inhibit(true);
+                }
+
+                let state = chords.borrow_mut().process_key(modifiers, code, key);
+                match state {
+                    ChordState::Unmatched => gtk::Inhibit(false),
+                    ChordState::Completed(entry) => {
+                        if let Some(item) = enabled_chord_item(&tree, &entry) {
+                            item.activate();
+                        }
+                        gtk::Inhibit(true)
+                    }
+                    ChordState::Pending | ChordState::Cancelled => gtk::Inhibit(true),
+                }
+            });
+
+            let sides = Rc::clone(&inner.modifier_sides);
+            let release_handler = window.connect_key_release_event(move |_, event| {
+                let modifiers = gdk_modifier_type_to_modifiers(event.state());
+                let code = hardware_keycode_to_code(event.hardware_keycode());
+                update_modifier_sides(&sides, modifiers, code, false);
+                gtk::Inhibit(false)
+            });
+
+            inner
+                .key_handlers
+                .insert(window.as_ptr() as _, vec![press_handler, release_handler]);
+        }
+
         // Construct the entries of the menubar
         let (menu_bar, vbox) = inner.native_menus.get(&(window.as_ptr() as _)).unwrap();
         add_entries_to_menu(
@@ -152,9 +462,42 @@ impl Menu {
             inner
                 .native_menus
                 .insert(window.as_ptr() as _, (None, vbox));
+            // Stop listening for key events on the window
+            for handler in inner
+                .key_handlers
+                .remove(&(window.as_ptr() as _))
+                .unwrap_or_default()
+            {
+                window.disconnect(handler);
+            }
         }
     }
 
+    /// Applies `keymap` and returns the entries that don't match any item
+    /// and the paths of the items whose accelerator comes from it.
+    pub fn set_keymap(&self, keymap: &Keymap) -> (Vec<KeymapIssue>, Vec<String>) {
+        let mut used = vec![false; keymap.entries().len()];
+        let mut overridden = Vec::new();
+        let inner = self.0.borrow();
+        apply_keymap(
+            &inner.entries,
+            "",
+            keymap,
+            &inner.accel_group,
+            &mut used,
+            &mut overridden,
+        );
+
+        let issues = keymap
+            .entries()
+            .iter()
+            .zip(used)
+            .filter(|(_, used)| !used)
+            .map(|(entry, _)| KeymapIssue::MissingItem(entry.item.clone()))
+            .collect();
+        (issues, overridden)
+    }
+
     pub fn hide_for_gtk_window<W>(&self, window: &W)
     where
         W: IsA<gtk::ApplicationWindow>,
@@ -185,62 +528,141 @@ impl Menu {
 }
 
 #[derive(Clone)]
-pub struct Submenu(Rc<RefCell<MenuEntry>>, Rc<gtk::AccelGroup>);
+pub struct Submenu(Rc<RefCell<MenuEntry>>, Rc<gtk::AccelGroup>, MenuChords);
 
 impl Submenu {
     pub fn label(&self) -> String {
         self.0.borrow().label.clone()
     }
 
-    pub fn set_label<S: AsRef<str>>(&mut self, label: S) {
+    pub fn set_label<S: AsRef<str>>(&mut self, label: S) -> Result<(), MenuError> {
+        self.0.borrow().check_removed()?;
         let label = label.as_ref().to_string();
         let mut entry = self.0.borrow_mut();
         if let MenuEntryType::Submenu(native_menus) = &mut entry.r#type {
             for (item, _) in native_menus {
-                item.set_label(&to_gtk_menemenoic(&label));
+                set_gtk_label(item, &to_gtk_menemenoic(&label));
             }
         }
         entry.label = label;
+        tray::schedule_menu_update();
+        Ok(())
     }
 
     pub fn enabled(&self) -> bool {
         self.0.borrow().enabled
     }
 
-    pub fn set_enabled(&mut self, enabled: bool) {
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<(), MenuError> {
+        self.0.borrow().check_removed()?;
         let mut entry = self.0.borrow_mut();
-        entry.enabled = true;
+        entry.enabled = enabled;
         if let MenuEntryType::Submenu(native_menus) = &mut entry.r#type {
             for (item, _) in native_menus {
                 item.set_sensitive(enabled);
             }
         }
+        tray::schedule_menu_update();
+        Ok(())
     }
 
-    pub fn add_submenu<S: AsRef<str>>(&mut self, label: S, enabled: bool) -> Submenu {
-        let label = label.as_ref().to_string();
+    pub fn set_icon(&mut self, icon: Option<Icon>) -> Result<(), MenuError> {
+        self.0.borrow().check_removed()?;
+        let mut entry = self.0.borrow_mut();
+        if let MenuEntryType::Submenu(native_menus) = &entry.r#type {
+            for (item, _) in native_menus {
+                set_gtk_icon(item, icon.as_ref());
+            }
+        }
+        entry.icon = icon;
+        tray::schedule_menu_update();
+        Ok(())
+    }
 
-        let entry = Rc::new(RefCell::new(MenuEntry {
-            label: label.clone(),
-            enabled,
-            entries: Some(Vec::new()),
-            r#type: MenuEntryType::Submenu(Vec::new()),
-            ..Default::default()
-        }));
+    pub fn tooltip(&self) -> Option<String> {
+        self.0.borrow().tooltip.clone()
+    }
 
-        let mut inner = self.0.borrow_mut();
-        if let MenuEntryType::Submenu(native_menus) = &mut inner.r#type {
-            for (_, menu) in native_menus {
-                let (item, submenu) = create_gtk_submenu(&label, enabled);
-                menu.append(&item);
-                if let MenuEntryType::Submenu(menus) = &mut entry.borrow_mut().r#type {
-                    menus.push((item, submenu));
-                }
-            }
+    pub fn set_tooltip(&mut self, tooltip: Option<&str>) -> Result<(), MenuError> {
+        self.0.borrow().check_removed()?;
+        set_entry_tooltip(&self.0, tooltip);
+        Ok(())
+    }
+
+    pub fn show_context_menu_for_gtk_window<W: IsA<gtk::Widget>>(
+        &self,
+        widget: &W,
+        x: f64,
+        y: f64,
+    ) {
+        self.show_context_menu_for_gtk_window_at_rect(
+            widget,
+            gdk::Rectangle::new(x as i32, y as i32, 1, 1),
+            gdk::Gravity::NorthWest,
+            gdk::Gravity::NorthWest,
+        )
+    }
+
+    pub fn show_context_menu_for_gtk_window_at_rect<W: IsA<gtk::Widget>>(
+        &self,
+        widget: &W,
+        rect: gdk::Rectangle,
+        rect_anchor: gdk::Gravity,
+        menu_anchor: gdk::Gravity,
+    ) {
+        if let Some(context_menu) = context_menu(&self.0, &self.1) {
+            popup_context_menu(
+                &context_menu,
+                widget,
+                rect,
+                rect_anchor,
+                menu_anchor,
+                gtk::current_event().as_ref(),
+            );
+        }
+    }
+
+    pub fn attach_context_menu<W: IsA<gtk::Widget>>(&self, widget: &W) {
+        let mut entry = self.0.borrow_mut();
+        if entry.removed {
+            return;
         }
+        let weak_entry = Rc::downgrade(&self.0);
+        let accel_group = Rc::clone(&self.1);
+        entry
+            .context_menu_attachments
+            .attach(widget.as_ptr() as isize, || {
+                ContextMenuAttachment::new(widget.upcast_ref(), move || {
+                    weak_entry
+                        .upgrade()
+                        .and_then(|entry| context_menu(&entry, &accel_group))
+                })
+            });
+    }
+
+    pub fn detach_context_menu<W: IsA<gtk::Widget>>(&self, widget: &W) {
+        self.0
+            .borrow_mut()
+            .context_menu_attachments
+            .detach(widget.as_ptr() as isize);
+    }
+
+    pub fn add_submenu<S: AsRef<str>>(&mut self, label: S, enabled: bool) -> Submenu {
+        let entry = MenuEntry::new_submenu(label.as_ref(), enabled);
+        self.insert_entry(Rc::clone(&entry), self.len());
+        self.submenu(entry)
+    }
 
-        inner.entries.as_mut().unwrap().push(entry.clone());
-        Submenu(entry, Rc::clone(&self.1))
+    pub fn insert_submenu<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        position: usize,
+    ) -> Result<Submenu, MenuError> {
+        self.check_insert(position)?;
+        let entry = MenuEntry::new_submenu(label.as_ref(), enabled);
+        self.insert_entry(Rc::clone(&entry), position);
+        Ok(self.submenu(entry))
     }
 
     pub fn add_item<S: AsRef<str>>(
@@ -248,49 +670,65 @@ impl Submenu {
         label: S,
         enabled: bool,
         accelerator: Option<Accelerator>,
+        id: Option<MenuId>,
     ) -> MenuItem {
-        let label = label.as_ref().to_string();
-        let id = COUNTER.next();
-
-        let entry = Rc::new(RefCell::new(MenuEntry {
-            label: label.clone(),
-            enabled,
-            r#type: MenuEntryType::MenuItem(Vec::new()),
-            id,
-            accelerator: accelerator.clone(),
-            ..Default::default()
-        }));
-
-        let mut inner = self.0.borrow_mut();
+        let entry = MenuEntry::new_item(label.as_ref(), enabled, accelerator, id);
+        self.insert_entry(Rc::clone(&entry), self.len());
+        MenuItem(entry)
+    }
 
-        if let MenuEntryType::Submenu(native_menus) = &mut inner.r#type {
-            for (_, menu) in native_menus {
-                let item = create_gtk_menu_item(&label, enabled, &accelerator, id, &*self.1);
-                menu.append(&item);
-                if let MenuEntryType::MenuItem(native_items) = &mut entry.borrow_mut().r#type {
-                    native_items.push(item);
-                }
-            }
-        }
+    pub fn insert_item<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        accelerator: Option<Accelerator>,
+        position: usize,
+        id: Option<MenuId>,
+    ) -> Result<MenuItem, MenuError> {
+        self.check_insert(position)?;
+        let entry = MenuEntry::new_item(label.as_ref(), enabled, accelerator, id);
+        self.insert_entry(Rc::clone(&entry), position);
+        Ok(MenuItem(entry))
+    }
 
-        inner.entries.as_mut().unwrap().push(entry.clone());
+    pub fn add_chord_item<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        chord: Chord,
+        id: Option<MenuId>,
+    ) -> MenuItem {
+        let entry = MenuEntry::new_item(label.as_ref(), enabled, None, id);
+        entry.borrow_mut().chord = Some(chord.clone());
+        self.2
+            .borrow_mut()
+            .register(chord, ChordEntry(Rc::clone(&entry)));
+        self.insert_entry(Rc::clone(&entry), self.len());
         MenuItem(entry)
     }
 
-    pub fn add_native_item(&mut self, item: NativeMenuItem) {
-        let mut inner = self.0.borrow_mut();
+    pub fn add_native_item(&mut self, item: NativeMenuItem) -> bool {
+        let position = self.len();
+        self.insert_native_item_at(item, position);
+        true
+    }
 
-        if let MenuEntryType::Submenu(native_menus) = &mut inner.r#type {
-            for (_, menu) in native_menus {
-                item.add_to_gtk_menu(menu);
-            }
-        }
+    pub fn insert_native_item(
+        &mut self,
+        item: NativeMenuItem,
+        position: usize,
+    ) -> Result<bool, MenuError> {
+        self.check_insert(position)?;
+        self.insert_native_item_at(item, position);
+        Ok(true)
+    }
 
+    fn insert_native_item_at(&mut self, item: NativeMenuItem, position: usize) {
         let entry = Rc::new(RefCell::new(MenuEntry {
             r#type: MenuEntryType::NativeMenuItem(item),
             ..Default::default()
         }));
-        inner.entries.as_mut().unwrap().push(entry);
+        self.insert_entry(entry, position);
     }
 
     pub fn add_check_item<S: AsRef<str>>(
@@ -299,41 +737,121 @@ impl Submenu {
         enabled: bool,
         checked: bool,
         accelerator: Option<Accelerator>,
+        id: Option<MenuId>,
     ) -> CheckMenuItem {
-        let label = label.as_ref().to_string();
-        let id = COUNTER.next();
+        let entry = MenuEntry::new_check_item(label.as_ref(), enabled, checked, accelerator, id);
+        self.insert_entry(Rc::clone(&entry), self.len());
+        CheckMenuItem(entry)
+    }
 
-        let entry = Rc::new(RefCell::new(MenuEntry {
-            label: label.clone(),
-            enabled,
-            checked,
-            r#type: MenuEntryType::CheckMenuItem(Vec::new()),
-            id,
-            accelerator: accelerator.clone(),
-            ..Default::default()
-        }));
+    pub fn insert_check_item<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        checked: bool,
+        accelerator: Option<Accelerator>,
+        position: usize,
+        id: Option<MenuId>,
+    ) -> Result<CheckMenuItem, MenuError> {
+        self.check_insert(position)?;
+        let entry = MenuEntry::new_check_item(label.as_ref(), enabled, checked, accelerator, id);
+        self.insert_entry(Rc::clone(&entry), position);
+        Ok(CheckMenuItem(entry))
+    }
+
+    pub fn add_radio_item<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        checked: bool,
+        group: &RadioGroup,
+        accelerator: Option<Accelerator>,
+        id: Option<MenuId>,
+    ) -> RadioMenuItem {
+        let entry = MenuEntry::new_radio_item(label.as_ref(), enabled, group, accelerator, id);
+        group.add(&entry, checked);
+        self.insert_entry(Rc::clone(&entry), self.len());
+        RadioMenuItem(entry)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn insert_radio_item<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        checked: bool,
+        group: &RadioGroup,
+        accelerator: Option<Accelerator>,
+        position: usize,
+        id: Option<MenuId>,
+    ) -> Result<RadioMenuItem, MenuError> {
+        self.check_insert(position)?;
+        let entry = MenuEntry::new_radio_item(label.as_ref(), enabled, group, accelerator, id);
+        group.add(&entry, checked);
+        self.insert_entry(Rc::clone(&entry), position);
+        Ok(RadioMenuItem(entry))
+    }
+
+    pub fn remove(&mut self, child: MenuChild) -> Result<usize, MenuError> {
+        let position = child_position(
+            self.0.borrow().entries.as_ref().unwrap(),
+            child_entry(&child),
+        )?;
+        self.remove_at(position)?;
+        Ok(position)
+    }
 
+    pub fn remove_at(&mut self, position: usize) -> Result<(), MenuError> {
         let mut inner = self.0.borrow_mut();
+        inner.check_removed()?;
+        let menus = inner.gtk_menus();
+        let entries = inner.entries.as_mut().unwrap();
+        check_position(position, entries.len())?;
+        remove_entry(entries, &menus, position, &self.1, &self.2);
+        Ok(())
+    }
 
-        if let MenuEntryType::Submenu(native_menus) = &mut inner.r#type {
-            for (_, menu) in native_menus {
-                let item = create_gtk_check_menu_item(
-                    &label,
-                    enabled,
-                    checked,
-                    &accelerator,
-                    id,
-                    &*self.1,
-                );
-                menu.append(&item);
-                if let MenuEntryType::CheckMenuItem(native_items) = &mut entry.borrow_mut().r#type {
-                    native_items.push(item);
-                }
-            }
+    pub fn move_item(&mut self, child: MenuChild, position: usize) -> Result<usize, MenuError> {
+        self.0
+            .borrow_mut()
+            .move_child(child_entry(&child), position)
+    }
+
+    pub fn clear(&mut self) -> Result<(), MenuError> {
+        let mut inner = self.0.borrow_mut();
+        inner.check_removed()?;
+        let menus = inner.gtk_menus();
+        let entries = inner.entries.as_mut().unwrap();
+        for position in (0..entries.len()).rev() {
+            remove_entry(entries, &menus, position, &self.1, &self.2);
         }
+        Ok(())
+    }
 
-        inner.entries.as_mut().unwrap().push(entry.clone());
-        CheckMenuItem(entry)
+    fn submenu(&self, entry: Rc<RefCell<MenuEntry>>) -> Submenu {
+        Submenu(entry, Rc::clone(&self.1), Rc::clone(&self.2))
+    }
+
+    fn len(&self) -> usize {
+        self.0.borrow().entries.as_ref().unwrap().len()
+    }
+
+    fn check_insert(&self, position: usize) -> Result<(), MenuError> {
+        self.0.borrow().check_removed()?;
+        check_insert_position(position, self.len())
+    }
+
+    /// Inserts `entry` at `position`, entries added to a removed submenu are removed right away
+    /// so their accelerators and chords aren't left registered.
+    fn insert_entry(&mut self, entry: Rc<RefCell<MenuEntry>>, position: usize) {
+        let mut inner = self.0.borrow_mut();
+        let removed = inner.removed;
+        let menus = inner.gtk_menus();
+        let entries = inner.entries.as_mut().unwrap();
+        insert_entry(entries, &menus, position, Rc::clone(&entry), &self.1);
+        if removed {
+            remove_entry(entries, &menus, position, &self.1, &self.2);
+        }
     }
 }
 
@@ -345,22 +863,26 @@ impl MenuItem {
         self.0.borrow().label.clone()
     }
 
-    pub fn set_label<S: AsRef<str>>(&mut self, label: S) {
+    pub fn set_label<S: AsRef<str>>(&mut self, label: S) -> Result<(), MenuError> {
+        self.0.borrow().check_removed()?;
         let label = label.as_ref().to_string();
         let mut entry = self.0.borrow_mut();
         if let MenuEntryType::MenuItem(native_items) = &mut entry.r#type {
             for item in native_items {
-                item.set_label(&to_gtk_menemenoic(&label));
+                set_gtk_label(item, &to_gtk_menemenoic(&label));
             }
         }
         entry.label = label;
+        tray::schedule_menu_update();
+        Ok(())
     }
 
     pub fn enabled(&self) -> bool {
         self.0.borrow().enabled
     }
 
-    pub fn set_enabled(&mut self, enabled: bool) {
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<(), MenuError> {
+        self.0.borrow().check_removed()?;
         let mut entry = self.0.borrow_mut();
         if let MenuEntryType::MenuItem(native_items) = &mut entry.r#type {
             for item in native_items {
@@ -368,10 +890,55 @@ impl MenuItem {
             }
         }
         entry.enabled = enabled;
+        tray::schedule_menu_update();
+        Ok(())
+    }
+
+    pub fn set_icon(&mut self, icon: Option<Icon>) -> Result<(), MenuError> {
+        self.0.borrow().check_removed()?;
+        set_entry_icon(&self.0, icon);
+        tray::schedule_menu_update();
+        Ok(())
+    }
+
+    pub fn tooltip(&self) -> Option<String> {
+        self.0.borrow().tooltip.clone()
+    }
+
+    pub fn set_tooltip(&mut self, tooltip: Option<&str>) -> Result<(), MenuError> {
+        self.0.borrow().check_removed()?;
+        set_entry_tooltip(&self.0, tooltip);
+        Ok(())
+    }
+
+    pub fn help_text(&self) -> Option<String> {
+        self.0.borrow().help_text.clone()
+    }
+
+    pub fn set_help_text(&mut self, help_text: Option<&str>) -> Result<(), MenuError> {
+        self.0.borrow().check_removed()?;
+        self.0.borrow_mut().help_text = help_text.map(|h| h.to_string());
+        Ok(())
+    }
+
+    pub fn id(&self) -> MenuId {
+        self.0.borrow().id.clone()
+    }
+
+    pub fn accelerator(&self) -> Option<Accelerator> {
+        self.0.borrow().accelerator.clone()
+    }
+
+    pub fn check_accelerator(&self) -> Result<(), UnsupportedAcceleratorError> {
+        self.0.borrow().check_accelerator()
+    }
+
+    pub fn activate(&self) {
+        activate_entry(&self.0);
     }
 
-    pub fn id(&self) -> u64 {
-        self.0.borrow().id
+    pub fn chord(&self) -> Option<Chord> {
+        self.0.borrow().chord.clone()
     }
 }
 
@@ -383,22 +950,26 @@ impl CheckMenuItem {
         self.0.borrow().label.clone()
     }
 
-    pub fn set_label<S: AsRef<str>>(&mut self, label: S) {
+    pub fn set_label<S: AsRef<str>>(&mut self, label: S) -> Result<(), MenuError> {
+        self.0.borrow().check_removed()?;
         let label = label.as_ref().to_string();
         let mut entry = self.0.borrow_mut();
         if let MenuEntryType::CheckMenuItem(native_items) = &mut entry.r#type {
             for item in native_items {
-                item.set_label(&to_gtk_menemenoic(&label));
+                set_gtk_label(item.upcast_ref(), &to_gtk_menemenoic(&label));
             }
         }
         entry.label = label;
+        tray::schedule_menu_update();
+        Ok(())
     }
 
     pub fn enabled(&self) -> bool {
         self.0.borrow().enabled
     }
 
-    pub fn set_enabled(&mut self, enabled: bool) {
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<(), MenuError> {
+        self.0.borrow().check_removed()?;
         let mut entry = self.0.borrow_mut();
         if let MenuEntryType::CheckMenuItem(native_items) = &mut entry.r#type {
             for item in native_items {
@@ -406,6 +977,35 @@ impl CheckMenuItem {
             }
         }
         entry.enabled = enabled;
+        tray::schedule_menu_update();
+        Ok(())
+    }
+
+    pub fn set_icon(&mut self, icon: Option<Icon>) -> Result<(), MenuError> {
+        self.0.borrow().check_removed()?;
+        set_entry_icon(&self.0, icon);
+        tray::schedule_menu_update();
+        Ok(())
+    }
+
+    pub fn tooltip(&self) -> Option<String> {
+        self.0.borrow().tooltip.clone()
+    }
+
+    pub fn set_tooltip(&mut self, tooltip: Option<&str>) -> Result<(), MenuError> {
+        self.0.borrow().check_removed()?;
+        set_entry_tooltip(&self.0, tooltip);
+        Ok(())
+    }
+
+    pub fn help_text(&self) -> Option<String> {
+        self.0.borrow().help_text.clone()
+    }
+
+    pub fn set_help_text(&mut self, help_text: Option<&str>) -> Result<(), MenuError> {
+        self.0.borrow().check_removed()?;
+        self.0.borrow_mut().help_text = help_text.map(|h| h.to_string());
+        Ok(())
     }
 
     pub fn checked(&self) -> bool {
@@ -420,7 +1020,8 @@ impl CheckMenuItem {
         checked
     }
 
-    pub fn set_checked(&mut self, checked: bool) {
+    pub fn set_checked(&mut self, checked: bool) -> Result<(), MenuError> {
+        self.0.borrow().check_removed()?;
         let mut entry = self.0.borrow_mut();
         if let MenuEntryType::CheckMenuItem(native_items) = &mut entry.r#type {
             for item in native_items {
@@ -428,69 +1029,691 @@ impl CheckMenuItem {
             }
         }
         entry.checked = checked;
+        tray::schedule_menu_update();
+        Ok(())
     }
 
-    pub fn id(&self) -> u64 {
-        self.0.borrow().id
+    pub fn id(&self) -> MenuId {
+        self.0.borrow().id.clone()
     }
-}
 
-fn add_entries_to_menu<M: IsA<gtk::MenuShell>>(
-    gtk_menu: &M,
-    entries: &Vec<Rc<RefCell<MenuEntry>>>,
-    accel_group: &gtk::AccelGroup,
-) {
+    pub fn accelerator(&self) -> Option<Accelerator> {
+        self.0.borrow().accelerator.clone()
+    }
+
+    pub fn check_accelerator(&self) -> Result<(), UnsupportedAcceleratorError> {
+        self.0.borrow().check_accelerator()
+    }
+
+    pub fn activate(&self) {
+        activate_entry(&self.0);
+    }
+}
+
+#[derive(Debug, Default)]
+struct InnerRadioGroup {
+    entries: Vec<Weak<RefCell<MenuEntry>>>,
+    checked: Option<Weak<RefCell<MenuEntry>>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RadioGroup(Rc<RefCell<InnerRadioGroup>>);
+
+impl RadioGroup {
+    pub fn checked(&self) -> Option<RadioMenuItem> {
+        self.checked_entry().map(RadioMenuItem)
+    }
+
+    fn checked_entry(&self) -> Option<Rc<RefCell<MenuEntry>>> {
+        self.0.borrow().checked.as_ref().and_then(Weak::upgrade)
+    }
+
+    fn is_checked(&self, entry: &Rc<RefCell<MenuEntry>>) -> bool {
+        matches!(&self.0.borrow().checked, Some(checked) if checked.as_ptr() == Rc::as_ptr(entry))
+    }
+
+    /// Adds `entry` to the group, it becomes the checked entry if `checked` is `true`
+    /// or if no entry of the group is checked.
+    fn add(&self, entry: &Rc<RefCell<MenuEntry>>, checked: bool) {
+        let no_checked = self.checked_entry().is_none();
+        let mut inner = self.0.borrow_mut();
+        inner.entries.retain(|e| e.strong_count() > 0);
+        inner.entries.push(Rc::downgrade(entry));
+        if checked || no_checked {
+            inner.checked = Some(Rc::downgrade(entry));
+        }
+    }
+
+    fn remove(&self, entry: &Rc<RefCell<MenuEntry>>) {
+        if self.is_checked(entry) {
+            self.0.borrow_mut().checked = None;
+        }
+        self.0
+            .borrow_mut()
+            .entries
+            .retain(|e| e.as_ptr() != Rc::as_ptr(entry));
+    }
+
+    /// Makes `entry` the checked entry and returns whether the checked entry changed.
+    fn check(&self, entry: &Rc<RefCell<MenuEntry>>) -> bool {
+        if self.is_checked(entry) {
+            return false;
+        }
+        self.0.borrow_mut().checked = Some(Rc::downgrade(entry));
+        true
+    }
+
+    /// Returns a gtk widget of another entry of the group within the menu bar or popup menu `root`,
+    /// for a new widget of `entry` to join its gtk group.
+    fn gtk_group_source(
+        &self,
+        entry: &Rc<RefCell<MenuEntry>>,
+        root: &gtk::MenuShell,
+    ) -> Option<gtk::RadioMenuItem> {
+        let inner = self.0.borrow();
+        inner
+            .entries
+            .iter()
+            .filter(|e| e.as_ptr() != Rc::as_ptr(entry))
+            .filter_map(Weak::upgrade)
+            .find_map(|e| match &e.borrow().r#type {
+                MenuEntryType::RadioMenuItem(native_items) => native_items
+                    .iter()
+                    .find(|item| {
+                        item.parent()
+                            .and_then(|p| p.downcast::<gtk::MenuShell>().ok())
+                            .map(|shell| &root_menu_shell(&shell) == root)
+                            .unwrap_or(false)
+                    })
+                    .cloned(),
+                _ => None,
+            })
+    }
+}
+
+#[derive(Clone)]
+pub struct RadioMenuItem(Rc<RefCell<MenuEntry>>);
+
+impl RadioMenuItem {
+    pub fn label(&self) -> String {
+        self.0.borrow().label.clone()
+    }
+
+    pub fn set_label<S: AsRef<str>>(&mut self, label: S) -> Result<(), MenuError> {
+        self.0.borrow().check_removed()?;
+        let label = label.as_ref().to_string();
+        let mut entry = self.0.borrow_mut();
+        if let MenuEntryType::RadioMenuItem(native_items) = &mut entry.r#type {
+            for item in native_items {
+                set_gtk_label(item.upcast_ref(), &to_gtk_menemenoic(&label));
+            }
+        }
+        entry.label = label;
+        tray::schedule_menu_update();
+        Ok(())
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.0.borrow().enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<(), MenuError> {
+        self.0.borrow().check_removed()?;
+        let mut entry = self.0.borrow_mut();
+        if let MenuEntryType::RadioMenuItem(native_items) = &mut entry.r#type {
+            for item in native_items {
+                item.set_sensitive(enabled);
+            }
+        }
+        entry.enabled = enabled;
+        tray::schedule_menu_update();
+        Ok(())
+    }
+
+    pub fn set_icon(&mut self, icon: Option<Icon>) -> Result<(), MenuError> {
+        self.0.borrow().check_removed()?;
+        set_entry_icon(&self.0, icon);
+        tray::schedule_menu_update();
+        Ok(())
+    }
+
+    pub fn tooltip(&self) -> Option<String> {
+        self.0.borrow().tooltip.clone()
+    }
+
+    pub fn set_tooltip(&mut self, tooltip: Option<&str>) -> Result<(), MenuError> {
+        self.0.borrow().check_removed()?;
+        set_entry_tooltip(&self.0, tooltip);
+        Ok(())
+    }
+
+    pub fn help_text(&self) -> Option<String> {
+        self.0.borrow().help_text.clone()
+    }
+
+    pub fn set_help_text(&mut self, help_text: Option<&str>) -> Result<(), MenuError> {
+        self.0.borrow().check_removed()?;
+        self.0.borrow_mut().help_text = help_text.map(|h| h.to_string());
+        Ok(())
+    }
+
+    pub fn checked(&self) -> bool {
+        self.group().is_checked(&self.0)
+    }
+
+    pub fn check(&mut self) -> Result<(), MenuError> {
+        self.0.borrow().check_removed()?;
+        check_radio_entry(&self.0);
+        Ok(())
+    }
+
+    pub fn group(&self) -> RadioGroup {
+        self.0.borrow().radio_group.clone().unwrap()
+    }
+
+    pub fn id(&self) -> MenuId {
+        self.0.borrow().id.clone()
+    }
+
+    pub fn accelerator(&self) -> Option<Accelerator> {
+        self.0.borrow().accelerator.clone()
+    }
+
+    pub fn check_accelerator(&self) -> Result<(), UnsupportedAcceleratorError> {
+        self.0.borrow().check_accelerator()
+    }
+
+    pub fn activate(&self) {
+        activate_entry(&self.0);
+    }
+}
+
+/// Makes `entry` the checked entry of its radio group and checks its gtk widgets,
+/// which unchecks the other widgets of the group in every window, returns whether the checked entry changed.
+fn check_radio_entry(entry: &Rc<RefCell<MenuEntry>>) -> bool {
+    let group = entry.borrow().radio_group.clone().unwrap();
+    if !group.check(entry) {
+        return false;
+    }
+    // the activate handlers of the widgets see that the entry is already checked
+    let native_items = match &entry.borrow().r#type {
+        MenuEntryType::RadioMenuItem(native_items) => native_items.clone(),
+        _ => Vec::new(),
+    };
+    for item in native_items {
+        item.set_active(true);
+    }
+    tray::schedule_menu_update();
+    true
+}
+
+/// Returns the menu bar or popup menu `shell` is part of.
+fn root_menu_shell(shell: &gtk::MenuShell) -> gtk::MenuShell {
+    let mut shell = shell.clone();
+    while let Some(parent) = shell
+        .downcast_ref::<gtk::Menu>()
+        .and_then(|menu| menu.attach_widget())
+        .and_then(|item| item.parent())
+        .and_then(|parent| parent.downcast::<gtk::MenuShell>().ok())
+    {
+        shell = parent;
+    }
+    shell
+}
+
+/// Returns the gtk menu of a submenu shown as a context menu, creating it
+/// the first time, or `None` if the submenu was removed.
+fn context_menu(
+    entry: &Rc<RefCell<MenuEntry>>,
+    accel_group: &gtk::AccelGroup,
+) -> Option<gtk::Menu> {
+    let e = entry.borrow();
+    if e.removed {
+        return None;
+    }
+    if let Some(context_menu) = &e.context_menu {
+        return Some(context_menu.clone());
+    }
+    let entries = e.entries.clone().unwrap();
+    drop(e);
+
+    let context_menu = gtk::Menu::new();
+    add_entries_to_menu(&context_menu, &entries, accel_group);
+    entry.borrow_mut().context_menu = Some(context_menu.clone());
+    Some(context_menu)
+}
+
+/// Sets the icon of a menu item or check menu item and updates its gtk widgets.
+fn set_entry_icon(entry: &Rc<RefCell<MenuEntry>>, icon: Option<Icon>) {
+    let mut entry = entry.borrow_mut();
+    for item in entry.gtk_items() {
+        set_gtk_icon(&item, icon.as_ref());
+    }
+    entry.icon = icon;
+}
+
+/// Sets the tooltip of a submenu, menu item or check menu item and updates its gtk widgets.
+fn set_entry_tooltip(entry: &Rc<RefCell<MenuEntry>>, tooltip: Option<&str>) {
+    let mut entry = entry.borrow_mut();
+    let items = match &entry.r#type {
+        MenuEntryType::Submenu(native_menus) => {
+            native_menus.iter().map(|(item, _)| item.clone()).collect()
+        }
+        _ => entry.gtk_items(),
+    };
+    for item in items {
+        item.set_tooltip_text(tooltip);
+    }
+    entry.tooltip = tooltip.map(|t| t.to_string());
+}
+
+/// Emits the [`crate::MenuHighlightEvent`]s of a menu item when its widget is selected,
+/// by hovering it or moving to it with the keyboard, and deselected.
+fn connect_highlight_events(item: &gtk::MenuItem, entry: &Rc<RefCell<MenuEntry>>) {
+    let weak_entry = Rc::downgrade(entry);
+    item.connect_select(move |_| send_highlight_event(&weak_entry, true));
+    let weak_entry = Rc::downgrade(entry);
+    item.connect_deselect(move |_| send_highlight_event(&weak_entry, false));
+}
+
+fn send_highlight_event(entry: &Weak<RefCell<MenuEntry>>, highlighted: bool) {
+    let entry = match entry.upgrade() {
+        Some(entry) => entry,
+        None => return,
+    };
+    // the entry can already be borrowed when gtk deselects its widget as part of a change to the menu
+    if let Ok(e) = entry.try_borrow() {
+        let _ = crate::MENU_HIGHLIGHT_CHANNEL
+            .0
+            .send(crate::MenuHighlightEvent {
+                id: e.id.clone(),
+                help_text: e.help_text.clone(),
+                highlighted,
+            });
+    };
+}
+
+/// Activates a menu item like clicking it does.
+fn activate_entry(entry: &Rc<RefCell<MenuEntry>>) {
+    let native_item: Option<gtk::MenuItem> = match &entry.borrow().r#type {
+        MenuEntryType::MenuItem(native_items) => native_items.first().cloned(),
+        MenuEntryType::CheckMenuItem(native_items) => {
+            native_items.first().map(|i| i.clone().upcast())
+        }
+        MenuEntryType::RadioMenuItem(native_items) => {
+            native_items.first().map(|i| i.clone().upcast())
+        }
+        _ => None,
+    };
+    match native_item {
+        // activating the gtk item toggles check items, checks radio items and emits the event
+        Some(item) => {
+            item.activate();
+        }
+        None if entry.borrow().radio_group.is_some() => {
+            if check_radio_entry(entry) {
+                let _ = crate::MENU_CHANNEL.0.send(crate::MenuEvent {
+                    id: entry.borrow().id.clone(),
+                });
+            }
+        }
+        None => {
+            let mut entry = entry.borrow_mut();
+            if let MenuEntryType::CheckMenuItem(_) = entry.r#type {
+                entry.checked = !entry.checked;
+            }
+            let _ = crate::MENU_CHANNEL.0.send(crate::MenuEvent {
+                id: entry.id.clone(),
+            });
+        }
+    }
+}
+
+/// Updates the held modifier keys from a key event and returns them.
+///
+/// Modifiers missing from the event state are released, in case their release
+/// happened while the window wasn't focused.
+fn update_modifier_sides(
+    sides: &Cell<ModifierSides>,
+    modifiers: crate::accelerator::Modifiers,
+    code: crate::accelerator::Code,
+    pressed: bool,
+) -> ModifierSides {
+    let mut held = sides.get();
+    held.left &= modifiers;
+    held.right &= modifiers;
+    held.update(code, pressed);
+    sides.set(held);
+    held
+}
+
+/// Activates the item matching `key` if its accelerator isn't registered with gtk,
+/// see [`is_matched_by_gtk`].
+fn dispatch_unregistered_accelerator(tree: &MenuTree, key: &KeyPress) -> bool {
+    match tree.find_by_accelerator(key) {
+        Some(item) if matches!(item_accelerator(&item), Some(a) if !is_matched_by_gtk(&a)) => {
+            item.activate();
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Finds the item of a completed chord in `tree`,
+/// `None` if the item or one of its submenus is disabled.
+fn enabled_chord_item(tree: &MenuTree, entry: &ChordEntry) -> Option<MenuItemKind> {
+    tree.find_enabled(
+        &|item| matches!(item, MenuItemKind::MenuItem(item) if Rc::ptr_eq(&(item.0).0, &entry.0)),
+    )
+}
+
+/// Sets the accelerator of every item to the one from `keymap` or its default one and updates
+/// the accelerators registered with gtk, marks the used keymap entries in `used`
+/// and collects the paths of the items whose accelerator comes from `keymap` in `overridden`.
+fn apply_keymap(
+    entries: &[Rc<RefCell<MenuEntry>>],
+    parent: &str,
+    keymap: &Keymap,
+    accel_group: &gtk::AccelGroup,
+    used: &mut [bool],
+    overridden: &mut Vec<String>,
+) {
     for entry in entries {
         let mut entry = entry.borrow_mut();
-        let (item, submenu) = match &mut entry.r#type {
-            MenuEntryType::Submenu(_) => {
-                let (item, submenu) = create_gtk_submenu(&entry.label, entry.enabled);
-                gtk_menu.append(&item);
-                add_entries_to_menu(&submenu, entry.entries.as_ref().unwrap(), accel_group);
-                (Some(item), Some(submenu))
-            }
-            MenuEntryType::MenuItem(_) => {
-                let item = create_gtk_menu_item(
-                    &entry.label,
-                    entry.enabled,
-                    &entry.accelerator,
-                    entry.id,
-                    accel_group,
-                );
-                gtk_menu.append(&item);
-                (Some(item), None)
-            }
-            MenuEntryType::CheckMenuItem(_) => {
-                let item = create_gtk_check_menu_item(
-                    &entry.label,
-                    entry.enabled,
-                    entry.checked,
-                    &entry.accelerator,
-                    entry.id,
-                    accel_group,
-                );
-                gtk_menu.append(&item);
-                (Some(item.upcast::<gtk::MenuItem>()), None)
-            }
-            MenuEntryType::NativeMenuItem(native_menu_item) => {
-                native_menu_item.add_to_gtk_menu(gtk_menu);
-                (None, None)
+        let path = item_path(parent, &entry.label);
+        if let MenuEntryType::Submenu(_) = &entry.r#type {
+            apply_keymap(
+                entry.entries.as_ref().unwrap(),
+                &path,
+                keymap,
+                accel_group,
+                used,
+                overridden,
+            );
+            continue;
+        }
+
+        let accelerator = match keymap.find(&entry.id, &path) {
+            Some(i) => {
+                used[i] = true;
+                overridden.push(path);
+                keymap.entries()[i].accelerator.clone()
             }
+            None => entry.default_accelerator.clone(),
         };
+        if accelerator == entry.accelerator {
+            continue;
+        }
 
-        match &mut entry.r#type {
-            MenuEntryType::Submenu(native_menus) => {
-                native_menus.push((item.unwrap(), submenu.unwrap()));
+        entry.accelerator_registration = None;
+        for item in entry.gtk_items() {
+            if let Some(old) = &entry.accelerator {
+                unregister_accelerator(&item, accel_group, old);
             }
-            MenuEntryType::MenuItem(native_items) => {
-                native_items.push(item.unwrap());
+            if let Some(new) = &accelerator {
+                // unsupported accelerators are reported by `Menu::accelerator_conflicts`
+                // and `check_accelerator` of the menu item
+                entry.accelerator_registration =
+                    Some(register_accelerator(&item, accel_group, new));
             }
-            MenuEntryType::CheckMenuItem(native_items) => {
-                native_items.push(item.unwrap().downcast().unwrap());
+        }
+        entry.accelerator = accelerator;
+    }
+}
+
+fn add_entries_to_menu<M: IsA<gtk::MenuShell>>(
+    gtk_menu: &M,
+    entries: &[Rc<RefCell<MenuEntry>>],
+    accel_group: &gtk::AccelGroup,
+) {
+    for entry in entries {
+        add_entry_to_menu(gtk_menu, entry, -1, accel_group);
+    }
+}
+
+/// Creates the gtk widgets of `entry` and inserts them at `position` in `gtk_menu`, `-1` appends them.
+fn add_entry_to_menu<M: IsA<gtk::MenuShell>>(
+    gtk_menu: &M,
+    entry: &Rc<RefCell<MenuEntry>>,
+    position: i32,
+    accel_group: &gtk::AccelGroup,
+) {
+    let mut e = entry.borrow_mut();
+    let (item, submenu) = match &mut e.r#type {
+        MenuEntryType::Submenu(_) => {
+            let (item, submenu) = create_gtk_submenu(&e.label, e.enabled);
+            gtk_menu.insert(&item, position);
+            add_entries_to_menu(&submenu, e.entries.as_ref().unwrap(), accel_group);
+            (Some(item), Some(submenu))
+        }
+        MenuEntryType::MenuItem(_) => {
+            let item = create_gtk_menu_item(&e.label, e.enabled, e.id.clone());
+            if let Some(chord) = &e.chord {
+                set_chord_accel_label(&item, chord);
             }
-            MenuEntryType::NativeMenuItem(_) => {}
-        };
+            gtk_menu.insert(&item, position);
+            (Some(item), None)
+        }
+        MenuEntryType::CheckMenuItem(_) => {
+            let item = create_gtk_check_menu_item(&e.label, e.enabled, e.checked, e.id.clone());
+            gtk_menu.insert(&item, position);
+            (Some(item.upcast::<gtk::MenuItem>()), None)
+        }
+        MenuEntryType::RadioMenuItem(_) => {
+            let item = create_gtk_radio_menu_item(&e.label, e.enabled);
+            gtk_menu.insert(&item, position);
+            let group = e.radio_group.as_ref().unwrap();
+            let root = root_menu_shell(gtk_menu.upcast_ref::<gtk::MenuShell>());
+            if let Some(source) = group.gtk_group_source(entry, &root) {
+                item.join_group(Some(&source));
+            }
+            item.set_active(group.is_checked(entry));
+            // connected once the widget is set up, so setting its initial state doesn't emit events
+            let weak_entry = Rc::downgrade(entry);
+            item.connect_activate(move |item| {
+                // gtk also activates the widget being unchecked
+                if !item.is_active() {
+                    return;
+                }
+                if let Some(entry) = weak_entry.upgrade() {
+                    if check_radio_entry(&entry) {
+                        let _ = crate::MENU_CHANNEL.0.send(crate::MenuEvent {
+                            id: entry.borrow().id.clone(),
+                        });
+                    }
+                }
+            });
+            (Some(item.upcast::<gtk::MenuItem>()), None)
+        }
+        MenuEntryType::NativeMenuItem(native_menu_item) => {
+            native_menu_item.add_to_gtk_menu(gtk_menu, position);
+            (None, None)
+        }
+    };
+    if let (Some(item), Some(accelerator)) = (&item, &e.accelerator) {
+        // unsupported accelerators are reported by `check_accelerator` of the menu item
+        e.accelerator_registration = Some(register_accelerator(item, accel_group, accelerator));
+    }
+    if let (Some(item), Some(icon)) = (&item, &e.icon) {
+        set_gtk_icon(item, Some(icon));
     }
+    if let Some(item) = &item {
+        if e.tooltip.is_some() {
+            item.set_tooltip_text(e.tooltip.as_deref());
+        }
+        if submenu.is_none() {
+            connect_highlight_events(item, entry);
+        }
+    }
+
+    match &mut e.r#type {
+        MenuEntryType::Submenu(native_menus) => {
+            native_menus.push((item.unwrap(), submenu.unwrap()));
+        }
+        MenuEntryType::MenuItem(native_items) => {
+            native_items.push(item.unwrap());
+        }
+        MenuEntryType::CheckMenuItem(native_items) => {
+            native_items.push(item.unwrap().downcast().unwrap());
+        }
+        MenuEntryType::RadioMenuItem(native_items) => {
+            native_items.push(item.unwrap().downcast().unwrap());
+        }
+        MenuEntryType::NativeMenuItem(_) => {}
+    };
+}
+
+/// Returns the menu bars of a menu, one for each window it is currently added to.
+fn gtk_menu_bars(
+    native_menus: &HashMap<isize, (Option<gtk::MenuBar>, Rc<gtk::Box>)>,
+) -> Vec<gtk::MenuShell> {
+    native_menus
+        .values()
+        .filter_map(|(menu_bar, _)| menu_bar.clone())
+        .map(|menu_bar| menu_bar.upcast())
+        .collect()
+}
+
+fn child_entry<'a>(child: &MenuChild<'a>) -> &'a Rc<RefCell<MenuEntry>> {
+    match child {
+        MenuChild::Submenu(submenu) => &submenu.0,
+        MenuChild::MenuItem(item) => &item.0,
+        MenuChild::CheckMenuItem(item) => &item.0,
+        MenuChild::RadioMenuItem(item) => &item.0,
+    }
+}
+
+/// Returns the position of `child` in `entries`.
+fn child_position(
+    entries: &[Rc<RefCell<MenuEntry>>],
+    child: &Rc<RefCell<MenuEntry>>,
+) -> Result<usize, MenuError> {
+    match entries.iter().position(|entry| Rc::ptr_eq(entry, child)) {
+        Some(position) => Ok(position),
+        // `child` can be the submenu the operation was called on, which is already borrowed
+        None if matches!(child.try_borrow(), Ok(child) if child.removed) => {
+            Err(MenuError::ItemRemoved)
+        }
+        None => Err(MenuError::NotAChild),
+    }
+}
+
+/// Returns the position in the gtk menus of the widget of the entry at `position`,
+/// which differs from `position` when previous entries have no widget.
+fn gtk_position(entries: &[Rc<RefCell<MenuEntry>>], position: usize) -> i32 {
+    entries[..position]
+        .iter()
+        .filter(|entry| entry.borrow().has_gtk_widget())
+        .count() as i32
+}
+
+/// Inserts `entry` at `position` in `entries` and adds its widgets to each of `gtk_menus`.
+fn insert_entry(
+    entries: &mut Vec<Rc<RefCell<MenuEntry>>>,
+    gtk_menus: &[gtk::MenuShell],
+    position: usize,
+    entry: Rc<RefCell<MenuEntry>>,
+    accel_group: &gtk::AccelGroup,
+) {
+    let gtk_position = gtk_position(entries, position);
+    for gtk_menu in gtk_menus {
+        add_entry_to_menu(gtk_menu, &entry, gtk_position, accel_group);
+    }
+    entries.insert(position, entry);
+    tray::schedule_menu_update();
+}
+
+/// Removes the entry at `position` from `entries` and destroys its widget in each of `gtk_menus`.
+fn remove_entry(
+    entries: &mut Vec<Rc<RefCell<MenuEntry>>>,
+    gtk_menus: &[gtk::MenuShell],
+    position: usize,
+    accel_group: &gtk::AccelGroup,
+    chords: &MenuChords,
+) {
+    let gtk_position = gtk_position(entries, position);
+    let entry = entries.remove(position);
+    release_entry(&entry, accel_group, chords);
+
+    if entry.borrow().has_gtk_widget() {
+        for gtk_menu in gtk_menus {
+            if let Some(widget) = gtk_menu.children().get(gtk_position as usize) {
+                unsafe { widget.destroy() };
+            }
+        }
+    }
+    tray::schedule_menu_update();
+}
+
+/// Unregisters the accelerators and chords of a removed entry and its children,
+/// forgets their widgets and marks them as removed.
+fn release_entry(
+    entry: &Rc<RefCell<MenuEntry>>,
+    accel_group: &gtk::AccelGroup,
+    chords: &MenuChords,
+) {
+    if let Some(group) = entry.borrow().radio_group.clone() {
+        group.remove(entry);
+    }
+    let mut e = entry.borrow_mut();
+    if let Some(accelerator) = &e.accelerator {
+        for item in e.gtk_items() {
+            unregister_accelerator(&item, accel_group, accelerator);
+        }
+    }
+    if e.chord.is_some() {
+        chords
+            .borrow_mut()
+            .unregister(&ChordEntry(Rc::clone(entry)));
+    }
+    for child in e.entries.iter().flatten() {
+        release_entry(child, accel_group, chords);
+    }
+
+    e.removed = true;
+    if let Some(context_menu) = e.context_menu.take() {
+        unsafe { context_menu.destroy() };
+    }
+    e.context_menu_attachments.detach_all();
+    let mut checked = None;
+    match &mut e.r#type {
+        MenuEntryType::Submenu(native_menus) => native_menus.clear(),
+        MenuEntryType::MenuItem(native_items) => native_items.clear(),
+        MenuEntryType::RadioMenuItem(native_items) => native_items.clear(),
+        MenuEntryType::CheckMenuItem(native_items) => {
+            // the checked state is read from the widgets while there are any
+            checked = native_items.first().map(|item| item.is_active());
+            native_items.clear();
+        }
+        MenuEntryType::NativeMenuItem(_) => {}
+    }
+    if let Some(checked) = checked {
+        e.checked = checked;
+    }
+}
+
+/// Moves the entry at `from` to `to` in `entries` and its widget in each of `gtk_menus`.
+fn move_entry(
+    entries: &mut Vec<Rc<RefCell<MenuEntry>>>,
+    gtk_menus: &[gtk::MenuShell],
+    from: usize,
+    to: usize,
+) {
+    let from_gtk_position = gtk_position(entries, from);
+    let entry = entries.remove(from);
+    let has_gtk_widget = entry.borrow().has_gtk_widget();
+    entries.insert(to, entry);
+
+    if has_gtk_widget {
+        let to_gtk_position = gtk_position(entries, to);
+        for gtk_menu in gtk_menus {
+            if let Some(widget) = gtk_menu.children().get(from_gtk_position as usize) {
+                gtk_menu.remove(widget);
+                gtk_menu.insert(widget, to_gtk_position);
+            }
+        }
+    }
+    tray::schedule_menu_update();
 }
 
 fn create_gtk_submenu(label: &str, enabled: bool) -> (gtk::MenuItem, gtk::Menu) {
@@ -502,48 +1725,85 @@ fn create_gtk_submenu(label: &str, enabled: bool) -> (gtk::MenuItem, gtk::Menu)
     (item, menu)
 }
 
-fn create_gtk_menu_item(
-    label: &str,
-    enabled: bool,
-    accelerator: &Option<Accelerator>,
-    id: u64,
-    accel_group: &gtk::AccelGroup,
-) -> gtk::MenuItem {
+fn create_gtk_menu_item(label: &str, enabled: bool, id: MenuId) -> gtk::MenuItem {
     let item = gtk::MenuItem::with_mnemonic(&to_gtk_menemenoic(label));
     item.set_sensitive(enabled);
-    if let Some(accelerator) = accelerator {
-        register_accelerator(&item, accel_group, accelerator);
-    }
     item.connect_activate(move |_| {
-        let _ = crate::MENU_CHANNEL.0.send(crate::MenuEvent { id });
+        let _ = crate::MENU_CHANNEL
+            .0
+            .send(crate::MenuEvent { id: id.clone() });
     });
     item.show();
     item
 }
 
+/// Displays the first stroke of `chord` as the accelerator of `item`,
+/// gtk can't display multi-stroke accelerators.
+fn set_chord_accel_label(item: &gtk::MenuItem, chord: &Chord) {
+    if let Ok((key, modifiers)) = to_gtk_accelerator(chord.first()) {
+        set_accel_label(item, key, modifiers);
+    }
+}
+
 fn create_gtk_check_menu_item(
     label: &str,
     enabled: bool,
     checked: bool,
-    accelerator: &Option<Accelerator>,
-    id: u64,
-    accel_group: &gtk::AccelGroup,
+    id: MenuId,
 ) -> gtk::CheckMenuItem {
     let item = gtk::CheckMenuItem::with_mnemonic(&to_gtk_menemenoic(label));
     item.set_sensitive(enabled);
     item.set_active(checked);
-    if let Some(accelerator) = accelerator {
-        register_accelerator(&item, accel_group, accelerator);
-    }
     item.connect_activate(move |_| {
-        let _ = crate::MENU_CHANNEL.0.send(crate::MenuEvent { id });
+        let _ = crate::MENU_CHANNEL
+            .0
+            .send(crate::MenuEvent { id: id.clone() });
     });
     item.show();
     item
 }
 
+fn create_gtk_radio_menu_item(label: &str, enabled: bool) -> gtk::RadioMenuItem {
+    let item = gtk::RadioMenuItem::with_mnemonic(&to_gtk_menemenoic(label));
+    item.set_sensitive(enabled);
+    item.show();
+    item
+}
+
 impl NativeMenuItem {
-    fn add_to_gtk_menu<M: IsA<gtk::MenuShell>>(&self, gtk_menu: &M) {
+    /// The label this native menu item is displayed with, using `&` for mnemonics.
+    pub(crate) fn label(&self) -> String {
+        match self {
+            NativeMenuItem::Copy => "&Copy".into(),
+            NativeMenuItem::Cut => "Cu&t".into(),
+            NativeMenuItem::Paste => "&Paste".into(),
+            NativeMenuItem::SelectAll => "Select &All".into(),
+            NativeMenuItem::Minimize => "&Minimize".into(),
+            NativeMenuItem::CloseWindow => "C&lose Window".into(),
+            NativeMenuItem::Quit => "&Quit".into(),
+            NativeMenuItem::About(app_name, _) => format!("About {}", app_name),
+            _ => format!("{:?}", self),
+        }
+    }
+
+    /// Returns whether this native menu item is supported on Linux,
+    /// which are the ones [`NativeMenuItem::add_to_gtk_menu`] creates a widget for.
+    fn has_gtk_widget(&self) -> bool {
+        matches!(
+            self,
+            NativeMenuItem::Copy
+                | NativeMenuItem::Cut
+                | NativeMenuItem::Paste
+                | NativeMenuItem::SelectAll
+                | NativeMenuItem::Separator
+                | NativeMenuItem::Minimize
+                | NativeMenuItem::CloseWindow
+                | NativeMenuItem::Quit
+                | NativeMenuItem::About(..)
+        )
+    }
+
+    fn add_to_gtk_menu<M: IsA<gtk::MenuShell>>(&self, gtk_menu: &M, position: i32) {
         match self {
             NativeMenuItem::Copy => {
                 let item = gtk::MenuItem::with_mnemonic("_Copy");
@@ -560,7 +1820,7 @@ impl NativeMenuItem {
                     }
                 });
                 item.show();
-                gtk_menu.append(&item);
+                gtk_menu.insert(&item, position);
             }
             NativeMenuItem::Cut => {
                 let item = gtk::MenuItem::with_mnemonic("Cu_t");
@@ -577,7 +1837,7 @@ impl NativeMenuItem {
                     }
                 });
                 item.show();
-                gtk_menu.append(&item);
+                gtk_menu.insert(&item, position);
             }
             NativeMenuItem::Paste => {
                 let item = gtk::MenuItem::with_mnemonic("_Paste");
@@ -594,7 +1854,7 @@ impl NativeMenuItem {
                     }
                 });
                 item.show();
-                gtk_menu.append(&item);
+                gtk_menu.insert(&item, position);
             }
             NativeMenuItem::SelectAll => {
                 let item = gtk::MenuItem::with_mnemonic("Select _All");
@@ -611,10 +1871,10 @@ impl NativeMenuItem {
                     }
                 });
                 item.show();
-                gtk_menu.append(&item);
+                gtk_menu.insert(&item, position);
             }
             NativeMenuItem::Separator => {
-                gtk_menu.append(&gtk::SeparatorMenuItem::new());
+                gtk_menu.insert(&gtk::SeparatorMenuItem::new(), position);
             }
             NativeMenuItem::Minimize => {
                 let item = gtk::MenuItem::with_mnemonic("_Minimize");
@@ -624,7 +1884,7 @@ impl NativeMenuItem {
                     }
                 });
                 item.show();
-                gtk_menu.append(&item);
+                gtk_menu.insert(&item, position);
             }
             NativeMenuItem::CloseWindow => {
                 let item = gtk::MenuItem::with_mnemonic("C_lose Window");
@@ -634,7 +1894,7 @@ impl NativeMenuItem {
                     }
                 });
                 item.show();
-                gtk_menu.append(&item);
+                gtk_menu.insert(&item, position);
             }
             NativeMenuItem::Quit => {
                 let item = gtk::MenuItem::with_mnemonic("_Quit");
@@ -642,7 +1902,7 @@ impl NativeMenuItem {
                     std::process::exit(0);
                 });
                 item.show();
-                gtk_menu.append(&item);
+                gtk_menu.insert(&item, position);
             }
             NativeMenuItem::About(app_name, metadata) => {
                 let app_name = app_name.clone();
@@ -682,9 +1942,444 @@ impl NativeMenuItem {
                     }
                 });
                 item.show();
-                gtk_menu.append(&item);
+                gtk_menu.insert(&item, position);
             }
             _ => {}
         }
     }
 }
+
+/// Creates a [`crate::Submenu`] with the items of `tree` for tests,
+/// `gtk::AccelGroup::new` needs gtk to be initialized but creating the object doesn't.
+#[cfg(test)]
+fn test_submenu(label: &str, enabled: bool, tree: MenuTree) -> crate::Submenu {
+    let accel_group = glib::Object::with_type(gtk::AccelGroup::static_type(), &[])
+        .unwrap()
+        .downcast()
+        .unwrap();
+    let entry = MenuEntry::new_submenu(label, enabled);
+    crate::Submenu(
+        Submenu(entry, Rc::new(accel_group), Default::default()),
+        tree,
+    )
+}
+
+#[test]
+fn test_find_by_accelerator() {
+    use crate::{
+        accelerator::{Code, Modifiers},
+        MenuItemKind,
+    };
+
+    let item = |id: u64, enabled, accelerator: &str| {
+        let entry = MenuEntry::new_item(
+            "",
+            enabled,
+            Some(accelerator.parse().unwrap()),
+            Some(MenuId::from(id)),
+        );
+        MenuItemKind::MenuItem(crate::MenuItem(MenuItem(entry)))
+    };
+    let tree = MenuTree::default();
+    let disabled = tree.submenu();
+    disabled.push(item(1, true, "Ctrl+KeyO"));
+    tree.push(MenuItemKind::Submenu(test_submenu(
+        "Disabled", false, disabled,
+    )));
+    let enabled = tree.submenu();
+    enabled.push(item(2, false, "Ctrl+KeyS"));
+    enabled.push(item(3, true, "Ctrl+KeyS"));
+    enabled.push(item(4, true, "Ctrl+KeyO"));
+    let mut submenu = test_submenu("Enabled", true, enabled);
+    tree.push(MenuItemKind::Submenu(submenu.clone()));
+
+    let find = |mods, code| {
+        let key = KeyPress {
+            mods,
+            code,
+            key: None,
+            sides: None,
+        };
+        tree.find_by_accelerator(&key).and_then(|item| item.id())
+    };
+    assert_eq!(find(Modifiers::CONTROL, Code::KeyS), Some(MenuId::from(3)));
+    assert_eq!(find(Modifiers::CONTROL, Code::KeyO), Some(MenuId::from(4)));
+    assert_eq!(find(Modifiers::SHIFT, Code::KeyO), None);
+
+    submenu.set_enabled(false).unwrap();
+    assert_eq!(find(Modifiers::CONTROL, Code::KeyS), None);
+}
+
+#[test]
+fn test_enabled_chord_item() {
+    let chord_item = |id: u64| {
+        let entry = MenuEntry::new_item("", true, None, Some(MenuId::from(id)));
+        entry.borrow_mut().chord = Some("Ctrl+KeyK Ctrl+KeyS".parse().unwrap());
+        entry
+    };
+    let tree = MenuTree::default();
+    let top_level = chord_item(1);
+    tree.push(MenuItemKind::MenuItem(crate::MenuItem(MenuItem(
+        Rc::clone(&top_level),
+    ))));
+    let items = tree.submenu();
+    let nested = chord_item(2);
+    items.push(MenuItemKind::MenuItem(crate::MenuItem(MenuItem(
+        Rc::clone(&nested),
+    ))));
+    let mut submenu = test_submenu("Disabled", false, items);
+    tree.push(MenuItemKind::Submenu(submenu.clone()));
+
+    let find = |entry: &Rc<RefCell<MenuEntry>>| {
+        enabled_chord_item(&tree, &ChordEntry(Rc::clone(entry))).and_then(|item| item.id())
+    };
+    assert_eq!(find(&top_level), Some(MenuId::from(1)));
+    assert_eq!(find(&nested), None);
+
+    submenu.set_enabled(true).unwrap();
+    assert_eq!(find(&nested), Some(MenuId::from(2)));
+
+    nested.borrow_mut().enabled = false;
+    assert_eq!(find(&nested), None);
+}
+
+#[test]
+fn test_add_to_removed_submenu() {
+    use crate::accelerator::{Code, Modifiers};
+
+    let mut submenu = test_submenu("File", true, MenuTree::default());
+    (submenu.0).0.borrow_mut().removed = true;
+    assert_eq!(submenu.clear(), Err(MenuError::ItemRemoved));
+
+    let mut item = submenu.add_chord_item("Save", true, "Ctrl+KeyK Ctrl+KeyS".parse().unwrap());
+    assert_eq!(item.set_label("Save As"), Err(MenuError::ItemRemoved));
+    let mut item = submenu.add_item("Open", true, "Ctrl+KeyO".parse().ok());
+    assert_eq!(item.set_enabled(false), Err(MenuError::ItemRemoved));
+    assert!((submenu.0).0.borrow().entries.as_ref().unwrap().is_empty());
+
+    let mut chords = (submenu.0).2.borrow_mut();
+    chords.process(Modifiers::CONTROL, Code::KeyK);
+    assert!(matches!(
+        chords.process(Modifiers::CONTROL, Code::KeyS),
+        ChordState::Unmatched
+    ));
+}
+
+#[test]
+fn test_move_entries() {
+    let item = |id: u64| MenuEntry::new_item("", true, None, Some(MenuId::from(id)));
+    let native = |item| {
+        Rc::new(RefCell::new(MenuEntry {
+            r#type: MenuEntryType::NativeMenuItem(item),
+            ..Default::default()
+        }))
+    };
+    let mut entries = vec![
+        item(1),
+        native(NativeMenuItem::Hide),
+        item(2),
+        native(NativeMenuItem::Separator),
+    ];
+    let ids = |entries: &[Rc<RefCell<MenuEntry>>]| {
+        entries
+            .iter()
+            .map(|entry| entry.borrow().id.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    // `Hide` has no widget on Linux
+    assert_eq!(gtk_position(&entries, 2), 1);
+    assert_eq!(gtk_position(&entries, 4), 3);
+
+    let second = Rc::clone(&entries[2]);
+    assert_eq!(child_position(&entries, &second), Ok(2));
+    move_entry(&mut entries, &[], 2, 0);
+    assert_eq!(ids(&entries), ["2", "1", "", ""]);
+    assert_eq!(child_position(&entries, &second), Ok(0));
+
+    let other = item(3);
+    assert_eq!(child_position(&entries, &other), Err(MenuError::NotAChild));
+    other.borrow_mut().removed = true;
+    assert_eq!(
+        child_position(&entries, &other),
+        Err(MenuError::ItemRemoved)
+    );
+}
+
+#[test]
+fn test_removed_entries() {
+    let file = MenuEntry::new_submenu("&File", true);
+    let open = MenuEntry::new_item("Open", true, None, None);
+    let save = MenuEntry::new_item("Save", true, None, None);
+    file.borrow_mut().entries = Some(vec![Rc::clone(&open), Rc::clone(&save)]);
+    let first = || Rc::clone(&file.borrow().entries.as_ref().unwrap()[0]);
+
+    file.borrow_mut().move_child(&save, 0).unwrap();
+    assert!(Rc::ptr_eq(&first(), &save));
+
+    file.borrow_mut().removed = true;
+    let moved = file.borrow_mut().move_child(&open, 0);
+    assert_eq!(moved, Err(MenuError::ItemRemoved));
+    assert!(Rc::ptr_eq(&first(), &save));
+
+    let mut item = MenuItem(save);
+    item.0.borrow_mut().removed = true;
+    assert_eq!(item.set_label("Save As"), Err(MenuError::ItemRemoved));
+    assert_eq!(item.set_enabled(false), Err(MenuError::ItemRemoved));
+    assert!(item.label() == "Save" && item.enabled());
+}
+
+#[test]
+fn test_menu_tree() {
+    use crate::MenuItemKind;
+
+    let item = |label, id: &str| {
+        let entry = MenuEntry::new_item(label, true, None, Some(MenuId::from(id)));
+        MenuItemKind::MenuItem(crate::MenuItem(MenuItem(entry)))
+    };
+    let label = |item: Option<MenuItemKind>| item.and_then(|i| i.as_menu_item().map(|i| i.label()));
+    let labels = |tree: &MenuTree| {
+        tree.items()
+            .into_iter()
+            .map(|i| label(Some(i)).unwrap_or_default())
+            .collect::<Vec<_>>()
+    };
+
+    let tree = MenuTree::default();
+    tree.push(item("&Open", "file.open"));
+    tree.push(MenuItemKind::NativeMenuItem(NativeMenuItem::Copy));
+    tree.insert(0, item("&Save", "file.save"));
+    tree.push(item("Save &Copy", "file.save"));
+    assert_eq!(labels(&tree), ["&Save", "&Open", "", "Save &Copy"]);
+
+    assert_eq!(
+        label(tree.find_by_path("Save Copy")),
+        Some("Save &Copy".into())
+    );
+    assert!(matches!(
+        tree.find_by_path("Copy"),
+        Some(MenuItemKind::NativeMenuItem(NativeMenuItem::Copy))
+    ));
+    assert!(tree.find_by_path("Sav").is_none());
+    assert!(tree.find_by_path("Save/").is_none());
+    let save = MenuId::from("file.save");
+    assert_eq!(label(tree.find_by_id(&save)), Some("&Save".into()));
+
+    tree.move_item(0, 2);
+    assert_eq!(labels(&tree), ["&Open", "", "&Save", "Save &Copy"]);
+    tree.remove(2);
+    assert_eq!(label(tree.find_by_id(&save)), Some("Save &Copy".into()));
+
+    let save_all = "Ctrl+Shift+S".parse::<Chord>().unwrap();
+    let entry = MenuEntry::new_item("Save &All", true, Some(save_all.first().clone()), None);
+    tree.push(MenuItemKind::MenuItem(crate::MenuItem(MenuItem(entry))));
+    let (mut bindings, mut native_items) = (Vec::new(), Vec::new());
+    tree.collect_bindings("File", &mut bindings, &mut native_items);
+    assert!(matches!(
+        bindings.as_slice(),
+        [binding] if binding.path == "File/Save All" && binding.chord == save_all && binding.registered
+    ));
+    assert_eq!(native_items, [("File/Copy".into(), NativeMenuItem::Copy)]);
+
+    let submenu = tree.submenu();
+    submenu.push(item("&Export", "file.export"));
+    assert!(tree.find_by_id(&MenuId::from("file.export")).is_some());
+    tree.clear();
+    assert!(tree.items().is_empty() && tree.find_by_id(&save).is_none());
+}
+
+#[test]
+fn test_check_accelerator() {
+    use crate::accelerator::{Code, Modifiers};
+
+    let supported = Accelerator::new(Some(Modifiers::CONTROL), Code::KeyS);
+    let item = crate::MenuItem(MenuItem(MenuEntry::new_item(
+        "&Save",
+        true,
+        Some(supported),
+        None,
+    )));
+    assert_eq!(item.check_accelerator(), Ok(()));
+
+    // `Fn` is handled by the keyboard and has no keyval
+    let unsupported = Accelerator::new(None, Code::Fn);
+    let item = crate::CheckMenuItem(CheckMenuItem(MenuEntry::new_check_item(
+        "Mute",
+        true,
+        false,
+        Some(unsupported.clone()),
+        None,
+    )));
+    let error = item.check_accelerator().unwrap_err();
+    assert_eq!(error.accelerator(), &unsupported);
+    assert_eq!(item.accelerator(), Some(unsupported));
+
+    let item = crate::MenuItem(MenuItem(MenuEntry::new_item("Open", true, None, None)));
+    assert_eq!(item.check_accelerator(), Ok(()));
+
+    // once registered, the outcome of the registration is reported
+    let supported = Accelerator::new(Some(Modifiers::CONTROL), Code::KeyO);
+    let entry = MenuEntry::new_item("Open", true, Some(supported.clone()), None);
+    let failed = Err(UnsupportedAcceleratorError::new(supported));
+    entry.borrow_mut().accelerator_registration = Some(failed.clone());
+    assert_eq!(crate::MenuItem(MenuItem(entry)).check_accelerator(), failed);
+}
+
+#[test]
+fn test_generated_ids() {
+    let generated = MenuEntry::new_item("", true, None, None);
+    let id = generated.borrow().id.clone();
+    let counter = id.as_str().strip_prefix("__muda:").unwrap();
+    let stable = MenuId::from(counter.parse::<u64>().unwrap());
+    assert_ne!(stable, id);
+    assert_ne!(MenuId::new(id.as_str()), id);
+}
+
+#[test]
+fn test_radio_group() {
+    let group = RadioGroup::default();
+    let item = |checked| {
+        let entry = MenuEntry::new_radio_item("", true, &group, None, None);
+        group.add(&entry, checked);
+        RadioMenuItem(entry)
+    };
+    let name = item(false);
+    let date = item(false);
+    assert!(name.checked());
+    assert!(!date.checked());
+
+    let mut size = item(true);
+    assert!(size.checked());
+    assert!(!name.checked());
+
+    assert!(check_radio_entry(&date.0));
+    assert!(!check_radio_entry(&date.0));
+    assert!(date.checked() && !size.checked());
+    size.check().unwrap();
+    assert!(Rc::ptr_eq(&group.checked().unwrap().0, &size.0));
+
+    group.remove(&size.0);
+    assert!(group.checked().is_none());
+    assert_eq!(group.0.borrow().entries.len(), 2);
+    drop(name);
+    let mut last = item(false);
+    assert!(last.checked());
+    assert_eq!(group.0.borrow().entries.len(), 2);
+
+    last.set_tooltip(Some("Sort by date")).unwrap();
+    last.set_help_text(Some("Newest first")).unwrap();
+    assert_eq!(last.tooltip().as_deref(), Some("Sort by date"));
+    assert_eq!(last.help_text().as_deref(), Some("Newest first"));
+    last.0.borrow_mut().removed = true;
+    assert_eq!(last.set_help_text(None), Err(MenuError::ItemRemoved));
+    assert_eq!(last.set_icon(None), Err(MenuError::ItemRemoved));
+}
+
+#[test]
+fn test_highlight_event() {
+    let mut item = MenuItem(MenuEntry::new_item("Save", true, None, Some("save".into())));
+    item.set_tooltip(Some("No document is open")).unwrap();
+    item.set_help_text(Some("Saves the document")).unwrap();
+    assert_eq!(item.tooltip().as_deref(), Some("No document is open"));
+
+    send_highlight_event(&Rc::downgrade(&item.0), true);
+    let event = crate::menu_highlight_event_receiver().try_recv().unwrap();
+    assert!(event.highlighted && event.id == "save");
+    assert_eq!(event.help_text.as_deref(), Some("Saves the document"));
+
+    item.set_help_text(None).unwrap();
+    let _borrowed = item.0.borrow_mut();
+    send_highlight_event(&Rc::downgrade(&item.0), false);
+    assert!(crate::menu_highlight_event_receiver().try_recv().is_err());
+    drop(_borrowed);
+    send_highlight_event(&Rc::downgrade(&item.0), false);
+    let event = crate::menu_highlight_event_receiver().try_recv().unwrap();
+    assert!(!event.highlighted && event.help_text.is_none());
+}
+
+#[test]
+fn test_context_menu_closed_event() {
+    use context_menu::ClosedEventEmitter;
+    use std::cell::Cell;
+
+    let receiver = crate::context_menu_closed_event_receiver();
+    let disconnected = Rc::new(Cell::new(0));
+    let emitter = ClosedEventEmitter::default();
+    for _ in 0..2 {
+        let disconnected = Rc::clone(&disconnected);
+        emitter.on_done(move || disconnected.set(disconnected.get() + 1));
+    }
+
+    // gtk deactivates the menu before activating the chosen item
+    emitter.item_activated();
+    assert!(receiver.try_recv().is_err());
+    emitter.selection_done();
+    assert!(receiver.try_recv().unwrap().item_activated);
+    assert_eq!(disconnected.get(), 2);
+
+    // the handlers are disconnected after `selection-done`, later calls emit nothing
+    emitter.item_activated();
+    emitter.selection_done();
+    assert!(receiver.try_recv().is_err());
+    assert_eq!(disconnected.get(), 2);
+
+    // the menu was dismissed
+    let emitter = ClosedEventEmitter::default();
+    emitter.selection_done();
+    assert!(!receiver.try_recv().unwrap().item_activated);
+}
+
+#[test]
+fn test_context_menu_attachments() {
+    use context_menu::Attachment;
+    use std::cell::Cell;
+
+    struct TestAttachment {
+        widget_alive: Rc<Cell<bool>>,
+        detached: Rc<Cell<usize>>,
+    }
+
+    impl Attachment for TestAttachment {
+        fn is_alive(&self) -> bool {
+            self.widget_alive.get()
+        }
+
+        fn detach(self) {
+            self.detached.set(self.detached.get() + 1);
+        }
+    }
+
+    let detached = Rc::new(Cell::new(0));
+    let attach = |widget_alive: &Rc<Cell<bool>>| {
+        let widget_alive = Rc::clone(widget_alive);
+        let detached = Rc::clone(&detached);
+        move || TestAttachment {
+            widget_alive,
+            detached,
+        }
+    };
+    let window = Rc::new(Cell::new(true));
+    let mut attachments = ContextMenuAttachments::default();
+
+    // attaching to the same widget twice keeps the first handlers
+    assert!(attachments.attach(1, attach(&window)));
+    assert!(!attachments.attach(1, attach(&window)));
+    assert!(attachments.detach(1));
+    assert_eq!(detached.get(), 1);
+    assert!(!attachments.detach(1));
+    assert_eq!(detached.get(), 1);
+
+    // the handlers of a destroyed widget are gone with it,
+    // a new widget at the same address gets its own
+    assert!(attachments.attach(1, attach(&window)));
+    window.set(false);
+    let new_window = Rc::new(Cell::new(true));
+    assert!(attachments.attach(1, attach(&new_window)));
+    assert_eq!(detached.get(), 1);
+
+    // removing the submenu detaches it from every widget
+    assert!(attachments.attach(2, attach(&new_window)));
+    attachments.detach_all();
+    assert_eq!(detached.get(), 3);
+    assert!(!attachments.detach(1) && !attachments.detach(2));
+}
@@ -0,0 +1,1086 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, HashMap},
+    rc::{Rc, Weak},
+};
+
+use gtk::{
+    gio,
+    glib::{self, ToVariant, Variant},
+};
+
+use super::{
+    accelerator::to_gtk_menemenoic,
+    activate_entry,
+    icon::{scale_to_icon_size, to_pixbuf},
+    CheckMenuItem, MenuEntry, MenuEntryType, RadioMenuItem, Submenu, COUNTER,
+};
+use crate::{
+    icon::{Icon, IconSource},
+    tray::TrayIconError,
+    MenuId, NativeMenuItem,
+};
+
+const ITEM_PATH: &str = "/StatusNotifierItem";
+const ITEM_INTERFACE: &str = "org.kde.StatusNotifierItem";
+const MENU_PATH: &str = "/MenuBar";
+const MENU_INTERFACE: &str = "com.canonical.dbusmenu";
+const WATCHER_NAME: &str = "org.kde.StatusNotifierWatcher";
+const WATCHER_PATH: &str = "/StatusNotifierWatcher";
+
+const ITEM_XML: &str = r#"<node>
+  <interface name="org.kde.StatusNotifierItem">
+    <property name="Category" type="s" access="read"/>
+    <property name="Id" type="s" access="read"/>
+    <property name="Title" type="s" access="read"/>
+    <property name="Status" type="s" access="read"/>
+    <property name="WindowId" type="i" access="read"/>
+    <property name="IconName" type="s" access="read"/>
+    <property name="IconPixmap" type="a(iiay)" access="read"/>
+    <property name="ToolTip" type="(sa(iiay)ss)" access="read"/>
+    <property name="ItemIsMenu" type="b" access="read"/>
+    <property name="Menu" type="o" access="read"/>
+    <method name="ContextMenu">
+      <arg name="x" type="i" direction="in"/>
+      <arg name="y" type="i" direction="in"/>
+    </method>
+    <method name="Activate">
+      <arg name="x" type="i" direction="in"/>
+      <arg name="y" type="i" direction="in"/>
+    </method>
+    <method name="SecondaryActivate">
+      <arg name="x" type="i" direction="in"/>
+      <arg name="y" type="i" direction="in"/>
+    </method>
+    <method name="Scroll">
+      <arg name="delta" type="i" direction="in"/>
+      <arg name="orientation" type="s" direction="in"/>
+    </method>
+    <signal name="NewTitle"/>
+    <signal name="NewIcon"/>
+    <signal name="NewToolTip"/>
+    <signal name="NewStatus">
+      <arg name="status" type="s"/>
+    </signal>
+  </interface>
+</node>"#;
+
+const MENU_XML: &str = r#"<node>
+  <interface name="com.canonical.dbusmenu">
+    <property name="Version" type="u" access="read"/>
+    <property name="TextDirection" type="s" access="read"/>
+    <property name="Status" type="s" access="read"/>
+    <property name="IconThemePath" type="as" access="read"/>
+    <method name="GetLayout">
+      <arg name="parentId" type="i" direction="in"/>
+      <arg name="recursionDepth" type="i" direction="in"/>
+      <arg name="propertyNames" type="as" direction="in"/>
+      <arg name="revision" type="u" direction="out"/>
+      <arg name="layout" type="(ia{sv}av)" direction="out"/>
+    </method>
+    <method name="GetGroupProperties">
+      <arg name="ids" type="ai" direction="in"/>
+      <arg name="propertyNames" type="as" direction="in"/>
+      <arg name="properties" type="a(ia{sv})" direction="out"/>
+    </method>
+    <method name="GetProperty">
+      <arg name="id" type="i" direction="in"/>
+      <arg name="name" type="s" direction="in"/>
+      <arg name="value" type="v" direction="out"/>
+    </method>
+    <method name="Event">
+      <arg name="id" type="i" direction="in"/>
+      <arg name="eventId" type="s" direction="in"/>
+      <arg name="data" type="v" direction="in"/>
+      <arg name="timestamp" type="u" direction="in"/>
+    </method>
+    <method name="EventGroup">
+      <arg name="events" type="a(isvu)" direction="in"/>
+      <arg name="idErrors" type="ai" direction="out"/>
+    </method>
+    <method name="AboutToShow">
+      <arg name="id" type="i" direction="in"/>
+      <arg name="needUpdate" type="b" direction="out"/>
+    </method>
+    <method name="AboutToShowGroup">
+      <arg name="ids" type="ai" direction="in"/>
+      <arg name="updatesNeeded" type="ai" direction="out"/>
+      <arg name="idErrors" type="ai" direction="out"/>
+    </method>
+    <signal name="ItemsPropertiesUpdated">
+      <arg name="updatedProps" type="a(ia{sv})"/>
+      <arg name="removedProps" type="a(ias)"/>
+    </signal>
+    <signal name="LayoutUpdated">
+      <arg name="revision" type="u"/>
+      <arg name="parent" type="i"/>
+    </signal>
+    <signal name="ItemActivationRequested">
+      <arg name="id" type="i"/>
+      <arg name="timestamp" type="u"/>
+    </signal>
+  </interface>
+</node>"#;
+
+/// The properties of a dbusmenu item by their name.
+type Properties = BTreeMap<String, Variant>;
+
+thread_local! {
+    // gio requires the D-Bus handlers to be `Send + Sync` even though they are called on
+    // the thread the tray icon was created on, so they only capture its key and look its state up here
+    static TRAYS: RefCell<HashMap<u64, Rc<RefCell<TrayState>>>> = RefCell::new(HashMap::new());
+    static MENU_UPDATE_SCHEDULED: Cell<bool> = const { Cell::new(false) };
+}
+
+fn tray_state(key: u64) -> Option<Rc<RefCell<TrayState>>> {
+    TRAYS.with(|trays| trays.borrow().get(&key).cloned())
+}
+
+/// Schedules an update of the menus of the tray icons, called whenever a menu entry changes.
+///
+/// The update runs once the main loop is idle, so a burst of changes results in a single
+/// `LayoutUpdated` or `ItemsPropertiesUpdated` signal and menus that aren't exported are only compared.
+pub(super) fn schedule_menu_update() {
+    if TRAYS.with(|trays| trays.borrow().is_empty())
+        || MENU_UPDATE_SCHEDULED.with(|s| s.replace(true))
+    {
+        return;
+    }
+    glib::idle_add_local_once(|| {
+        MENU_UPDATE_SCHEDULED.with(|s| s.set(false));
+        let trays: Vec<_> = TRAYS.with(|trays| trays.borrow().values().cloned().collect());
+        for state in trays {
+            state.borrow_mut().update_menu();
+        }
+    });
+}
+
+struct TrayState {
+    id: MenuId,
+    connection: gio::DBusConnection,
+    title: Option<String>,
+    tooltip: Option<String>,
+    icon: Option<Icon>,
+    menu: DbusMenu,
+}
+
+impl TrayState {
+    fn emit_signal(&self, path: &str, interface: &str, signal: &str, parameters: Option<&Variant>) {
+        if let Err(_e) = self
+            .connection
+            .emit_signal(None, path, interface, signal, parameters)
+        {
+            #[cfg(debug_assertions)]
+            eprintln!(
+                "Failed to emit the {} signal of the tray icon: {}",
+                signal, _e
+            );
+        }
+    }
+
+    /// Takes a new snapshot of the menu and tells the host what changed,
+    /// returns whether the layout changed and has to be fetched again.
+    fn update_menu(&mut self) -> bool {
+        match self.menu.update() {
+            MenuUpdate::None => false,
+            MenuUpdate::Layout => {
+                let parameters = (self.menu.revision, 0i32).to_variant();
+                self.emit_signal(
+                    MENU_PATH,
+                    MENU_INTERFACE,
+                    "LayoutUpdated",
+                    Some(&parameters),
+                );
+                true
+            }
+            MenuUpdate::Properties { updated, removed } => {
+                let parameters = (updated, removed).to_variant();
+                self.emit_signal(
+                    MENU_PATH,
+                    MENU_INTERFACE,
+                    "ItemsPropertiesUpdated",
+                    Some(&parameters),
+                );
+                false
+            }
+        }
+    }
+
+    fn property(&self, name: &str) -> Variant {
+        match name {
+            "Category" => "ApplicationStatus".to_variant(),
+            "Id" => glib::prgname()
+                .unwrap_or_else(|| "muda".into())
+                .to_variant(),
+            "Title" => self.title.clone().unwrap_or_default().to_variant(),
+            "Status" => "Active".to_variant(),
+            "WindowId" => 0i32.to_variant(),
+            "IconName" => match self.icon.as_ref().map(|icon| &icon.0) {
+                Some(IconSource::Name(name)) => name.to_variant(),
+                _ => "".to_variant(),
+            },
+            "IconPixmap" => Variant::array_from_iter::<(i32, i32, Vec<u8>), _>(
+                self.icon.as_ref().and_then(to_argb_pixmap),
+            ),
+            "ToolTip" => (
+                "",
+                Vec::<(i32, i32, Vec<u8>)>::new(),
+                self.tooltip.clone().unwrap_or_default(),
+                "",
+            )
+                .to_variant(),
+            "ItemIsMenu" => false.to_variant(),
+            "Menu" => Variant::parse(
+                Some(glib::VariantTy::OBJECT_PATH),
+                &format!("'{}'", MENU_PATH),
+            )
+            .unwrap(),
+            _ => unreachable!("gio only asks for the properties of the interface"),
+        }
+    }
+}
+
+/// The menu of a tray icon, exported with the `com.canonical.dbusmenu` protocol.
+#[derive(Default)]
+struct DbusMenu {
+    root: Option<Rc<RefCell<MenuEntry>>>,
+    // dbusmenu numbers of the entries by their address, `0` being the root, an entry keeps its number
+    // while it is alive so hosts can match the items of a new layout with the ones they display
+    ids: HashMap<usize, (Weak<RefCell<MenuEntry>>, i32)>,
+    last_id: i32,
+    revision: u32,
+    // the children and the properties of every item, by id, when the menu was last updated
+    children: BTreeMap<i32, Vec<i32>>,
+    properties: BTreeMap<i32, Properties>,
+}
+
+/// What changed in a [`DbusMenu`] since its last update.
+enum MenuUpdate {
+    None,
+    /// Items were added, removed or moved.
+    Layout,
+    /// Only the properties of some items changed, `updated` has all the properties
+    /// of these items and `removed` the names of the properties they no longer have.
+    Properties {
+        updated: Vec<(i32, Properties)>,
+        removed: Vec<(i32, Vec<String>)>,
+    },
+}
+
+impl DbusMenu {
+    fn item_id(&mut self, entry: &Rc<RefCell<MenuEntry>>) -> i32 {
+        if matches!(&self.root, Some(root) if Rc::ptr_eq(root, entry)) {
+            return 0;
+        }
+        let last_id = &mut self.last_id;
+        self.ids
+            .entry(Rc::as_ptr(entry) as usize)
+            .or_insert_with(|| {
+                *last_id += 1;
+                (Rc::downgrade(entry), *last_id)
+            })
+            .1
+    }
+
+    fn entry(&self, id: i32) -> Option<Rc<RefCell<MenuEntry>>> {
+        if id == 0 {
+            return self.root.clone();
+        }
+        self.ids
+            .values()
+            .find(|(_, item_id)| *item_id == id)
+            .and_then(|(entry, _)| entry.upgrade())
+    }
+
+    /// Returns the layout of the item `id` and its children up to `depth` levels deep,
+    /// or all of them if `depth` is `-1`, with the properties in `names` or all of them if it's empty.
+    fn layout(&mut self, id: i32, depth: i32, names: &[String]) -> Option<Variant> {
+        match self.entry(id) {
+            Some(entry) => Some(self.entry_layout(&entry, depth, names)),
+            // a tray icon without a menu has an empty one
+            None if id == 0 => Some((0i32, root_properties(), Vec::<Variant>::new()).to_variant()),
+            None => None,
+        }
+    }
+
+    fn entry_layout(
+        &mut self,
+        entry: &Rc<RefCell<MenuEntry>>,
+        depth: i32,
+        names: &[String],
+    ) -> Variant {
+        let id = self.item_id(entry);
+        let mut properties = self.properties(id, entry);
+        if !names.is_empty() {
+            properties.retain(|name, _| names.contains(name));
+        }
+        let children: Vec<Variant> = if depth == 0 {
+            Vec::new()
+        } else {
+            exported_children(entry)
+                .iter()
+                .map(|child| self.entry_layout(child, depth - 1, names))
+                .collect()
+        };
+        (id, properties, children).to_variant()
+    }
+
+    fn properties(&self, id: i32, entry: &Rc<RefCell<MenuEntry>>) -> Properties {
+        if id == 0 {
+            root_properties()
+        } else {
+            item_properties(entry)
+        }
+    }
+
+    /// Takes a snapshot of the menu and returns what changed since the last one,
+    /// the revision of the layout is bumped if the layout changed.
+    fn update(&mut self) -> MenuUpdate {
+        self.ids.retain(|_, (entry, _)| entry.strong_count() > 0);
+        let mut children = BTreeMap::new();
+        let mut properties = BTreeMap::new();
+        if let Some(root) = self.root.clone() {
+            self.snapshot(&root, &mut children, &mut properties);
+        }
+
+        let update = if children != self.children {
+            self.revision += 1;
+            MenuUpdate::Layout
+        } else {
+            let mut updated = Vec::new();
+            let mut removed = Vec::new();
+            for (id, item_properties) in &properties {
+                let previous = &self.properties[id];
+                if previous != item_properties {
+                    updated.push((*id, item_properties.clone()));
+                    let names: Vec<String> = previous
+                        .keys()
+                        .filter(|name| !item_properties.contains_key(*name))
+                        .cloned()
+                        .collect();
+                    if !names.is_empty() {
+                        removed.push((*id, names));
+                    }
+                }
+            }
+            if updated.is_empty() {
+                MenuUpdate::None
+            } else {
+                MenuUpdate::Properties { updated, removed }
+            }
+        };
+        self.children = children;
+        self.properties = properties;
+        update
+    }
+
+    fn snapshot(
+        &mut self,
+        entry: &Rc<RefCell<MenuEntry>>,
+        children: &mut BTreeMap<i32, Vec<i32>>,
+        properties: &mut BTreeMap<i32, Properties>,
+    ) {
+        let id = self.item_id(entry);
+        properties.insert(id, self.properties(id, entry));
+        let entries = exported_children(entry);
+        children.insert(id, entries.iter().map(|e| self.item_id(e)).collect());
+        for entry in &entries {
+            self.snapshot(entry, children, properties);
+        }
+    }
+}
+
+pub struct TrayIcon {
+    key: u64,
+    id: MenuId,
+    connection: gio::DBusConnection,
+    registrations: Vec<gio::RegistrationId>,
+    // gio doesn't export the type of the ids returned by `bus_watch_name_on_connection`
+    unwatch: Option<Box<dyn FnOnce()>>,
+}
+
+impl TrayIcon {
+    pub fn new(id: Option<MenuId>, menu: Option<&Submenu>) -> Result<Self, TrayIconError> {
+        // every tray icon gets its own connection, so its name and objects
+        // are released by closing it, which is also how the watcher learns it is gone
+        let connection =
+            gio::dbus_address_get_for_bus_sync(gio::BusType::Session, None::<&gio::Cancellable>)
+                .and_then(|address| {
+                    gio::DBusConnection::for_address_sync(
+                        &address,
+                        gio::DBusConnectionFlags::AUTHENTICATION_CLIENT
+                            | gio::DBusConnectionFlags::MESSAGE_BUS_CONNECTION,
+                        None,
+                        None::<&gio::Cancellable>,
+                    )
+                })
+                .map_err(|e| TrayIconError::NoSessionBus(e.to_string()))?;
+        Self::with_connection(connection, id, menu.map(|m| Rc::clone(&m.0)))
+    }
+
+    fn with_connection(
+        connection: gio::DBusConnection,
+        id: Option<MenuId>,
+        menu: Option<Rc<RefCell<MenuEntry>>>,
+    ) -> Result<Self, TrayIconError> {
+        let key = COUNTER.next();
+        let id = id.unwrap_or_else(|| MenuId::generated(key));
+        let mut menu = DbusMenu {
+            root: menu,
+            ..Default::default()
+        };
+        menu.update();
+        let state = TrayState {
+            id: id.clone(),
+            connection: connection.clone(),
+            title: None,
+            tooltip: None,
+            icon: None,
+            menu,
+        };
+        TRAYS.with(|trays| trays.borrow_mut().insert(key, Rc::new(RefCell::new(state))));
+
+        let mut tray = Self {
+            key,
+            id,
+            connection,
+            registrations: Vec::new(),
+            unwatch: None,
+        };
+        tray.export()
+            .map_err(|e| TrayIconError::Export(e.to_string()))?;
+        Ok(tray)
+    }
+
+    /// Registers the tray icon and its menu on the bus under a well-known name
+    /// and with the watcher whenever it appears.
+    fn export(&mut self) -> Result<(), glib::Error> {
+        let key = self.key;
+        let item_info = interface_info(ITEM_XML, ITEM_INTERFACE)?;
+        self.registrations.push(self.connection.register_object(
+            ITEM_PATH,
+            &item_info,
+            move |_, _, _, _, method, _, invocation| {
+                if method == "Activate" {
+                    if let Some(state) = tray_state(key) {
+                        let _ = crate::MENU_CHANNEL.0.send(crate::MenuEvent {
+                            id: state.borrow().id.clone(),
+                        });
+                    }
+                }
+                // hosts display the exported menu themselves on `ContextMenu`
+                invocation.return_value(None);
+            },
+            move |_, _, _, _, property| match tray_state(key) {
+                Some(state) => state.borrow().property(property),
+                None => {
+                    unreachable!("the objects are unregistered before the tray icon is removed")
+                }
+            },
+            |_, _, _, _, _, _| false,
+        )?);
+
+        let menu_info = interface_info(MENU_XML, MENU_INTERFACE)?;
+        self.registrations.push(self.connection.register_object(
+            MENU_PATH,
+            &menu_info,
+            move |_, _, _, _, method, parameters, invocation| match tray_state(key) {
+                Some(state) => call_menu_method(&state, method, &parameters, invocation),
+                None => invocation.return_dbus_error(
+                    "org.freedesktop.DBus.Error.UnknownObject",
+                    "The tray icon was removed",
+                ),
+            },
+            |_, _, _, _, property| match property {
+                "Version" => 3u32.to_variant(),
+                "TextDirection" => "ltr".to_variant(),
+                "Status" => "normal".to_variant(),
+                "IconThemePath" => Vec::<String>::new().to_variant(),
+                _ => unreachable!("gio only asks for the properties of the interface"),
+            },
+            |_, _, _, _, _, _| false,
+        )?);
+
+        let name = format!("org.kde.StatusNotifierItem-{}-{}", std::process::id(), key);
+        self.connection.call_sync(
+            Some("org.freedesktop.DBus"),
+            "/org/freedesktop/DBus",
+            "org.freedesktop.DBus",
+            "RequestName",
+            // DBUS_NAME_FLAG_DO_NOT_QUEUE
+            Some(&(&name, 4u32).to_variant()),
+            None,
+            gio::DBusCallFlags::NONE,
+            -1,
+            None::<&gio::Cancellable>,
+        )?;
+
+        let watcher = gio::bus_watch_name_on_connection(
+            &self.connection,
+            WATCHER_NAME,
+            gio::BusNameWatcherFlags::NONE,
+            move |connection, _, _| {
+                connection.call(
+                    Some(WATCHER_NAME),
+                    WATCHER_PATH,
+                    WATCHER_NAME,
+                    "RegisterStatusNotifierItem",
+                    Some(&(&name,).to_variant()),
+                    None,
+                    gio::DBusCallFlags::NONE,
+                    -1,
+                    None::<&gio::Cancellable>,
+                    |result| {
+                        if let Err(_e) = result {
+                            #[cfg(debug_assertions)]
+                            eprintln!("Failed to register the tray icon: {}", _e);
+                        }
+                    },
+                )
+            },
+            |_, _| {},
+        );
+        self.unwatch = Some(Box::new(move || gio::bus_unwatch_name(watcher)));
+        Ok(())
+    }
+
+    fn with_state<R, F: FnOnce(&mut TrayState) -> R>(&self, f: F) -> R {
+        let state = tray_state(self.key).unwrap();
+        let mut state = state.borrow_mut();
+        f(&mut state)
+    }
+
+    pub fn id(&self) -> &MenuId {
+        &self.id
+    }
+
+    pub fn set_menu(&mut self, menu: Option<&Submenu>) {
+        self.with_state(|state| {
+            state.menu.root = menu.map(|m| Rc::clone(&m.0));
+            state.update_menu();
+        })
+    }
+
+    pub fn set_icon(&mut self, icon: Option<Icon>) {
+        self.with_state(|state| {
+            state.icon = icon;
+            state.emit_signal(ITEM_PATH, ITEM_INTERFACE, "NewIcon", None);
+        })
+    }
+
+    pub fn title(&self) -> Option<String> {
+        self.with_state(|state| state.title.clone())
+    }
+
+    pub fn set_title(&mut self, title: Option<&str>) {
+        self.with_state(|state| {
+            state.title = title.map(|t| t.to_string());
+            state.emit_signal(ITEM_PATH, ITEM_INTERFACE, "NewTitle", None);
+        })
+    }
+
+    pub fn tooltip(&self) -> Option<String> {
+        self.with_state(|state| state.tooltip.clone())
+    }
+
+    pub fn set_tooltip(&mut self, tooltip: Option<&str>) {
+        self.with_state(|state| {
+            state.tooltip = tooltip.map(|t| t.to_string());
+            state.emit_signal(ITEM_PATH, ITEM_INTERFACE, "NewToolTip", None);
+        })
+    }
+}
+
+impl Drop for TrayIcon {
+    fn drop(&mut self) {
+        if let Some(unwatch) = self.unwatch.take() {
+            unwatch();
+        }
+        for registration in self.registrations.drain(..) {
+            let _ = self.connection.unregister_object(registration);
+        }
+        TRAYS.with(|trays| trays.borrow_mut().remove(&self.key));
+        let _ = self.connection.close_sync(None::<&gio::Cancellable>);
+    }
+}
+
+fn interface_info(xml: &str, interface: &str) -> Result<gio::DBusInterfaceInfo, glib::Error> {
+    Ok(gio::DBusNodeInfo::for_xml(xml)?
+        .lookup_interface(interface)
+        .unwrap())
+}
+
+fn call_menu_method(
+    state: &Rc<RefCell<TrayState>>,
+    method: &str,
+    parameters: &Variant,
+    invocation: gio::DBusMethodInvocation,
+) {
+    match method {
+        "GetLayout" => {
+            let (parent, depth, names) = parameters
+                .get::<(i32, i32, Vec<String>)>()
+                .unwrap_or_default();
+            let mut state = state.borrow_mut();
+            let revision = state.menu.revision;
+            match state.menu.layout(parent, depth, &names) {
+                Some(layout) => invocation.return_value(Some(&Variant::tuple_from_iter([
+                    revision.to_variant(),
+                    layout,
+                ]))),
+                None => return_unknown_id(invocation, parent),
+            }
+        }
+        "GetGroupProperties" => {
+            let (ids, names) = parameters
+                .get::<(Vec<i32>, Vec<String>)>()
+                .unwrap_or_default();
+            let state = state.borrow();
+            let menu = &state.menu;
+            let items = ids.into_iter().filter_map(|id| {
+                let mut properties = menu.properties(id, &menu.entry(id)?);
+                if !names.is_empty() {
+                    properties.retain(|name, _| names.contains(name));
+                }
+                Some((id, properties).to_variant())
+            });
+            invocation.return_value(Some(&Variant::tuple_from_iter([
+                Variant::array_from_iter::<(i32, Properties), _>(items),
+            ])));
+        }
+        "GetProperty" => {
+            let (id, name) = parameters.get::<(i32, String)>().unwrap_or_default();
+            let state = state.borrow();
+            let menu = &state.menu;
+            let value = menu
+                .entry(id)
+                .and_then(|entry| menu.properties(id, &entry).remove(&name));
+            match value {
+                Some(value) => {
+                    invocation.return_value(Some(&Variant::tuple_from_iter([value.to_variant()])))
+                }
+                None => return_unknown_id(invocation, id),
+            }
+        }
+        "Event" => {
+            let id = parameters.child_value(0).get::<i32>().unwrap_or_default();
+            let event = parameters
+                .child_value(1)
+                .get::<String>()
+                .unwrap_or_default();
+            if handle_menu_event(state, id, &event) {
+                invocation.return_value(None);
+            } else {
+                return_unknown_id(invocation, id);
+            }
+        }
+        "EventGroup" => {
+            let events = parameters.child_value(0);
+            let id_errors: Vec<i32> = (0..events.n_children())
+                .map(|i| events.child_value(i))
+                .filter_map(|event| {
+                    let id = event.child_value(0).get::<i32>().unwrap_or_default();
+                    let event = event.child_value(1).get::<String>().unwrap_or_default();
+                    (!handle_menu_event(state, id, &event)).then_some(id)
+                })
+                .collect();
+            invocation.return_value(Some(&(id_errors,).to_variant()));
+        }
+        "AboutToShow" => {
+            let updated = state.borrow_mut().update_menu();
+            invocation.return_value(Some(&(updated,).to_variant()));
+        }
+        "AboutToShowGroup" => {
+            let ids = parameters.get::<(Vec<i32>,)>().unwrap_or_default().0;
+            let mut state = state.borrow_mut();
+            let updated = state.update_menu();
+            let (known, unknown): (Vec<i32>, Vec<i32>) = ids
+                .into_iter()
+                .partition(|id| state.menu.entry(*id).is_some());
+            let updates_needed = if updated { known } else { Vec::new() };
+            invocation.return_value(Some(&(updates_needed, unknown).to_variant()));
+        }
+        _ => unreachable!("gio only dispatches the methods of the interface"),
+    }
+}
+
+fn return_unknown_id(invocation: gio::DBusMethodInvocation, id: i32) {
+    invocation.return_dbus_error(
+        "com.canonical.dbusmenu.Error.UnknownId",
+        &format!("There is no menu item with the id {}", id),
+    );
+}
+
+/// Handles an event the host sends for the item `id`, returns `false` if there is no such item.
+fn handle_menu_event(state: &Rc<RefCell<TrayState>>, id: i32, event: &str) -> bool {
+    let entry = match state.borrow().menu.entry(id) {
+        Some(entry) => entry,
+        None => return false,
+    };
+    // hosts also send "opened", "closed" and "hovered" events
+    if event == "clicked" && is_activatable(&entry.borrow()) {
+        // the state isn't borrowed while the item is activated
+        activate_entry(&entry);
+        state.borrow_mut().update_menu();
+    }
+    true
+}
+
+fn is_activatable(entry: &MenuEntry) -> bool {
+    entry.enabled
+        && matches!(
+            entry.r#type,
+            MenuEntryType::MenuItem(_)
+                | MenuEntryType::CheckMenuItem(_)
+                | MenuEntryType::RadioMenuItem(_)
+        )
+}
+
+/// Returns the children of a submenu that are exported, native menu items
+/// other than separators act on a window of the application so they are left out.
+fn exported_children(entry: &Rc<RefCell<MenuEntry>>) -> Vec<Rc<RefCell<MenuEntry>>> {
+    entry
+        .borrow()
+        .entries
+        .iter()
+        .flatten()
+        .filter(|e| match &e.borrow().r#type {
+            MenuEntryType::NativeMenuItem(item) => matches!(item, NativeMenuItem::Separator),
+            _ => true,
+        })
+        .cloned()
+        .collect()
+}
+
+fn root_properties() -> Properties {
+    let mut properties = Properties::new();
+    properties.insert("children-display".into(), "submenu".to_variant());
+    properties
+}
+
+fn item_properties(entry: &Rc<RefCell<MenuEntry>>) -> Properties {
+    let mut properties = Properties::new();
+    let toggle = match entry.borrow().r#type {
+        MenuEntryType::CheckMenuItem(_) => {
+            Some(("checkmark", CheckMenuItem(Rc::clone(entry)).checked()))
+        }
+        MenuEntryType::RadioMenuItem(_) => {
+            Some(("radio", RadioMenuItem(Rc::clone(entry)).checked()))
+        }
+        _ => None,
+    };
+    let entry = entry.borrow();
+
+    if let MenuEntryType::NativeMenuItem(_) = entry.r#type {
+        properties.insert("type".into(), "separator".to_variant());
+        return properties;
+    }
+
+    properties.insert("label".into(), to_gtk_menemenoic(&entry.label).to_variant());
+    if !entry.enabled {
+        properties.insert("enabled".into(), false.to_variant());
+    }
+    match entry.icon.as_ref().map(|icon| &icon.0) {
+        Some(IconSource::Name(name)) => {
+            properties.insert("icon-name".into(), name.to_variant());
+        }
+        Some(_) => {
+            let png = to_pixbuf(entry.icon.as_ref().unwrap())
+                .and_then(scale_to_icon_size)
+                .and_then(|pixbuf| pixbuf.save_to_bufferv("png", &[]).ok());
+            if let Some(png) = png {
+                properties.insert("icon-data".into(), Variant::array_from_fixed_array(&png));
+            }
+        }
+        None => {}
+    }
+    if let MenuEntryType::Submenu(_) = entry.r#type {
+        properties.insert("children-display".into(), "submenu".to_variant());
+    }
+    if let Some((toggle_type, checked)) = toggle {
+        properties.insert("toggle-type".into(), toggle_type.to_variant());
+        properties.insert("toggle-state".into(), (checked as i32).to_variant());
+    }
+    properties
+}
+
+/// Converts `icon` to the pixmap format of StatusNotifierItem, 32bpp ARGB pixels in network byte order.
+fn to_argb_pixmap(icon: &Icon) -> Option<Variant> {
+    let pixbuf = to_pixbuf(icon)?;
+    let pixbuf = if pixbuf.has_alpha() {
+        pixbuf
+    } else {
+        pixbuf.add_alpha(false, 0, 0, 0)?
+    };
+    let (width, height) = (pixbuf.width(), pixbuf.height());
+    let rowstride = pixbuf.rowstride() as usize;
+    let pixels = pixbuf.read_pixel_bytes()?;
+
+    let mut argb = Vec::with_capacity(width as usize * height as usize * 4);
+    for row in 0..height as usize {
+        let row = &pixels[row * rowstride..][..width as usize * 4];
+        for pixel in row.chunks_exact(4) {
+            argb.extend_from_slice(&[pixel[3], pixel[0], pixel[1], pixel[2]]);
+        }
+    }
+    Some(Variant::tuple_from_iter([
+        width.to_variant(),
+        height.to_variant(),
+        Variant::array_from_fixed_array(&argb),
+    ]))
+}
+
+#[test]
+fn test_tray_icon() {
+    use std::{
+        collections::HashMap,
+        io::{BufRead, BufReader},
+        process::{Command, Stdio},
+        sync::mpsc,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    // runs against a private session bus, skipped if `dbus-daemon` isn't installed
+    let mut daemon = match Command::new("dbus-daemon")
+        .args(["--session", "--nofork", "--print-address"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(daemon) => daemon,
+        Err(_) => return,
+    };
+    let mut address = String::new();
+    BufReader::new(daemon.stdout.take().unwrap())
+        .read_line(&mut address)
+        .unwrap();
+    let address = address.trim().to_string();
+    let connect = move || {
+        gio::DBusConnection::for_address_sync(
+            &address,
+            gio::DBusConnectionFlags::AUTHENTICATION_CLIENT
+                | gio::DBusConnectionFlags::MESSAGE_BUS_CONNECTION,
+            None,
+            None::<&gio::Cancellable>,
+        )
+        .unwrap()
+    };
+
+    let native_item = |item| {
+        Rc::new(RefCell::new(MenuEntry {
+            r#type: MenuEntryType::NativeMenuItem(item),
+            ..Default::default()
+        }))
+    };
+    let menu = MenuEntry::new_submenu("Tray", true);
+    let open = MenuEntry::new_item("&Open", true, None, Some("open".into()));
+    let muted = MenuEntry::new_check_item("Muted", false, true, None, None);
+    menu.borrow_mut().entries.as_mut().unwrap().extend([
+        Rc::clone(&open),
+        Rc::clone(&muted),
+        native_item(NativeMenuItem::Separator),
+        native_item(NativeMenuItem::Copy),
+    ]);
+
+    let mut tray =
+        TrayIcon::with_connection(connect(), Some("tray".into()), Some(Rc::clone(&menu))).unwrap();
+    tray.set_title(Some("Muda"));
+    tray.set_icon(Some(Icon::from_rgba(vec![255, 0, 0, 128], 1, 1).unwrap()));
+    let name = format!(
+        "org.kde.StatusNotifierItem-{}-{}",
+        std::process::id(),
+        tray.key
+    );
+
+    // the calls are made from another thread while this one runs the handlers of the tray icon,
+    // the client asks for a change of the menu on `changes` and waits for the signal about it
+    let (changes, requested) = mpsc::channel();
+    let client = {
+        let name = name.clone();
+        let connect = connect.clone();
+        thread::spawn(move || {
+            // the signals are dispatched on the context that is the thread default when subscribing
+            let context = glib::MainContext::new();
+            context
+                .with_thread_default(|| {
+                    let connection = connect();
+                    let signals = Rc::new(RefCell::new(Vec::<(String, Variant)>::new()));
+                    let received = Rc::clone(&signals);
+                    connection.signal_subscribe(
+                        None,
+                        Some(MENU_INTERFACE),
+                        None,
+                        Some(MENU_PATH),
+                        None,
+                        gio::DBusSignalFlags::NONE,
+                        move |_, _, _, _, signal, parameters| {
+                            received
+                                .borrow_mut()
+                                .push((signal.to_string(), parameters.clone()));
+                        },
+                    );
+                    let wait_for_signal = || {
+                        let start = Instant::now();
+                        while signals.borrow().is_empty()
+                            && start.elapsed() < Duration::from_secs(5)
+                        {
+                            context.iteration(false);
+                            thread::sleep(Duration::from_millis(1));
+                        }
+                        signals.borrow_mut().drain(..).next()
+                    };
+
+                    let call = |path: &str, interface: &str, method: &str, parameters: Variant| {
+                        connection
+                            .call_sync(
+                                Some(&name),
+                                path,
+                                interface,
+                                method,
+                                Some(&parameters),
+                                None,
+                                gio::DBusCallFlags::NONE,
+                                5000,
+                                None::<&gio::Cancellable>,
+                            )
+                            .unwrap()
+                    };
+                    let property = |name: &str| {
+                        call(
+                            ITEM_PATH,
+                            "org.freedesktop.DBus.Properties",
+                            "Get",
+                            (ITEM_INTERFACE, name).to_variant(),
+                        )
+                        .child_value(0)
+                        .as_variant()
+                        .unwrap()
+                    };
+
+                    let title = property("Title");
+                    let pixmap = property("IconPixmap");
+                    let layout = call(
+                        MENU_PATH,
+                        MENU_INTERFACE,
+                        "GetLayout",
+                        (0i32, -1i32, Vec::<String>::new()).to_variant(),
+                    );
+                    call(
+                        MENU_PATH,
+                        MENU_INTERFACE,
+                        "Event",
+                        (1i32, "clicked", 0i32.to_variant(), 0u32).to_variant(),
+                    );
+                    call(
+                        ITEM_PATH,
+                        ITEM_INTERFACE,
+                        "Activate",
+                        (0i32, 0i32).to_variant(),
+                    );
+                    // the update after the click may not be over
+                    thread::sleep(Duration::from_millis(100));
+                    context.iteration(false);
+                    signals.borrow_mut().clear();
+
+                    changes.send(1).unwrap();
+                    let properties_updated = wait_for_signal();
+                    changes.send(2).unwrap();
+                    let layout_updated = wait_for_signal();
+                    (title, pixmap, layout, properties_updated, layout_updated)
+                })
+                .unwrap()
+        })
+    };
+    let context = glib::MainContext::default();
+    while !client.is_finished() {
+        match requested.try_recv() {
+            Ok(1) => super::MenuItem(Rc::clone(&open))
+                .set_label("&Open Muda")
+                .unwrap(),
+            Ok(_) => {
+                menu.borrow_mut().move_child(&muted, 0).unwrap();
+            }
+            Err(_) => {}
+        }
+        context.iteration(false);
+        thread::sleep(Duration::from_millis(1));
+    }
+    let (title, pixmap, layout, properties_updated, layout_updated) = client.join().unwrap();
+
+    assert_eq!(title.str(), Some("Muda"));
+    assert_eq!(
+        pixmap.get::<Vec<(i32, i32, Vec<u8>)>>(),
+        Some(vec![(1, 1, vec![128, 255, 0, 0])])
+    );
+
+    let items: Vec<(i32, HashMap<String, Variant>)> =
+        (0..layout.child_value(1).child_value(2).n_children())
+            .map(|i| {
+                let item = layout
+                    .child_value(1)
+                    .child_value(2)
+                    .child_value(i)
+                    .as_variant()
+                    .unwrap();
+                (
+                    item.child_value(0).get().unwrap(),
+                    item.child_value(1).get().unwrap(),
+                )
+            })
+            .collect();
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[0].0, 1);
+    assert_eq!(items[0].1["label"].str(), Some("_Open"));
+    assert_eq!(items[1].1["enabled"].get(), Some(false));
+    assert_eq!(items[1].1["toggle-type"].str(), Some("checkmark"));
+    assert_eq!(items[1].1["toggle-state"].get(), Some(1i32));
+    assert_eq!(items[2].1["type"].str(), Some("separator"));
+
+    // changing a label only updates the properties of the item
+    let (signal, parameters) = properties_updated.unwrap();
+    assert_eq!(signal, "ItemsPropertiesUpdated");
+    let (updated, removed) = parameters
+        .get::<(
+            Vec<(i32, HashMap<String, Variant>)>,
+            Vec<(i32, Vec<String>)>,
+        )>()
+        .unwrap();
+    assert_eq!(updated.len(), 1);
+    assert_eq!(updated[0].0, 1);
+    assert_eq!(updated[0].1["label"].str(), Some("_Open Muda"));
+    assert!(removed.is_empty());
+
+    // moving an item changes the layout and its revision
+    let (signal, parameters) = layout_updated.unwrap();
+    assert_eq!(signal, "LayoutUpdated");
+    let (revision, parent) = parameters.get::<(u32, i32)>().unwrap();
+    assert!(revision > layout.child_value(0).get::<u32>().unwrap());
+    assert_eq!(parent, 0);
+
+    let ids: Vec<MenuId> = crate::menu_event_receiver()
+        .try_iter()
+        .map(|e| e.id)
+        .collect();
+    assert!(ids.contains(&"open".into()) && ids.contains(&"tray".into()));
+
+    // dropping the tray icon releases its name
+    drop(tray);
+    let has_owner = connect()
+        .call_sync(
+            Some("org.freedesktop.DBus"),
+            "/org/freedesktop/DBus",
+            "org.freedesktop.DBus",
+            "NameHasOwner",
+            Some(&(&name,).to_variant()),
+            None,
+            gio::DBusCallFlags::NONE,
+            5000,
+            None::<&gio::Cancellable>,
+        )
+        .unwrap();
+    assert_eq!(has_owner.get(), Some((false,)));
+
+    let _ = daemon.kill();
+    let _ = daemon.wait();
+}
@@ -1,7 +1,10 @@
 use cocoa::appkit::NSEventModifierFlags;
-use keyboard_types::{Code, Modifiers};
+use keyboard_types::{Code, Key, Modifiers};
 
-use crate::accelerator::Accelerator;
+use crate::accelerator::{
+    keys::{code_to_char, named_key_to_code},
+    Accelerator, AcceleratorKey,
+};
 
 /// Mnemonic is deprecated since macOS 10
 pub fn remove_mnemonic(string: impl AsRef<str>) -> String {
@@ -14,55 +17,19 @@ impl Accelerator {
     ///
     /// Returns the empty string if no key equivalent is known.
     pub fn key_equivalent(&self) -> String {
-        match self.key {
-            Code::KeyA => "a".into(),
-            Code::KeyB => "b".into(),
-            Code::KeyC => "c".into(),
-            Code::KeyD => "d".into(),
-            Code::KeyE => "e".into(),
-            Code::KeyF => "f".into(),
-            Code::KeyG => "g".into(),
-            Code::KeyH => "h".into(),
-            Code::KeyI => "i".into(),
-            Code::KeyJ => "j".into(),
-            Code::KeyK => "k".into(),
-            Code::KeyL => "l".into(),
-            Code::KeyM => "m".into(),
-            Code::KeyN => "n".into(),
-            Code::KeyO => "o".into(),
-            Code::KeyP => "p".into(),
-            Code::KeyQ => "q".into(),
-            Code::KeyR => "r".into(),
-            Code::KeyS => "s".into(),
-            Code::KeyT => "t".into(),
-            Code::KeyU => "u".into(),
-            Code::KeyV => "v".into(),
-            Code::KeyW => "w".into(),
-            Code::KeyX => "x".into(),
-            Code::KeyY => "y".into(),
-            Code::KeyZ => "z".into(),
-            Code::Digit0 => "0".into(),
-            Code::Digit1 => "1".into(),
-            Code::Digit2 => "2".into(),
-            Code::Digit3 => "3".into(),
-            Code::Digit4 => "4".into(),
-            Code::Digit5 => "5".into(),
-            Code::Digit6 => "6".into(),
-            Code::Digit7 => "7".into(),
-            Code::Digit8 => "8".into(),
-            Code::Digit9 => "9".into(),
-            Code::Comma => ",".into(),
-            Code::Minus => "-".into(),
-            Code::Period => ".".into(),
-            Code::Space => "\u{0020}".into(),
-            Code::Equal => "=".into(),
-            Code::Semicolon => ";".into(),
-            Code::Slash => "/".into(),
-            Code::Backslash => "\\".into(),
-            Code::Quote => "\'".into(),
-            Code::Backquote => "`".into(),
-            Code::BracketLeft => "[".into(),
-            Code::BracketRight => "]".into(),
+        // Key equivalents are matched by character, so physical keys use the character
+        // they produce on a US layout and named logical keys use the key producing them.
+        let code = match &*self.key() {
+            AcceleratorKey::Physical(code) => *code,
+            AcceleratorKey::Logical(Key::Character(c)) => return c.clone(),
+            AcceleratorKey::Logical(key) => named_key_to_code(key).unwrap_or(Code::Unidentified),
+        };
+
+        if let Some(c) = code_to_char(&code) {
+            return c.to_string();
+        }
+
+        match code {
             Code::Tab => "⇥".into(),
             Code::Escape => "\u{001b}".into(),
             // from NSText.h
@@ -122,7 +89,8 @@ impl Accelerator {
         if mods.contains(Modifiers::SUPER) {
             flags.insert(NSEventModifierFlags::NSCommandKeyMask);
         }
-        if mods.contains(Modifiers::ALT) {
+        // macOS has no AltGr key, Option is used to type alternative characters instead
+        if mods.intersects(Modifiers::ALT | Modifiers::ALT_GRAPH) {
             flags.insert(NSEventModifierFlags::NSAlternateKeyMask);
         }
         if mods.contains(Modifiers::CONTROL) {
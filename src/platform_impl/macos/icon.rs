@@ -0,0 +1,70 @@
+use crate::icon::{scaled_size, Icon, IconSource, ICON_SIZE};
+use cocoa::{
+    base::{id, nil, NO, YES},
+    foundation::{NSSize, NSString},
+};
+use objc::{class, msg_send, sel, sel_impl};
+
+/// `NSBitmapFormatAlphaNonpremultiplied`, the alpha of our rgba icons isn't premultiplied.
+const ALPHA_NONPREMULTIPLIED: usize = 1 << 1;
+
+/// Sets the image of `ns_menu_item` to `icon`, or removes it if `icon` is `None`.
+pub fn set_menu_item_icon(ns_menu_item: id, icon: Option<&Icon>) {
+    let image = icon.map(to_ns_image).unwrap_or(nil);
+    unsafe {
+        let () = msg_send![ns_menu_item, setImage: image];
+    }
+}
+
+fn to_ns_image(icon: &Icon) -> id {
+    unsafe {
+        let (image, (width, height)): (id, _) = match &icon.0 {
+            IconSource::Rgba {
+                rgba,
+                width,
+                height,
+            } => {
+                let color_space = NSString::alloc(nil).init_str("NSDeviceRGBColorSpace");
+                let rep: id = msg_send![class!(NSBitmapImageRep), alloc];
+                // the representation allocates its own buffer when no planes are given
+                let rep: id = msg_send![rep,
+                    initWithBitmapDataPlanes: std::ptr::null_mut::<*mut u8>()
+                    pixelsWide: *width as isize
+                    pixelsHigh: *height as isize
+                    bitsPerSample: 8_isize
+                    samplesPerPixel: 4_isize
+                    hasAlpha: YES
+                    isPlanar: NO
+                    colorSpaceName: color_space
+                    bitmapFormat: ALPHA_NONPREMULTIPLIED
+                    bytesPerRow: *width as isize * 4
+                    bitsPerPixel: 32_isize];
+                let data: *mut u8 = msg_send![rep, bitmapData];
+                std::ptr::copy_nonoverlapping(rgba.as_ptr(), data, rgba.len());
+
+                let image: id = msg_send![class!(NSImage), alloc];
+                let image: id = msg_send![image,
+                    initWithSize: NSSize::new(*width as f64, *height as f64)];
+                let () = msg_send![image, addRepresentation: rep];
+                let () = msg_send![rep, release];
+                (image, scaled_size(*width as i32, *height as i32))
+            }
+            IconSource::Buffer(buffer) => {
+                let data: id = msg_send![class!(NSData),
+                    dataWithBytes: buffer.as_ptr()
+                    length: buffer.len()];
+                let image: id = msg_send![class!(NSImage), alloc];
+                let image: id = msg_send![image, initWithData: data];
+                (image, (ICON_SIZE, ICON_SIZE))
+            }
+        };
+        if image == nil {
+            #[cfg(debug_assertions)]
+            eprintln!("Failed to decode the menu icon");
+            return nil;
+        }
+
+        let () = msg_send![image, setSize: NSSize::new(width as f64, height as f64)];
+        msg_send![image, autorelease]
+    }
+}
@@ -1,6 +1,9 @@
-use crate::accelerator::Accelerator;
+use crate::accelerator::{Accelerator, UnsupportedAcceleratorError};
 use crate::counter::Counter;
+use crate::icon::Icon;
 use crate::platform_impl::platform_impl::accelerator::remove_mnemonic;
+use crate::platform_impl::platform_impl::icon::set_menu_item_icon;
+use crate::{MenuError, MenuId};
 use cocoa::{
     appkit::{NSButton, NSEventModifierFlags, NSMenuItem},
     base::{id, nil, BOOL, NO, YES},
@@ -13,14 +16,27 @@ use objc::{
     runtime::{Class, Object, Sel},
     sel, sel_impl,
 };
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::CStr;
 use std::rc::Rc;
 use std::sync::Once;
 
 static COUNTER: Counter = Counter::new();
 
+thread_local! {
+    // accelerators can't be read back from the key equivalent of an item
+    static ACCELERATORS: RefCell<HashMap<u64, Accelerator>> = RefCell::new(HashMap::new());
+    // the ivar of our menu items can only hold a number
+    static MENU_IDS: RefCell<HashMap<u64, MenuId>> = RefCell::new(HashMap::new());
+    static RADIO_GROUPS: RefCell<HashMap<u64, RadioGroup>> = RefCell::new(HashMap::new());
+    static HELP_TEXTS: RefCell<HashMap<u64, String>> = RefCell::new(HashMap::new());
+}
+
 #[derive(Debug, Clone)]
 pub struct MenuItem {
     pub(crate) id: u64,
+    pub(crate) menu_id: MenuId,
     pub(crate) ns_menu_item: id,
     label: Rc<str>,
 }
@@ -31,8 +47,15 @@ impl MenuItem {
         enabled: bool,
         selector: Sel,
         accelerator: Option<Accelerator>,
+        menu_id: Option<MenuId>,
     ) -> Self {
-        let (id, ns_menu_item) = make_menu_item(&remove_mnemonic(&label), selector, accelerator);
+        let (id, ns_menu_item) =
+            make_menu_item(&remove_mnemonic(&label), selector, accelerator.clone());
+        if let Some(accelerator) = &accelerator {
+            ACCELERATORS.with(|a| a.borrow_mut().insert(id, accelerator.clone()));
+        }
+        let menu_id = menu_id.unwrap_or_else(|| MenuId::generated(id));
+        MENU_IDS.with(|ids| ids.borrow_mut().insert(id, menu_id.clone()));
 
         unsafe {
             (&mut *ns_menu_item).set_ivar(MENU_IDENTITY, id);
@@ -44,6 +67,7 @@ impl MenuItem {
         }
         Self {
             id,
+            menu_id,
             ns_menu_item,
             label: Rc::from(label.as_ref()),
         }
@@ -53,12 +77,14 @@ impl MenuItem {
         self.label.to_string()
     }
 
-    pub fn set_label<S: AsRef<str>>(&mut self, label: S) {
+    pub fn set_label<S: AsRef<str>>(&mut self, label: S) -> Result<(), MenuError> {
+        self.check_removed()?;
         unsafe {
             let title = NSString::alloc(nil).init_str(&remove_mnemonic(&label));
             self.ns_menu_item.setTitle_(title);
         }
         self.label = Rc::from(label.as_ref());
+        Ok(())
     }
 
     pub fn enabled(&self) -> bool {
@@ -68,7 +94,8 @@ impl MenuItem {
         }
     }
 
-    pub fn set_enabled(&mut self, enabled: bool) {
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<(), MenuError> {
+        self.check_removed()?;
         unsafe {
             let status = match enabled {
                 true => YES,
@@ -76,16 +103,67 @@ impl MenuItem {
             };
             let () = msg_send![self.ns_menu_item, setEnabled: status];
         }
+        Ok(())
+    }
+
+    pub fn set_icon(&mut self, icon: Option<Icon>) -> Result<(), MenuError> {
+        self.check_removed()?;
+        set_menu_item_icon(self.ns_menu_item, icon.as_ref());
+        Ok(())
+    }
+
+    pub fn tooltip(&self) -> Option<String> {
+        ns_tooltip(self.ns_menu_item)
+    }
+
+    pub fn set_tooltip(&mut self, tooltip: Option<&str>) -> Result<(), MenuError> {
+        self.check_removed()?;
+        set_ns_tooltip(self.ns_menu_item, tooltip);
+        Ok(())
+    }
+
+    pub fn help_text(&self) -> Option<String> {
+        HELP_TEXTS.with(|h| h.borrow().get(&self.id).cloned())
+    }
+
+    pub fn set_help_text(&mut self, help_text: Option<&str>) -> Result<(), MenuError> {
+        self.check_removed()?;
+        HELP_TEXTS.with(|h| match help_text {
+            Some(help_text) => h.borrow_mut().insert(self.id, help_text.to_string()),
+            None => h.borrow_mut().remove(&self.id),
+        });
+        Ok(())
+    }
+
+    pub fn id(&self) -> MenuId {
+        self.menu_id.clone()
+    }
+
+    pub fn accelerator(&self) -> Option<Accelerator> {
+        ACCELERATORS.with(|a| a.borrow().get(&self.id).cloned())
     }
 
-    pub fn id(&self) -> u64 {
-        self.id
+    pub fn check_accelerator(&self) -> Result<(), UnsupportedAcceleratorError> {
+        crate::check_accelerator(self.accelerator())
+    }
+
+    pub fn activate(&self) {
+        send_event_for_id(self.id);
+    }
+
+    fn check_removed(&self) -> Result<(), MenuError> {
+        if is_released(self.id) {
+            Err(MenuError::ItemRemoved)
+        } else {
+            Ok(())
+        }
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct CheckMenuItem {
     pub(crate) id: u64,
+    pub(crate) menu_id: MenuId,
     pub(crate) ns_menu_item: id,
     label: Rc<str>,
 }
@@ -97,8 +175,15 @@ impl CheckMenuItem {
         checked: bool,
         selector: Sel,
         accelerator: Option<Accelerator>,
+        menu_id: Option<MenuId>,
     ) -> Self {
-        let (id, ns_menu_item) = make_menu_item(&remove_mnemonic(&label), selector, accelerator);
+        let (id, ns_menu_item) =
+            make_menu_item(&remove_mnemonic(&label), selector, accelerator.clone());
+        if let Some(accelerator) = &accelerator {
+            ACCELERATORS.with(|a| a.borrow_mut().insert(id, accelerator.clone()));
+        }
+        let menu_id = menu_id.unwrap_or_else(|| MenuId::generated(id));
+        MENU_IDS.with(|ids| ids.borrow_mut().insert(id, menu_id.clone()));
 
         unsafe {
             (&mut *ns_menu_item).set_ivar(MENU_IDENTITY, id);
@@ -114,6 +199,7 @@ impl CheckMenuItem {
         }
         Self {
             id,
+            menu_id,
             ns_menu_item,
             label: Rc::from(label.as_ref()),
         }
@@ -123,12 +209,14 @@ impl CheckMenuItem {
         self.label.to_string()
     }
 
-    pub fn set_label<S: AsRef<str>>(&mut self, label: S) {
+    pub fn set_label<S: AsRef<str>>(&mut self, label: S) -> Result<(), MenuError> {
+        self.check_removed()?;
         unsafe {
             let title = NSString::alloc(nil).init_str(&remove_mnemonic(&label));
             self.ns_menu_item.setTitle_(title);
         }
         self.label = Rc::from(label.as_ref());
+        Ok(())
     }
 
     pub fn enabled(&self) -> bool {
@@ -138,7 +226,8 @@ impl CheckMenuItem {
         }
     }
 
-    pub fn set_enabled(&mut self, enabled: bool) {
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<(), MenuError> {
+        self.check_removed()?;
         unsafe {
             let status = match enabled {
                 true => YES,
@@ -146,6 +235,36 @@ impl CheckMenuItem {
             };
             let () = msg_send![self.ns_menu_item, setEnabled: status];
         }
+        Ok(())
+    }
+
+    pub fn set_icon(&mut self, icon: Option<Icon>) -> Result<(), MenuError> {
+        self.check_removed()?;
+        set_menu_item_icon(self.ns_menu_item, icon.as_ref());
+        Ok(())
+    }
+
+    pub fn tooltip(&self) -> Option<String> {
+        ns_tooltip(self.ns_menu_item)
+    }
+
+    pub fn set_tooltip(&mut self, tooltip: Option<&str>) -> Result<(), MenuError> {
+        self.check_removed()?;
+        set_ns_tooltip(self.ns_menu_item, tooltip);
+        Ok(())
+    }
+
+    pub fn help_text(&self) -> Option<String> {
+        HELP_TEXTS.with(|h| h.borrow().get(&self.id).cloned())
+    }
+
+    pub fn set_help_text(&mut self, help_text: Option<&str>) -> Result<(), MenuError> {
+        self.check_removed()?;
+        HELP_TEXTS.with(|h| match help_text {
+            Some(help_text) => h.borrow_mut().insert(self.id, help_text.to_string()),
+            None => h.borrow_mut().remove(&self.id),
+        });
+        Ok(())
     }
 
     pub fn checked(&self) -> bool {
@@ -155,7 +274,8 @@ impl CheckMenuItem {
         }
     }
 
-    pub fn set_checked(&mut self, checked: bool) {
+    pub fn set_checked(&mut self, checked: bool) -> Result<(), MenuError> {
+        self.check_removed()?;
         unsafe {
             let state = match checked {
                 true => 1_isize,
@@ -163,10 +283,172 @@ impl CheckMenuItem {
             };
             let () = msg_send![self.ns_menu_item, setState: state];
         }
+        Ok(())
+    }
+
+    pub fn id(&self) -> MenuId {
+        self.menu_id.clone()
+    }
+
+    pub fn accelerator(&self) -> Option<Accelerator> {
+        ACCELERATORS.with(|a| a.borrow().get(&self.id).cloned())
+    }
+
+    pub fn check_accelerator(&self) -> Result<(), UnsupportedAcceleratorError> {
+        crate::check_accelerator(self.accelerator())
+    }
+
+    pub fn activate(&self) {
+        send_event_for_id(self.id);
+    }
+
+    fn check_removed(&self) -> Result<(), MenuError> {
+        if is_released(self.id) {
+            Err(MenuError::ItemRemoved)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct InnerRadioGroup {
+    items: Vec<MenuItem>,
+    checked: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RadioGroup(Rc<RefCell<InnerRadioGroup>>);
+
+impl RadioGroup {
+    pub fn checked(&self) -> Option<RadioMenuItem> {
+        let inner = self.0.borrow();
+        let id = inner.checked?;
+        inner
+            .items
+            .iter()
+            .find(|item| item.id == id)
+            .map(|item| RadioMenuItem(item.clone(), self.clone()))
+    }
+
+    fn is_checked(&self, id: u64) -> bool {
+        self.0.borrow().checked == Some(id)
+    }
+
+    /// Checks the item with `id` and unchecks the other items,
+    /// returns whether the checked item changed.
+    fn check(&self, id: u64) -> bool {
+        let mut inner = self.0.borrow_mut();
+        if inner.checked == Some(id) {
+            return false;
+        }
+        inner.checked = Some(id);
+        for item in &inner.items {
+            let state = if item.id == id { 1_isize } else { 0_isize };
+            unsafe {
+                let () = msg_send![item.ns_menu_item, setState: state];
+            }
+        }
+        true
     }
 
-    pub fn id(&self) -> u64 {
-        self.id
+    fn remove(&self, id: u64) {
+        let mut inner = self.0.borrow_mut();
+        inner.items.retain(|item| item.id != id);
+        if inner.checked == Some(id) {
+            inner.checked = None;
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RadioMenuItem(pub(crate) MenuItem, RadioGroup);
+
+impl RadioMenuItem {
+    pub fn new<S: AsRef<str>>(
+        label: S,
+        enabled: bool,
+        checked: bool,
+        group: &RadioGroup,
+        selector: Sel,
+        accelerator: Option<Accelerator>,
+        menu_id: Option<MenuId>,
+    ) -> Self {
+        let item = MenuItem::new(label, enabled, selector, accelerator, menu_id);
+        RADIO_GROUPS.with(|groups| groups.borrow_mut().insert(item.id, group.clone()));
+
+        let no_checked = group.0.borrow().checked.is_none();
+        group.0.borrow_mut().items.push(item.clone());
+        if checked || no_checked {
+            group.check(item.id);
+        }
+        Self(item, group.clone())
+    }
+
+    pub fn label(&self) -> String {
+        self.0.label()
+    }
+
+    pub fn set_label<S: AsRef<str>>(&mut self, label: S) -> Result<(), MenuError> {
+        self.0.set_label(label)
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.0.enabled()
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<(), MenuError> {
+        self.0.set_enabled(enabled)
+    }
+
+    pub fn set_icon(&mut self, icon: Option<Icon>) -> Result<(), MenuError> {
+        self.0.set_icon(icon)
+    }
+
+    pub fn tooltip(&self) -> Option<String> {
+        self.0.tooltip()
+    }
+
+    pub fn set_tooltip(&mut self, tooltip: Option<&str>) -> Result<(), MenuError> {
+        self.0.set_tooltip(tooltip)
+    }
+
+    pub fn help_text(&self) -> Option<String> {
+        self.0.help_text()
+    }
+
+    pub fn set_help_text(&mut self, help_text: Option<&str>) -> Result<(), MenuError> {
+        self.0.set_help_text(help_text)
+    }
+
+    pub fn checked(&self) -> bool {
+        self.1.is_checked(self.0.id)
+    }
+
+    pub fn check(&mut self) -> Result<(), MenuError> {
+        self.0.check_removed()?;
+        self.1.check(self.0.id);
+        Ok(())
+    }
+
+    pub fn group(&self) -> RadioGroup {
+        self.1.clone()
+    }
+
+    pub fn id(&self) -> MenuId {
+        self.0.id()
+    }
+
+    pub fn accelerator(&self) -> Option<Accelerator> {
+        self.0.accelerator()
+    }
+
+    pub fn check_accelerator(&self) -> Result<(), UnsupportedAcceleratorError> {
+        crate::check_accelerator(self.accelerator())
+    }
+
+    pub fn activate(&self) {
+        self.0.activate();
     }
 }
 
@@ -192,6 +474,18 @@ fn make_menu_item_alloc() -> *mut Object {
 
 static MENU_IDENTITY: &str = "MenuItemIdentity";
 
+/// Returns the id of `item` if it is one of our menu items.
+fn menu_item_id(item: id) -> Option<u64> {
+    unsafe {
+        let is_ours: BOOL = msg_send![item, isKindOfClass: make_menu_item_class()];
+        if is_ours == YES {
+            Some(*(&*item).get_ivar(MENU_IDENTITY))
+        } else {
+            None
+        }
+    }
+}
+
 fn make_menu_item_class() -> *const Class {
     static mut APP_CLASS: *const Class = 0 as *const Class;
     static INIT: Once = Once::new();
@@ -267,5 +561,66 @@ extern "C" fn dealloc_custom_menuitem(this: &Object, _: Sel) {
 
 fn send_event(this: &Object) {
     let id: u64 = unsafe { *this.get_ivar(MENU_IDENTITY) };
-    let _ = crate::MENU_CHANNEL.0.send(crate::MenuEvent { id: id as _ });
+    send_event_for_id(id);
+}
+
+/// Forgets the id and accelerator of a removed `item`, or of all the items within it if it has a submenu.
+pub(crate) fn release_menu_item(item: id) {
+    unsafe {
+        let submenu: id = msg_send![item, submenu];
+        if submenu != nil {
+            let count: isize = msg_send![submenu, numberOfItems];
+            for i in 0..count {
+                let child: id = msg_send![submenu, itemAtIndex: i];
+                release_menu_item(child);
+            }
+        }
+    }
+    if let Some(id) = menu_item_id(item) {
+        ACCELERATORS.with(|a| a.borrow_mut().remove(&id));
+        MENU_IDS.with(|ids| ids.borrow_mut().remove(&id));
+        HELP_TEXTS.with(|h| h.borrow_mut().remove(&id));
+        if let Some(group) = RADIO_GROUPS.with(|groups| groups.borrow_mut().remove(&id)) {
+            group.remove(id);
+        }
+    }
+}
+
+fn ns_tooltip(ns_menu_item: id) -> Option<String> {
+    unsafe {
+        let tooltip: id = msg_send![ns_menu_item, toolTip];
+        if tooltip == nil {
+            return None;
+        }
+        let bytes: *const std::os::raw::c_char = msg_send![tooltip, UTF8String];
+        Some(CStr::from_ptr(bytes).to_string_lossy().into_owned())
+    }
+}
+
+fn set_ns_tooltip(ns_menu_item: id, tooltip: Option<&str>) {
+    unsafe {
+        let tooltip = match tooltip {
+            Some(tooltip) => NSString::alloc(nil).init_str(tooltip),
+            None => nil,
+        };
+        let () = msg_send![ns_menu_item, setToolTip: tooltip];
+    }
+}
+
+/// Returns whether the item with `id` was removed from its menu, see [`release_menu_item`].
+pub(crate) fn is_released(id: u64) -> bool {
+    MENU_IDS.with(|ids| !ids.borrow().contains_key(&id))
+}
+
+fn send_event_for_id(id: u64) {
+    // activating the checked radio menu item does nothing, like on Linux
+    let group = RADIO_GROUPS.with(|groups| groups.borrow().get(&id).cloned());
+    if matches!(group, Some(group) if !group.check(id)) {
+        return;
+    }
+
+    let id = MENU_IDS
+        .with(|ids| ids.borrow().get(&id).cloned())
+        .unwrap_or_else(|| MenuId::generated(id));
+    let _ = crate::MENU_CHANNEL.0.send(crate::MenuEvent { id });
 }
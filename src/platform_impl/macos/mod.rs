@@ -1,13 +1,20 @@
 mod accelerator;
+mod icon;
 mod menu_item;
 
 use crate::accelerator::{RawMods, SysMods};
-use crate::NativeMenuItem;
-use crate::{accelerator::Accelerator, platform_impl::platform_impl::menu_item::make_menu_item};
+use crate::{
+    accelerator::Accelerator,
+    platform_impl::platform_impl::menu_item::{is_released, make_menu_item, release_menu_item},
+};
+use crate::{
+    check_insert_position, check_position, icon::Icon, sealed::MenuChild, MenuError, MenuId,
+    NativeMenuItem,
+};
 use cocoa::{
     appkit::{NSApp, NSApplication, NSMenu, NSMenuItem},
-    base::{id, nil, selector, NO},
-    foundation::{NSAutoreleasePool, NSString},
+    base::{id, nil, selector, BOOL, NO, YES},
+    foundation::{NSAutoreleasePool, NSPoint, NSString},
 };
 use keyboard_types::Code;
 use objc::{class, msg_send, sel, sel_impl};
@@ -15,6 +22,12 @@ use objc::{class, msg_send, sel, sel_impl};
 use self::accelerator::remove_mnemonic;
 pub use menu_item::CheckMenuItem;
 pub use menu_item::MenuItem;
+pub use menu_item::{RadioGroup, RadioMenuItem};
+
+/// Returns whether `accelerator` has a key equivalent.
+pub(crate) fn is_accelerator_supported(accelerator: &Accelerator) -> bool {
+    !accelerator.key_equivalent().is_empty()
+}
 
 #[derive(Debug, Clone)]
 pub struct Menu(id);
@@ -29,20 +42,109 @@ impl Menu {
     }
 
     pub fn add_submenu<S: AsRef<str>>(&mut self, label: S, enabled: bool) -> Submenu {
+        let position = self.len();
+        self.insert_submenu_at(label, enabled, position)
+    }
+
+    pub fn insert_submenu<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        position: usize,
+    ) -> Result<Submenu, MenuError> {
+        check_insert_position(position, self.len())?;
+        Ok(self.insert_submenu_at(label, enabled, position))
+    }
+
+    fn insert_submenu_at<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        position: usize,
+    ) -> Submenu {
         let menu = Menu::new();
-        let menu_item = MenuItem::new("", enabled, sel!(fireMenubarAction:), None);
+        let menu_item = MenuItem::new("", enabled, sel!(fireMenubarAction:), None, None);
 
         unsafe {
             menu_item.ns_menu_item.setSubmenu_(menu.0);
-            self.0.addItem_(menu_item.ns_menu_item);
         }
+        self.insert_ns_menu_item(menu_item.ns_menu_item, position);
 
         let mut sub_menu = Submenu { menu, menu_item };
-        sub_menu.set_label(label);
+        let _ = sub_menu.set_label(label);
 
         sub_menu
     }
 
+    pub fn remove(&mut self, child: MenuChild) -> Result<usize, MenuError> {
+        let position = self.child_position(&child)?;
+        self.remove_at(position)?;
+        Ok(position)
+    }
+
+    pub fn remove_at(&mut self, position: usize) -> Result<(), MenuError> {
+        check_position(position, self.len())?;
+        unsafe {
+            let item: id = msg_send![self.0, itemAtIndex: position as isize];
+            release_menu_item(item);
+            let () = msg_send![self.0, removeItemAtIndex: position as isize];
+        }
+        Ok(())
+    }
+
+    pub fn move_item(&mut self, child: MenuChild, position: usize) -> Result<usize, MenuError> {
+        let from = self.child_position(&child)?;
+        check_position(position, self.len())?;
+        unsafe {
+            let item: id = msg_send![self.0, itemAtIndex: from as isize];
+            // the menu releases the item once removed, which may be its last owner
+            let item: id = msg_send![item, retain];
+            let () = msg_send![self.0, removeItemAtIndex: from as isize];
+            let () = msg_send![self.0, insertItem: item atIndex: position as isize];
+            let () = msg_send![item, release];
+        }
+        Ok(from)
+    }
+
+    pub fn clear(&mut self) {
+        for position in (0..self.len()).rev() {
+            let _ = self.remove_at(position);
+        }
+    }
+
+    fn len(&self) -> usize {
+        unsafe {
+            let count: isize = msg_send![self.0, numberOfItems];
+            count as usize
+        }
+    }
+
+    fn insert_ns_menu_item(&mut self, item: id, position: usize) {
+        unsafe {
+            let () = msg_send![self.0, insertItem: item atIndex: position as isize];
+        }
+    }
+
+    /// Returns the position of `child` within this menu.
+    fn child_position(&self, child: &MenuChild) -> Result<usize, MenuError> {
+        let (id, ns_menu_item) = match child {
+            MenuChild::Submenu(submenu) => (submenu.menu_item.id, submenu.menu_item.ns_menu_item),
+            MenuChild::MenuItem(item) => (item.id, item.ns_menu_item),
+            MenuChild::CheckMenuItem(item) => (item.id, item.ns_menu_item),
+            MenuChild::RadioMenuItem(item) => (item.0.id, item.0.ns_menu_item),
+        };
+        if is_released(id) {
+            return Err(MenuError::ItemRemoved);
+        }
+
+        let position: isize = unsafe { msg_send![self.0, indexOfItem: ns_menu_item] };
+        if position < 0 {
+            Err(MenuError::NotAChild)
+        } else {
+            Ok(position as usize)
+        }
+    }
+
     pub fn init_for_nsapp(&self) {
         unsafe {
             NSApp().setMainMenu_(self.0);
@@ -67,25 +169,66 @@ impl Submenu {
         self.menu_item.label()
     }
 
-    pub fn set_label<S: AsRef<str>>(&mut self, label: S) {
+    pub fn set_label<S: AsRef<str>>(&mut self, label: S) -> Result<(), MenuError> {
         let label = remove_mnemonic(label);
-        self.menu_item.set_label(&label);
+        self.menu_item.set_label(&label)?;
         unsafe {
             let menu_title = NSString::alloc(nil).init_str(&label);
             let () = msg_send![self.menu.0, setTitle: menu_title];
         }
+        Ok(())
     }
 
     pub fn enabled(&self) -> bool {
         self.menu_item.enabled()
     }
 
-    pub fn set_enabled(&mut self, _enabled: bool) {
+    pub fn set_enabled(&mut self, _enabled: bool) -> Result<(), MenuError> {
         self.menu_item.set_enabled(_enabled)
     }
 
+    pub fn set_icon(&mut self, icon: Option<Icon>) -> Result<(), MenuError> {
+        self.menu_item.set_icon(icon)
+    }
+
+    pub fn tooltip(&self) -> Option<String> {
+        self.menu_item.tooltip()
+    }
+
+    pub fn set_tooltip(&mut self, tooltip: Option<&str>) -> Result<(), MenuError> {
+        self.menu_item.set_tooltip(tooltip)
+    }
+
+    pub fn show_context_menu_for_nsview(&self, view: id, x: f64, y: f64) {
+        unsafe {
+            let location = NSPoint::new(x, y);
+            // returns once the menu is closed, whether an item was chosen or not
+            let item_activated: BOOL = msg_send![self.menu.0,
+                popUpMenuPositioningItem: nil
+                atLocation: location
+                inView: view];
+            let _ = crate::CONTEXT_MENU_CHANNEL
+                .0
+                .send(crate::ContextMenuClosedEvent {
+                    item_activated: item_activated == YES,
+                });
+        }
+    }
+
     pub fn add_submenu<S: AsRef<str>>(&mut self, label: S, enabled: bool) -> Submenu {
-        self.menu.add_submenu(label, enabled)
+        let submenu = self.menu.add_submenu(label, enabled);
+        self.release_added_item();
+        submenu
+    }
+
+    pub fn insert_submenu<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        position: usize,
+    ) -> Result<Submenu, MenuError> {
+        self.check_removed()?;
+        self.menu.insert_submenu(label, enabled, position)
     }
 
     pub fn add_item<S: AsRef<str>>(
@@ -93,101 +236,59 @@ impl Submenu {
         label: S,
         enabled: bool,
         accelerator: Option<Accelerator>,
+        menu_id: Option<MenuId>,
     ) -> MenuItem {
-        let item = MenuItem::new(label, enabled, sel!(fireMenubarAction:), accelerator);
+        let item = MenuItem::new(
+            label,
+            enabled,
+            sel!(fireMenubarAction:),
+            accelerator,
+            menu_id,
+        );
         unsafe {
             self.menu.0.addItem_(item.ns_menu_item);
         }
+        self.release_added_item();
         item
     }
 
-    pub fn add_native_item(&mut self, item: NativeMenuItem) {
-        let (_, native_menu_item) = match item {
-            NativeMenuItem::Separator => unsafe { (0, NSMenuItem::separatorItem(nil)) },
-            NativeMenuItem::About(app_name, _) => {
-                let title = format!("About {}", app_name);
-                make_menu_item(
-                    title.as_str(),
-                    selector("orderFrontStandardAboutPanel:"),
-                    None,
-                )
-            }
-            NativeMenuItem::CloseWindow => make_menu_item(
-                "Close Window",
-                selector("performClose:"),
-                Some(Accelerator::new(SysMods::Cmd, Code::KeyW)),
-            ),
-            NativeMenuItem::Quit => make_menu_item(
-                "Quit",
-                selector("terminate:"),
-                Some(Accelerator::new(SysMods::Cmd, Code::KeyQ)),
-            ),
-            NativeMenuItem::Hide => make_menu_item(
-                "Hide",
-                selector("hide:"),
-                Some(Accelerator::new(SysMods::Cmd, Code::KeyH)),
-            ),
-            NativeMenuItem::HideOthers => make_menu_item(
-                "Hide Others",
-                selector("hideOtherApplications:"),
-                Some(Accelerator::new(RawMods::Alt, Code::KeyH)),
-            ),
-            NativeMenuItem::ShowAll => {
-                make_menu_item("Show All", selector("unhideAllApplications:"), None)
-            }
-            NativeMenuItem::ToggleFullScreen => make_menu_item(
-                "Toggle Full Screen",
-                selector("toggleFullScreen:"),
-                Some(Accelerator::new(RawMods::Ctrl, Code::KeyF)),
-            ),
-            NativeMenuItem::Minimize => make_menu_item(
-                "Minimize",
-                selector("performMiniaturize:"),
-                Some(Accelerator::new(SysMods::Cmd, Code::KeyM)),
-            ),
-            NativeMenuItem::Zoom => make_menu_item("Zoom", selector("performZoom:"), None),
-            NativeMenuItem::Copy => make_menu_item(
-                "Copy",
-                selector("copy:"),
-                Some(Accelerator::new(SysMods::Cmd, Code::KeyC)),
-            ),
-            NativeMenuItem::Cut => make_menu_item(
-                "Cut",
-                selector("cut:"),
-                Some(Accelerator::new(SysMods::Cmd, Code::KeyX)),
-            ),
-            NativeMenuItem::Paste => make_menu_item(
-                "Paste",
-                selector("paste:"),
-                Some(Accelerator::new(SysMods::Cmd, Code::KeyV)),
-            ),
-            NativeMenuItem::Undo => make_menu_item(
-                "Undo",
-                selector("undo:"),
-                Some(Accelerator::new(SysMods::Cmd, Code::KeyZ)),
-            ),
-            NativeMenuItem::Redo => make_menu_item(
-                "Redo",
-                selector("redo:"),
-                Some(Accelerator::new(SysMods::CmdShift, Code::KeyZ)),
-            ),
-            NativeMenuItem::SelectAll => make_menu_item(
-                "Select All",
-                selector("selectAll:"),
-                Some(Accelerator::new(SysMods::Cmd, Code::KeyA)),
-            ),
-            NativeMenuItem::Services => unsafe {
-                let (_, item) = make_menu_item("Services", sel!(fireMenubarAction:), None);
-                let app_class = class!(NSApplication);
-                let app: id = msg_send![app_class, sharedApplication];
-                let services: id = msg_send![app, servicesMenu];
-                let _: () = msg_send![&*item, setSubmenu: services];
-                (0, item)
-            },
-        };
+    pub fn insert_item<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        accelerator: Option<Accelerator>,
+        position: usize,
+        menu_id: Option<MenuId>,
+    ) -> Result<MenuItem, MenuError> {
+        self.check_insert(position)?;
+        let item = MenuItem::new(
+            label,
+            enabled,
+            sel!(fireMenubarAction:),
+            accelerator,
+            menu_id,
+        );
+        self.menu.insert_ns_menu_item(item.ns_menu_item, position);
+        Ok(item)
+    }
+
+    pub fn add_native_item(&mut self, item: NativeMenuItem) -> bool {
         unsafe {
-            self.menu.0.addItem_(native_menu_item);
+            self.menu.0.addItem_(make_native_menu_item(item));
         }
+        self.release_added_item();
+        true
+    }
+
+    pub fn insert_native_item(
+        &mut self,
+        item: NativeMenuItem,
+        position: usize,
+    ) -> Result<bool, MenuError> {
+        self.check_insert(position)?;
+        self.menu
+            .insert_ns_menu_item(make_native_menu_item(item), position);
+        Ok(true)
     }
 
     pub fn add_check_item<S: AsRef<str>>(
@@ -196,6 +297,7 @@ impl Submenu {
         enabled: bool,
         checked: bool,
         accelerator: Option<Accelerator>,
+        menu_id: Option<MenuId>,
     ) -> CheckMenuItem {
         let item = CheckMenuItem::new(
             label,
@@ -203,10 +305,232 @@ impl Submenu {
             checked,
             sel!(fireMenubarAction:),
             accelerator,
+            menu_id,
         );
         unsafe {
             self.menu.0.addItem_(item.ns_menu_item);
         }
+        self.release_added_item();
+        item
+    }
+
+    pub fn insert_check_item<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        checked: bool,
+        accelerator: Option<Accelerator>,
+        position: usize,
+        menu_id: Option<MenuId>,
+    ) -> Result<CheckMenuItem, MenuError> {
+        self.check_insert(position)?;
+        let item = CheckMenuItem::new(
+            label,
+            enabled,
+            checked,
+            sel!(fireMenubarAction:),
+            accelerator,
+            menu_id,
+        );
+        self.menu.insert_ns_menu_item(item.ns_menu_item, position);
+        Ok(item)
+    }
+
+    pub fn add_radio_item<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        checked: bool,
+        group: &RadioGroup,
+        accelerator: Option<Accelerator>,
+        menu_id: Option<MenuId>,
+    ) -> RadioMenuItem {
+        let item = RadioMenuItem::new(
+            label,
+            enabled,
+            checked,
+            group,
+            sel!(fireMenubarAction:),
+            accelerator,
+            menu_id,
+        );
+        unsafe {
+            self.menu.0.addItem_(item.0.ns_menu_item);
+        }
+        self.release_added_item();
         item
     }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn insert_radio_item<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        checked: bool,
+        group: &RadioGroup,
+        accelerator: Option<Accelerator>,
+        position: usize,
+        menu_id: Option<MenuId>,
+    ) -> Result<RadioMenuItem, MenuError> {
+        self.check_insert(position)?;
+        let item = RadioMenuItem::new(
+            label,
+            enabled,
+            checked,
+            group,
+            sel!(fireMenubarAction:),
+            accelerator,
+            menu_id,
+        );
+        self.menu.insert_ns_menu_item(item.0.ns_menu_item, position);
+        Ok(item)
+    }
+
+    pub fn remove(&mut self, child: MenuChild) -> Result<usize, MenuError> {
+        self.check_removed()?;
+        self.menu.remove(child)
+    }
+
+    pub fn remove_at(&mut self, position: usize) -> Result<(), MenuError> {
+        self.check_removed()?;
+        self.menu.remove_at(position)
+    }
+
+    pub fn move_item(&mut self, child: MenuChild, position: usize) -> Result<usize, MenuError> {
+        self.check_removed()?;
+        self.menu.move_item(child, position)
+    }
+
+    pub fn clear(&mut self) -> Result<(), MenuError> {
+        self.check_removed()?;
+        self.menu.clear();
+        Ok(())
+    }
+
+    /// Removes the item that was just added if this submenu was removed,
+    /// so items added to a removed submenu are released like its other items.
+    fn release_added_item(&mut self) {
+        if self.check_removed().is_err() {
+            let _ = self.menu.remove_at(self.menu.len() - 1);
+        }
+    }
+
+    fn check_removed(&self) -> Result<(), MenuError> {
+        if is_released(self.menu_item.id) {
+            Err(MenuError::ItemRemoved)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_insert(&self, position: usize) -> Result<(), MenuError> {
+        self.check_removed()?;
+        check_insert_position(position, self.menu.len())
+    }
+}
+
+fn make_native_menu_item(item: NativeMenuItem) -> id {
+    let title = item.label();
+    let (_, native_menu_item) = match item {
+        NativeMenuItem::Separator => unsafe { (0, NSMenuItem::separatorItem(nil)) },
+        NativeMenuItem::About(..) => {
+            make_menu_item(&title, selector("orderFrontStandardAboutPanel:"), None)
+        }
+        NativeMenuItem::CloseWindow => make_menu_item(
+            &title,
+            selector("performClose:"),
+            Some(Accelerator::new(SysMods::Cmd, Code::KeyW)),
+        ),
+        NativeMenuItem::Quit => make_menu_item(
+            &title,
+            selector("terminate:"),
+            Some(Accelerator::new(SysMods::Cmd, Code::KeyQ)),
+        ),
+        NativeMenuItem::Hide => make_menu_item(
+            &title,
+            selector("hide:"),
+            Some(Accelerator::new(SysMods::Cmd, Code::KeyH)),
+        ),
+        NativeMenuItem::HideOthers => make_menu_item(
+            &title,
+            selector("hideOtherApplications:"),
+            Some(Accelerator::new(RawMods::Alt, Code::KeyH)),
+        ),
+        NativeMenuItem::ShowAll => make_menu_item(&title, selector("unhideAllApplications:"), None),
+        NativeMenuItem::ToggleFullScreen => make_menu_item(
+            &title,
+            selector("toggleFullScreen:"),
+            Some(Accelerator::new(RawMods::Ctrl, Code::KeyF)),
+        ),
+        NativeMenuItem::Minimize => make_menu_item(
+            &title,
+            selector("performMiniaturize:"),
+            Some(Accelerator::new(SysMods::Cmd, Code::KeyM)),
+        ),
+        NativeMenuItem::Zoom => make_menu_item(&title, selector("performZoom:"), None),
+        NativeMenuItem::Copy => make_menu_item(
+            &title,
+            selector("copy:"),
+            Some(Accelerator::new(SysMods::Cmd, Code::KeyC)),
+        ),
+        NativeMenuItem::Cut => make_menu_item(
+            &title,
+            selector("cut:"),
+            Some(Accelerator::new(SysMods::Cmd, Code::KeyX)),
+        ),
+        NativeMenuItem::Paste => make_menu_item(
+            &title,
+            selector("paste:"),
+            Some(Accelerator::new(SysMods::Cmd, Code::KeyV)),
+        ),
+        NativeMenuItem::Undo => make_menu_item(
+            &title,
+            selector("undo:"),
+            Some(Accelerator::new(SysMods::Cmd, Code::KeyZ)),
+        ),
+        NativeMenuItem::Redo => make_menu_item(
+            &title,
+            selector("redo:"),
+            Some(Accelerator::new(SysMods::CmdShift, Code::KeyZ)),
+        ),
+        NativeMenuItem::SelectAll => make_menu_item(
+            &title,
+            selector("selectAll:"),
+            Some(Accelerator::new(SysMods::Cmd, Code::KeyA)),
+        ),
+        NativeMenuItem::Services => unsafe {
+            let (_, item) = make_menu_item(&title, sel!(fireMenubarAction:), None);
+            let app_class = class!(NSApplication);
+            let app: id = msg_send![app_class, sharedApplication];
+            let services: id = msg_send![app, servicesMenu];
+            let _: () = msg_send![&*item, setSubmenu: services];
+            (0, item)
+        },
+    };
+    native_menu_item
+}
+
+impl NativeMenuItem {
+    /// The title this native menu item is displayed with.
+    pub(crate) fn label(&self) -> String {
+        match self {
+            NativeMenuItem::Separator => "Separator".into(),
+            NativeMenuItem::About(app_name, _) => format!("About {}", app_name),
+            NativeMenuItem::CloseWindow => "Close Window".into(),
+            NativeMenuItem::Quit => "Quit".into(),
+            NativeMenuItem::Hide => "Hide".into(),
+            NativeMenuItem::HideOthers => "Hide Others".into(),
+            NativeMenuItem::ShowAll => "Show All".into(),
+            NativeMenuItem::ToggleFullScreen => "Toggle Full Screen".into(),
+            NativeMenuItem::Minimize => "Minimize".into(),
+            NativeMenuItem::Zoom => "Zoom".into(),
+            NativeMenuItem::Copy => "Copy".into(),
+            NativeMenuItem::Cut => "Cut".into(),
+            NativeMenuItem::Paste => "Paste".into(),
+            NativeMenuItem::Undo => "Undo".into(),
+            NativeMenuItem::Redo => "Redo".into(),
+            NativeMenuItem::SelectAll => "Select All".into(),
+            NativeMenuItem::Services => "Services".into(),
+        }
+    }
 }
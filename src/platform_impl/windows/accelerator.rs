@@ -1,16 +1,16 @@
-use std::fmt;
-
-use keyboard_types::{Code, Modifiers};
+use keyboard_types::{Code, Key, Modifiers};
 use windows_sys::Win32::UI::{
     Input::KeyboardAndMouse::*,
     WindowsAndMessaging::{ACCEL, FALT, FCONTROL, FSHIFT, FVIRTKEY},
 };
 
-use crate::accelerator::Accelerator;
+use crate::accelerator::{
+    keys::named_key_to_code, Accelerator, AcceleratorKey, UnsupportedAcceleratorError,
+};
 
 impl Accelerator {
-    // Convert a hotkey to an accelerator.
-    pub fn to_accel(&self, menu_id: u16) -> ACCEL {
+    // Convert a hotkey to an accelerator, fails if its key has no virtual key in the current keyboard layout.
+    pub fn to_accel(&self, menu_id: u16) -> Result<ACCEL, UnsupportedAcceleratorError> {
         let mut virt_key = FVIRTKEY;
         let key_mods: Modifiers = self.mods;
         if key_mods.contains(Modifiers::CONTROL) {
@@ -22,8 +22,16 @@ impl Accelerator {
         if key_mods.contains(Modifiers::SHIFT) {
             virt_key |= FSHIFT;
         }
+        // Windows reports AltGr as Ctrl+Alt
+        if key_mods.contains(Modifiers::ALT_GRAPH) {
+            virt_key |= FCONTROL | FALT;
+        }
 
-        let vk_code = key_to_vk(&self.key);
+        let vk_code = match &*self.key() {
+            AcceleratorKey::Physical(code) => physical_key_to_vk(code),
+            AcceleratorKey::Logical(key) => logical_key_to_vk(key),
+        }
+        .ok_or_else(|| UnsupportedAcceleratorError::new(self.clone()))?;
         let mod_code = vk_code >> 8;
         if mod_code & 0x1 != 0 {
             virt_key |= FSHIFT;
@@ -36,64 +44,149 @@ impl Accelerator {
         }
         let raw_key = vk_code & 0x00ff;
 
-        ACCEL {
+        Ok(ACCEL {
             fVirt: virt_key as u8,
             key: raw_key as u16,
             cmd: menu_id,
+        })
+    }
+}
+
+// Physical keys that produce a character are mapped to the virtual key at their position
+// in the current keyboard layout, other keys have the same virtual key on every layout.
+fn physical_key_to_vk(code: &Code) -> Option<VIRTUAL_KEY> {
+    if let Some(scancode) = code_to_scancode(code) {
+        let vk = unsafe { MapVirtualKeyW(scancode, MAPVK_VSC_TO_VK) };
+        if vk != 0 {
+            return Some(vk as VIRTUAL_KEY);
         }
     }
+    key_to_vk(code)
 }
 
-// used to build accelerators table from Key
-fn key_to_vk(key: &Code) -> VIRTUAL_KEY {
+// `VkKeyScanW` returns the virtual key producing a character in the current keyboard layout,
+// along with the shift state needed, which `to_accel` adds to the modifiers.
+// Characters outside of the BMP and named keys without a virtual key can't be mapped.
+fn logical_key_to_vk(key: &Key) -> Option<VIRTUAL_KEY> {
     match key {
-        Code::KeyA => unsafe { VkKeyScanW('a' as u16) as u16 },
-        Code::KeyB => unsafe { VkKeyScanW('b' as u16) as u16 },
-        Code::KeyC => unsafe { VkKeyScanW('c' as u16) as u16 },
-        Code::KeyD => unsafe { VkKeyScanW('d' as u16) as u16 },
-        Code::KeyE => unsafe { VkKeyScanW('e' as u16) as u16 },
-        Code::KeyF => unsafe { VkKeyScanW('f' as u16) as u16 },
-        Code::KeyG => unsafe { VkKeyScanW('g' as u16) as u16 },
-        Code::KeyH => unsafe { VkKeyScanW('h' as u16) as u16 },
-        Code::KeyI => unsafe { VkKeyScanW('i' as u16) as u16 },
-        Code::KeyJ => unsafe { VkKeyScanW('j' as u16) as u16 },
-        Code::KeyK => unsafe { VkKeyScanW('k' as u16) as u16 },
-        Code::KeyL => unsafe { VkKeyScanW('l' as u16) as u16 },
-        Code::KeyM => unsafe { VkKeyScanW('m' as u16) as u16 },
-        Code::KeyN => unsafe { VkKeyScanW('n' as u16) as u16 },
-        Code::KeyO => unsafe { VkKeyScanW('o' as u16) as u16 },
-        Code::KeyP => unsafe { VkKeyScanW('p' as u16) as u16 },
-        Code::KeyQ => unsafe { VkKeyScanW('q' as u16) as u16 },
-        Code::KeyR => unsafe { VkKeyScanW('r' as u16) as u16 },
-        Code::KeyS => unsafe { VkKeyScanW('s' as u16) as u16 },
-        Code::KeyT => unsafe { VkKeyScanW('t' as u16) as u16 },
-        Code::KeyU => unsafe { VkKeyScanW('u' as u16) as u16 },
-        Code::KeyV => unsafe { VkKeyScanW('v' as u16) as u16 },
-        Code::KeyW => unsafe { VkKeyScanW('w' as u16) as u16 },
-        Code::KeyX => unsafe { VkKeyScanW('x' as u16) as u16 },
-        Code::KeyY => unsafe { VkKeyScanW('y' as u16) as u16 },
-        Code::KeyZ => unsafe { VkKeyScanW('z' as u16) as u16 },
-        Code::Digit0 => unsafe { VkKeyScanW('0' as u16) as u16 },
-        Code::Digit1 => unsafe { VkKeyScanW('1' as u16) as u16 },
-        Code::Digit2 => unsafe { VkKeyScanW('2' as u16) as u16 },
-        Code::Digit3 => unsafe { VkKeyScanW('3' as u16) as u16 },
-        Code::Digit4 => unsafe { VkKeyScanW('4' as u16) as u16 },
-        Code::Digit5 => unsafe { VkKeyScanW('5' as u16) as u16 },
-        Code::Digit6 => unsafe { VkKeyScanW('6' as u16) as u16 },
-        Code::Digit7 => unsafe { VkKeyScanW('7' as u16) as u16 },
-        Code::Digit8 => unsafe { VkKeyScanW('8' as u16) as u16 },
-        Code::Digit9 => unsafe { VkKeyScanW('9' as u16) as u16 },
+        Key::Character(s) => character_to_vk(s),
+        key => named_key_to_code(key).and_then(|code| key_to_vk(&code)),
+    }
+}
+
+fn character_to_vk(s: &str) -> Option<VIRTUAL_KEY> {
+    let mut chars = s.encode_utf16();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => char_to_vk(c),
+        _ => None,
+    }
+}
+
+// The PC/AT set 1 scancodes of the keys that produce a character.
+fn code_to_scancode(code: &Code) -> Option<u32> {
+    Some(match code {
+        Code::Digit1 => 0x02,
+        Code::Digit2 => 0x03,
+        Code::Digit3 => 0x04,
+        Code::Digit4 => 0x05,
+        Code::Digit5 => 0x06,
+        Code::Digit6 => 0x07,
+        Code::Digit7 => 0x08,
+        Code::Digit8 => 0x09,
+        Code::Digit9 => 0x0A,
+        Code::Digit0 => 0x0B,
+        Code::Minus => 0x0C,
+        Code::Equal => 0x0D,
+        Code::KeyQ => 0x10,
+        Code::KeyW => 0x11,
+        Code::KeyE => 0x12,
+        Code::KeyR => 0x13,
+        Code::KeyT => 0x14,
+        Code::KeyY => 0x15,
+        Code::KeyU => 0x16,
+        Code::KeyI => 0x17,
+        Code::KeyO => 0x18,
+        Code::KeyP => 0x19,
+        Code::BracketLeft => 0x1A,
+        Code::BracketRight => 0x1B,
+        Code::KeyA => 0x1E,
+        Code::KeyS => 0x1F,
+        Code::KeyD => 0x20,
+        Code::KeyF => 0x21,
+        Code::KeyG => 0x22,
+        Code::KeyH => 0x23,
+        Code::KeyJ => 0x24,
+        Code::KeyK => 0x25,
+        Code::KeyL => 0x26,
+        Code::Semicolon => 0x27,
+        Code::Quote => 0x28,
+        Code::Backquote => 0x29,
+        Code::Backslash => 0x2B,
+        Code::KeyZ => 0x2C,
+        Code::KeyX => 0x2D,
+        Code::KeyC => 0x2E,
+        Code::KeyV => 0x2F,
+        Code::KeyB => 0x30,
+        Code::KeyN => 0x31,
+        Code::KeyM => 0x32,
+        Code::Comma => 0x33,
+        Code::Period => 0x34,
+        Code::Slash => 0x35,
+        Code::IntlBackslash => 0x56,
+        _ => return None,
+    })
+}
+
+// used to build accelerators table from Key
+fn key_to_vk(key: &Code) -> Option<VIRTUAL_KEY> {
+    Some(match key {
+        Code::KeyA => return char_to_vk('a' as u16),
+        Code::KeyB => return char_to_vk('b' as u16),
+        Code::KeyC => return char_to_vk('c' as u16),
+        Code::KeyD => return char_to_vk('d' as u16),
+        Code::KeyE => return char_to_vk('e' as u16),
+        Code::KeyF => return char_to_vk('f' as u16),
+        Code::KeyG => return char_to_vk('g' as u16),
+        Code::KeyH => return char_to_vk('h' as u16),
+        Code::KeyI => return char_to_vk('i' as u16),
+        Code::KeyJ => return char_to_vk('j' as u16),
+        Code::KeyK => return char_to_vk('k' as u16),
+        Code::KeyL => return char_to_vk('l' as u16),
+        Code::KeyM => return char_to_vk('m' as u16),
+        Code::KeyN => return char_to_vk('n' as u16),
+        Code::KeyO => return char_to_vk('o' as u16),
+        Code::KeyP => return char_to_vk('p' as u16),
+        Code::KeyQ => return char_to_vk('q' as u16),
+        Code::KeyR => return char_to_vk('r' as u16),
+        Code::KeyS => return char_to_vk('s' as u16),
+        Code::KeyT => return char_to_vk('t' as u16),
+        Code::KeyU => return char_to_vk('u' as u16),
+        Code::KeyV => return char_to_vk('v' as u16),
+        Code::KeyW => return char_to_vk('w' as u16),
+        Code::KeyX => return char_to_vk('x' as u16),
+        Code::KeyY => return char_to_vk('y' as u16),
+        Code::KeyZ => return char_to_vk('z' as u16),
+        Code::Digit0 => return char_to_vk('0' as u16),
+        Code::Digit1 => return char_to_vk('1' as u16),
+        Code::Digit2 => return char_to_vk('2' as u16),
+        Code::Digit3 => return char_to_vk('3' as u16),
+        Code::Digit4 => return char_to_vk('4' as u16),
+        Code::Digit5 => return char_to_vk('5' as u16),
+        Code::Digit6 => return char_to_vk('6' as u16),
+        Code::Digit7 => return char_to_vk('7' as u16),
+        Code::Digit8 => return char_to_vk('8' as u16),
+        Code::Digit9 => return char_to_vk('9' as u16),
         Code::Comma => VK_OEM_COMMA,
         Code::Minus => VK_OEM_MINUS,
         Code::Period => VK_OEM_PERIOD,
-        Code::Equal => unsafe { VkKeyScanW('=' as u16) as u16 },
-        Code::Semicolon => unsafe { VkKeyScanW(';' as u16) as u16 },
-        Code::Slash => unsafe { VkKeyScanW('/' as u16) as u16 },
-        Code::Backslash => unsafe { VkKeyScanW('\\' as u16) as u16 },
-        Code::Quote => unsafe { VkKeyScanW('\'' as u16) as u16 },
-        Code::Backquote => unsafe { VkKeyScanW('`' as u16) as u16 },
-        Code::BracketLeft => unsafe { VkKeyScanW('[' as u16) as u16 },
-        Code::BracketRight => unsafe { VkKeyScanW(']' as u16) as u16 },
+        Code::Equal => return char_to_vk('=' as u16),
+        Code::Semicolon => return char_to_vk(';' as u16),
+        Code::Slash => return char_to_vk('/' as u16),
+        Code::Backslash => return char_to_vk('\\' as u16),
+        Code::Quote => return char_to_vk('\'' as u16),
+        Code::Backquote => return char_to_vk('`' as u16),
+        Code::BracketLeft => return char_to_vk('[' as u16),
+        Code::BracketRight => return char_to_vk(']' as u16),
         Code::Backspace => VK_BACK,
         Code::Tab => VK_TAB,
         Code::Space => VK_SPACE,
@@ -158,86 +251,14 @@ fn key_to_vk(key: &Code) -> VIRTUAL_KEY {
         Code::MediaPlayPause => VK_MEDIA_PLAY_PAUSE,
         Code::LaunchMail => VK_LAUNCH_MAIL,
         Code::Convert => VK_CONVERT,
-        key => panic!("Unsupported modifier: {}", key),
-    }
+        _ => return None,
+    })
 }
 
-impl fmt::Display for Accelerator {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let key_mods: Modifiers = self.mods;
-        if key_mods.contains(Modifiers::CONTROL) {
-            write!(f, "Ctrl+")?;
-        }
-        if key_mods.contains(Modifiers::SHIFT) {
-            write!(f, "Shift+")?;
-        }
-        if key_mods.contains(Modifiers::ALT) {
-            write!(f, "Alt+")?;
-        }
-        if key_mods.contains(Modifiers::SUPER) {
-            write!(f, "Windows+")?;
-        }
-        match &self.key {
-            Code::KeyA => write!(f, "A"),
-            Code::KeyB => write!(f, "B"),
-            Code::KeyC => write!(f, "C"),
-            Code::KeyD => write!(f, "D"),
-            Code::KeyE => write!(f, "E"),
-            Code::KeyF => write!(f, "F"),
-            Code::KeyG => write!(f, "G"),
-            Code::KeyH => write!(f, "H"),
-            Code::KeyI => write!(f, "I"),
-            Code::KeyJ => write!(f, "J"),
-            Code::KeyK => write!(f, "K"),
-            Code::KeyL => write!(f, "L"),
-            Code::KeyM => write!(f, "M"),
-            Code::KeyN => write!(f, "N"),
-            Code::KeyO => write!(f, "O"),
-            Code::KeyP => write!(f, "P"),
-            Code::KeyQ => write!(f, "Q"),
-            Code::KeyR => write!(f, "R"),
-            Code::KeyS => write!(f, "S"),
-            Code::KeyT => write!(f, "T"),
-            Code::KeyU => write!(f, "U"),
-            Code::KeyV => write!(f, "V"),
-            Code::KeyW => write!(f, "W"),
-            Code::KeyX => write!(f, "X"),
-            Code::KeyY => write!(f, "Y"),
-            Code::KeyZ => write!(f, "Z"),
-            Code::Digit0 => write!(f, "0"),
-            Code::Digit1 => write!(f, "1"),
-            Code::Digit2 => write!(f, "2"),
-            Code::Digit3 => write!(f, "3"),
-            Code::Digit4 => write!(f, "4"),
-            Code::Digit5 => write!(f, "5"),
-            Code::Digit6 => write!(f, "6"),
-            Code::Digit7 => write!(f, "7"),
-            Code::Digit8 => write!(f, "8"),
-            Code::Digit9 => write!(f, "9"),
-            Code::Comma => write!(f, ","),
-            Code::Minus => write!(f, "-"),
-            Code::Period => write!(f, "."),
-            Code::Space => write!(f, "Space"),
-            Code::Equal => write!(f, "="),
-            Code::Semicolon => write!(f, ";"),
-            Code::Slash => write!(f, "/"),
-            Code::Backslash => write!(f, "\\"),
-            Code::Quote => write!(f, "\'"),
-            Code::Backquote => write!(f, "`"),
-            Code::BracketLeft => write!(f, "["),
-            Code::BracketRight => write!(f, "]"),
-            Code::Tab => write!(f, "Tab"),
-            Code::Escape => write!(f, "Esc"),
-            Code::Delete => write!(f, "Del"),
-            Code::Insert => write!(f, "Ins"),
-            Code::PageUp => write!(f, "PgUp"),
-            Code::PageDown => write!(f, "PgDn"),
-            // These names match LibreOffice.
-            Code::ArrowLeft => write!(f, "Left"),
-            Code::ArrowRight => write!(f, "Right"),
-            Code::ArrowUp => write!(f, "Up"),
-            Code::ArrowDown => write!(f, "Down"),
-            _ => write!(f, "{:?}", self.key),
-        }
+// `VkKeyScanW` returns -1 when no key of the current keyboard layout produces the character.
+fn char_to_vk(c: u16) -> Option<VIRTUAL_KEY> {
+    match unsafe { VkKeyScanW(c) } {
+        -1 => None,
+        vk => Some(vk as VIRTUAL_KEY),
     }
 }
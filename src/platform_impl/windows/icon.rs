@@ -0,0 +1,146 @@
+use crate::icon::{scaled_size, Icon, IconSource, ICON_SIZE};
+use windows_sys::Win32::{
+    Graphics::Gdi::{
+        CreateCompatibleDC, CreateDIBSection, DeleteDC, DeleteObject, SelectObject, BITMAPINFO,
+        BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS, HBITMAP,
+    },
+    UI::WindowsAndMessaging::{
+        CreateIcon, CreateIconFromResourceEx, DestroyIcon, DrawIconEx, GetMenuItemInfoW,
+        SetMenuItemInfoW, DI_NORMAL, HICON, HMENU, LR_DEFAULTCOLOR, MENUITEMINFOW, MIIM_BITMAP,
+    },
+};
+
+/// Sets the bitmap of the menu item with the command id `id` within `hmenu`
+/// to `icon`, or removes it if `icon` is `None`, and deletes its previous bitmap.
+pub fn set_menu_item_icon(hmenu: HMENU, id: u32, icon: Option<&Icon>) {
+    let mut info: MENUITEMINFOW = unsafe { std::mem::zeroed() };
+    info.cbSize = std::mem::size_of::<MENUITEMINFOW>() as _;
+    info.fMask = MIIM_BITMAP;
+
+    unsafe { GetMenuItemInfoW(hmenu, id, false.into(), &mut info) };
+
+    let previous = info.hbmpItem;
+    info.hbmpItem = icon.and_then(to_hbitmap).unwrap_or(0);
+    unsafe {
+        SetMenuItemInfoW(hmenu, id, false.into(), &info);
+        if previous != 0 {
+            DeleteObject(previous);
+        }
+    }
+}
+
+/// Draws `icon` on a 32bpp bitmap with an alpha channel, which menus display with transparency.
+fn to_hbitmap(icon: &Icon) -> Option<HBITMAP> {
+    let (hicon, (width, height)) = match &icon.0 {
+        IconSource::Rgba {
+            rgba,
+            width,
+            height,
+        } => (
+            rgba_to_hicon(rgba, *width as i32, *height as i32),
+            scaled_size(*width as i32, *height as i32),
+        ),
+        IconSource::Buffer(buffer) => (buffer_to_hicon(buffer), (ICON_SIZE, ICON_SIZE)),
+    };
+    if hicon == 0 {
+        #[cfg(debug_assertions)]
+        eprintln!("Failed to decode the menu icon");
+        return None;
+    }
+
+    let mut info: BITMAPINFO = unsafe { std::mem::zeroed() };
+    info.bmiHeader.biSize = std::mem::size_of::<BITMAPINFOHEADER>() as _;
+    info.bmiHeader.biWidth = width;
+    // a negative height makes the bitmap top-down
+    info.bmiHeader.biHeight = -height;
+    info.bmiHeader.biPlanes = 1;
+    info.bmiHeader.biBitCount = 32;
+    info.bmiHeader.biCompression = BI_RGB as _;
+
+    unsafe {
+        let hdc = CreateCompatibleDC(0);
+        let mut bits = std::ptr::null_mut();
+        let hbitmap = CreateDIBSection(hdc, &info, DIB_RGB_COLORS, &mut bits, 0, 0);
+        if hbitmap != 0 {
+            let previous = SelectObject(hdc, hbitmap);
+            DrawIconEx(hdc, 0, 0, hicon, width, height, 0, 0, DI_NORMAL);
+            SelectObject(hdc, previous);
+        }
+        DeleteDC(hdc);
+        DestroyIcon(hicon);
+
+        if hbitmap == 0 {
+            None
+        } else {
+            Some(hbitmap)
+        }
+    }
+}
+
+fn rgba_to_hicon(rgba: &[u8], width: i32, height: i32) -> HICON {
+    let mut bgra = rgba.to_vec();
+    let mut and_mask = Vec::with_capacity(bgra.len() / 4);
+    for pixel in bgra.chunks_mut(4) {
+        // the mask hides the fully transparent pixels
+        and_mask.push(pixel[3].wrapping_sub(u8::MAX));
+        pixel.swap(0, 2);
+    }
+    unsafe { CreateIcon(0, width, height, 1, 32, and_mask.as_ptr(), bgra.as_ptr()) }
+}
+
+/// Decodes a PNG image or the image of an ICO file closest to [`ICON_SIZE`].
+fn buffer_to_hicon(buffer: &[u8]) -> HICON {
+    let image = if buffer.starts_with(&[0, 0, 1, 0]) {
+        match ico_image(buffer) {
+            Some(image) => image,
+            None => return 0,
+        }
+    } else {
+        buffer
+    };
+    unsafe {
+        CreateIconFromResourceEx(
+            image.as_ptr(),
+            image.len() as _,
+            true.into(),
+            0x00030000,
+            ICON_SIZE,
+            ICON_SIZE,
+            LR_DEFAULTCOLOR,
+        )
+    }
+}
+
+/// Returns the smallest image of an ICO file that is at least [`ICON_SIZE`] wide,
+/// or its largest image if they are all smaller.
+///
+/// `CreateIconFromResourceEx` takes a single image, either a PNG image or a bitmap
+/// without its file header, which is how images are stored in ICO files.
+fn ico_image(buffer: &[u8]) -> Option<&[u8]> {
+    let u16_at = |i: usize| Some(u16::from_le_bytes(buffer.get(i..i + 2)?.try_into().ok()?));
+    let u32_at = |i: usize| Some(u32::from_le_bytes(buffer.get(i..i + 4)?.try_into().ok()?));
+
+    let count = u16_at(4)? as usize;
+    let mut best: Option<(u32, usize, usize)> = None;
+    for i in 0..count {
+        let entry = 6 + i * 16;
+        // a width of 0 means 256 pixels
+        let width = match *buffer.get(entry)? {
+            0 => 256,
+            width => width as u32,
+        };
+        let len = u32_at(entry + 8)? as usize;
+        let offset = u32_at(entry + 12)? as usize;
+        let better = match best {
+            None => true,
+            Some((best_width, ..)) if best_width < ICON_SIZE as u32 => width > best_width,
+            Some((best_width, ..)) => width >= ICON_SIZE as u32 && width < best_width,
+        };
+        if better {
+            best = Some((width, offset, len));
+        }
+    }
+
+    let (_, offset, len) = best?;
+    buffer.get(offset..offset.checked_add(len)?)
+}
@@ -1,29 +1,47 @@
 #![cfg(target_os = "windows")]
 
 mod accelerator;
+mod icon;
 mod util;
 
-use crate::{accelerator::Accelerator, counter::Counter, NativeMenuItem};
+use crate::{
+    accelerator::{Accelerator, AcceleratorStyle, UnsupportedAcceleratorError},
+    check_insert_position, check_position,
+    counter::Counter,
+    icon::Icon,
+    sealed::MenuChild,
+    MenuError, MenuId, NativeMenuItem,
+};
+use icon::set_menu_item_icon;
 use once_cell::sync::Lazy;
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
-use util::{decode_wide, encode_wide, LOWORD};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+use util::{decode_wide, encode_wide, HIWORD, LOWORD};
 use windows_sys::Win32::{
-    Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+    Foundation::{HWND, LPARAM, LRESULT, POINT, WPARAM},
+    Graphics::Gdi::{ClientToScreen, DeleteObject},
     UI::{
         Input::KeyboardAndMouse::{SendInput, INPUT, INPUT_KEYBOARD, KEYEVENTF_KEYUP, VK_CONTROL},
         Shell::{DefSubclassProc, RemoveWindowSubclass, SetWindowSubclass},
         WindowsAndMessaging::{
-            AppendMenuW, CloseWindow, CreateAcceleratorTableW, CreateMenu, DrawMenuBar,
-            EnableMenuItem, GetMenuItemInfoW, MessageBoxW, PostQuitMessage, SetMenu,
-            SetMenuItemInfoW, ShowWindow, ACCEL, HACCEL, HMENU, MB_ICONINFORMATION, MENUITEMINFOW,
-            MFS_CHECKED, MFS_DISABLED, MF_CHECKED, MF_DISABLED, MF_ENABLED, MF_GRAYED, MF_POPUP,
-            MF_SEPARATOR, MF_STRING, MF_UNCHECKED, MIIM_STATE, MIIM_STRING, SW_MINIMIZE,
-            WM_COMMAND,
+            CloseWindow, CreateAcceleratorTableW, CreateMenu, DrawMenuBar, EnableMenuItem,
+            GetMenuItemCount, GetMenuItemInfoW, InsertMenuItemW, InsertMenuW, MessageBoxW,
+            PostQuitMessage, RemoveMenu, SendMessageW, SetMenu, SetMenuItemInfoW, ShowWindow,
+            TrackPopupMenu, ACCEL, HACCEL, HMENU, MB_ICONINFORMATION, MENUITEMINFOW, MFS_CHECKED,
+            MFS_DISABLED, MFT_RADIOCHECK, MFT_STRING, MF_BYPOSITION, MF_CHECKED, MF_DISABLED,
+            MF_ENABLED, MF_GRAYED, MF_POPUP, MF_SEPARATOR, MF_STRING, MF_SYSMENU, MF_UNCHECKED,
+            MIIM_BITMAP, MIIM_FTYPE, MIIM_ID, MIIM_STATE, MIIM_STRING, MIIM_SUBMENU, SW_MINIMIZE,
+            TPM_LEFTALIGN, TPM_RETURNCMD, WM_COMMAND, WM_MENUSELECT,
         },
     },
 };
 
 const COUNTER_START: u64 = 1000;
+/// Passed to `InsertMenuW` as the position to append an item.
+const APPEND: u32 = u32::MAX;
 static COUNTER: Counter = Counter::new_with_start(COUNTER_START);
 
 struct InnerMenu {
@@ -45,26 +63,58 @@ impl Menu {
     }
 
     pub fn add_submenu<S: AsRef<str>>(&mut self, label: S, enabled: bool) -> Submenu {
-        let hmenu = unsafe { CreateMenu() };
-        let mut flags = MF_POPUP;
-        if !enabled {
-            flags |= MF_GRAYED;
-        }
-        unsafe {
-            AppendMenuW(
-                self.0.borrow().hmenu,
-                flags,
-                hmenu as _,
-                encode_wide(label.as_ref()).as_ptr(),
-            )
-        };
+        let parent_hmenu = self.0.borrow().hmenu;
         Submenu {
-            hmenu,
-            parent_hmenu: self.0.borrow().hmenu,
+            hmenu: insert_submenu(parent_hmenu, label.as_ref(), enabled, APPEND),
+            parent_hmenu,
             parent_menu: self.clone(),
         }
     }
 
+    pub fn insert_submenu<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        position: usize,
+    ) -> Result<Submenu, MenuError> {
+        let parent_hmenu = self.0.borrow().hmenu;
+        check_insert_position(position, item_count(parent_hmenu))?;
+        Ok(Submenu {
+            hmenu: insert_submenu(parent_hmenu, label.as_ref(), enabled, position as _),
+            parent_hmenu,
+            parent_menu: self.clone(),
+        })
+    }
+
+    pub fn remove(&mut self, child: MenuChild) -> Result<usize, MenuError> {
+        let hmenu = self.0.borrow().hmenu;
+        let position = child_position(hmenu, &child)?;
+        remove_item(hmenu, position, self);
+        Ok(position as _)
+    }
+
+    pub fn remove_at(&mut self, position: usize) -> Result<(), MenuError> {
+        let hmenu = self.0.borrow().hmenu;
+        check_position(position, item_count(hmenu))?;
+        remove_item(hmenu, position as _, self);
+        Ok(())
+    }
+
+    pub fn move_item(&mut self, child: MenuChild, position: usize) -> Result<usize, MenuError> {
+        let hmenu = self.0.borrow().hmenu;
+        let from = child_position(hmenu, &child)?;
+        check_position(position, item_count(hmenu))?;
+        move_item(hmenu, from, position as _);
+        Ok(from as _)
+    }
+
+    pub fn clear(&mut self) {
+        let hmenu = self.0.borrow().hmenu;
+        for position in (0..item_count(hmenu)).rev() {
+            remove_item(hmenu, position as _, self);
+        }
+    }
+
     pub fn init_for_hwnd(&self, hwnd: isize) {
         unsafe {
             SetMenu(hwnd, self.0.borrow().hmenu);
@@ -107,10 +157,27 @@ impl Menu {
     }
 }
 
+/// Returns whether `accelerator` can be added to an accelerator table.
+pub(crate) fn is_accelerator_supported(accelerator: &Accelerator) -> bool {
+    accelerator.to_accel(0).is_ok()
+}
+
 const ABOUT_COUNTER_START: u64 = 400;
 static ABOUT_COUNTER: Counter = Counter::new_with_start(ABOUT_COUNTER_START);
 static mut ABOUT_MENU_ITEMS: Lazy<HashMap<u64, NativeMenuItem>> = Lazy::new(|| HashMap::new());
 static mut CHECK_MENU_ITEMS: Lazy<Vec<CheckMenuItem>> = Lazy::new(|| Vec::new());
+static mut RADIO_MENU_ITEMS: Lazy<Vec<RadioMenuItem>> = Lazy::new(|| Vec::new());
+static mut MENU_IDS: Lazy<HashMap<u64, MenuId>> = Lazy::new(|| HashMap::new());
+// win32 menus have no tooltips, they are only kept to be read back
+static mut TOOLTIPS: Lazy<HashMap<u64, String>> = Lazy::new(|| HashMap::new());
+static mut SUBMENU_TOOLTIPS: Lazy<HashMap<HMENU, String>> = Lazy::new(|| HashMap::new());
+static mut HELP_TEXTS: Lazy<HashMap<u64, String>> = Lazy::new(|| HashMap::new());
+// the accelerators the items were created with, including the ones the accelerator table can't hold
+static mut ACCELERATORS: Lazy<HashMap<u64, Accelerator>> = Lazy::new(|| HashMap::new());
+/// The custom menu item currently highlighted, see [`highlight_custom_item`].
+static mut HIGHLIGHTED_ITEM: Option<u64> = None;
+// removed submenus aren't destroyed so their handles stay valid
+static mut REMOVED_MENUS: Lazy<HashSet<HMENU>> = Lazy::new(|| HashSet::new());
 
 #[derive(Clone)]
 pub struct Submenu {
@@ -139,13 +206,15 @@ impl Submenu {
         decode_wide(info.dwTypeData)
     }
 
-    pub fn set_label<S: AsRef<str>>(&mut self, label: S) {
+    pub fn set_label<S: AsRef<str>>(&mut self, label: S) -> Result<(), MenuError> {
+        self.check_removed()?;
         let mut info: MENUITEMINFOW = unsafe { std::mem::zeroed() };
         info.cbSize = std::mem::size_of::<MENUITEMINFOW>() as _;
         info.fMask = MIIM_STRING;
         info.dwTypeData = encode_wide(label.as_ref()).as_mut_ptr();
 
         unsafe { SetMenuItemInfoW(self.parent_hmenu, self.hmenu as u32, false.into(), &info) };
+        Ok(())
     }
 
     pub fn enabled(&self) -> bool {
@@ -158,7 +227,8 @@ impl Submenu {
         (info.fState & MFS_DISABLED) == 0
     }
 
-    pub fn set_enabled(&mut self, enabled: bool) {
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<(), MenuError> {
+        self.check_removed()?;
         unsafe {
             EnableMenuItem(
                 self.parent_hmenu,
@@ -166,27 +236,82 @@ impl Submenu {
                 if enabled { MF_ENABLED } else { MF_DISABLED },
             )
         };
+        Ok(())
     }
 
-    pub fn add_submenu<S: AsRef<str>>(&mut self, label: S, enabled: bool) -> Submenu {
-        let hmenu = unsafe { CreateMenu() };
-        let mut flags = MF_POPUP;
-        if !enabled {
-            flags |= MF_GRAYED;
+    pub fn set_icon(&mut self, icon: Option<Icon>) -> Result<(), MenuError> {
+        self.check_removed()?;
+        set_menu_item_icon(self.parent_hmenu, self.hmenu as _, icon.as_ref());
+        Ok(())
+    }
+
+    pub fn tooltip(&self) -> Option<String> {
+        unsafe { SUBMENU_TOOLTIPS.get(&self.hmenu).cloned() }
+    }
+
+    pub fn set_tooltip(&mut self, tooltip: Option<&str>) -> Result<(), MenuError> {
+        self.check_removed()?;
+        unsafe {
+            match tooltip {
+                Some(tooltip) => SUBMENU_TOOLTIPS.insert(self.hmenu, tooltip.to_string()),
+                None => SUBMENU_TOOLTIPS.remove(&self.hmenu),
+            };
         }
+        Ok(())
+    }
+
+    pub fn show_context_menu_for_hwnd(&self, hwnd: isize, x: f64, y: f64) {
+        let mut point = POINT {
+            x: x as _,
+            y: y as _,
+        };
         unsafe {
-            AppendMenuW(
+            ClientToScreen(hwnd, &mut point);
+            // the chosen item is returned instead of being posted as `WM_COMMAND`
+            // so a dismissed menu can be told apart
+            SetWindowSubclass(hwnd, Some(menu_subclass_proc), MENU_SUBCLASS_ID, 0);
+            let id = TrackPopupMenu(
                 self.hmenu,
-                flags,
-                hmenu as _,
-                encode_wide(label.as_ref()).as_ptr(),
-            )
+                TPM_LEFTALIGN | TPM_RETURNCMD,
+                point.x,
+                point.y,
+                0,
+                hwnd,
+                std::ptr::null(),
+            );
+            if id != 0 {
+                SendMessageW(hwnd, WM_COMMAND, id as _, 0);
+            }
+            let _ = crate::CONTEXT_MENU_CHANNEL
+                .0
+                .send(crate::ContextMenuClosedEvent {
+                    item_activated: id != 0,
+                });
+        }
+    }
+
+    pub fn add_submenu<S: AsRef<str>>(&mut self, label: S, enabled: bool) -> Submenu {
+        let submenu = Submenu {
+            hmenu: insert_submenu(self.hmenu, label.as_ref(), enabled, APPEND),
+            parent_hmenu: self.hmenu,
+            parent_menu: self.parent_menu.clone(),
         };
-        Submenu {
-            hmenu,
+        self.release_added_item();
+        submenu
+    }
+
+    pub fn insert_submenu<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        position: usize,
+    ) -> Result<Submenu, MenuError> {
+        self.check_insert(position)?;
+        Ok(Submenu {
+            hmenu: insert_submenu(self.hmenu, label.as_ref(), enabled, position as _),
             parent_hmenu: self.hmenu,
             parent_menu: self.parent_menu.clone(),
-        }
+        })
     }
 
     pub fn add_item<S: AsRef<str>>(
@@ -194,17 +319,56 @@ impl Submenu {
         label: S,
         enabled: bool,
         accelerator: Option<Accelerator>,
+        menu_id: Option<MenuId>,
+    ) -> MenuItem {
+        let item = self.insert_item_at(label, enabled, accelerator, menu_id, APPEND);
+        self.release_added_item();
+        item
+    }
+
+    pub fn insert_item<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        accelerator: Option<Accelerator>,
+        position: usize,
+        menu_id: Option<MenuId>,
+    ) -> Result<MenuItem, MenuError> {
+        self.check_insert(position)?;
+        Ok(self.insert_item_at(label, enabled, accelerator, menu_id, position as _))
+    }
+
+    fn insert_item_at<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        accelerator: Option<Accelerator>,
+        menu_id: Option<MenuId>,
+        position: u32,
     ) -> MenuItem {
         let id = COUNTER.next();
+        let menu_id = menu_id.unwrap_or_else(|| MenuId::generated(id));
+        unsafe { MENU_IDS.insert(id, menu_id.clone()) };
         let mut flags = MF_STRING;
         if !enabled {
             flags |= MF_GRAYED;
         }
 
         let mut label = label.as_ref().to_string();
-        if let Some(accelerator) = accelerator {
-            let accel_str = accelerator.to_string();
-            let accel = accelerator.to_accel(id as u16);
+        if let Some(accelerator) = &accelerator {
+            unsafe { ACCELERATORS.insert(id, accelerator.clone()) };
+        }
+        let accelerator =
+            accelerator.and_then(|accelerator| match accelerator.to_accel(id as u16) {
+                Ok(accel) => Some((accelerator, accel)),
+                Err(_e) => {
+                    #[cfg(debug_assertions)]
+                    eprintln!("{}", _e);
+                    None
+                }
+            });
+        if let Some((accelerator, accel)) = accelerator {
+            let accel_str = accelerator.to_display_string(AcceleratorStyle::Windows);
 
             label.push_str("\t");
             label.push_str(&accel_str);
@@ -215,46 +379,80 @@ impl Submenu {
             self.parent_menu.update_haccel();
         }
 
-        unsafe { AppendMenuW(self.hmenu, flags, id as _, encode_wide(label).as_ptr()) };
+        unsafe {
+            InsertMenuW(
+                self.hmenu,
+                position,
+                MF_BYPOSITION | flags,
+                id as _,
+                encode_wide(label).as_ptr(),
+            )
+        };
         MenuItem {
             id,
+            menu_id,
             parent_hmenu: self.hmenu,
         }
     }
 
-    pub fn add_native_item(&mut self, item: NativeMenuItem) {
-        let (label, flags) = match item {
-            NativeMenuItem::Copy => ("&Copy\tCtrl+C", MF_STRING),
-            NativeMenuItem::Cut => ("Cu&t\tCtrl+X", MF_STRING),
-            NativeMenuItem::Paste => ("&Paste\tCtrl+V", MF_STRING),
-            NativeMenuItem::SelectAll => ("Select&All", MF_STRING),
-            NativeMenuItem::Separator => ("", MF_SEPARATOR),
-            NativeMenuItem::Minimize => ("&Minimize", MF_STRING),
-            NativeMenuItem::CloseWindow => ("Close", MF_STRING),
-            NativeMenuItem::Quit => ("Exit", MF_STRING),
-            NativeMenuItem::About(ref app_name, _) => {
+    /// Returns whether the item was added, [`NativeMenuItem`]s unsupported on Windows aren't.
+    pub fn add_native_item(&mut self, item: NativeMenuItem) -> bool {
+        let added = self.insert_native_item_at(item, APPEND);
+        if added {
+            self.release_added_item();
+        }
+        added
+    }
+
+    pub fn insert_native_item(
+        &mut self,
+        item: NativeMenuItem,
+        position: usize,
+    ) -> Result<bool, MenuError> {
+        self.check_insert(position)?;
+        Ok(self.insert_native_item_at(item, position as _))
+    }
+
+    fn insert_native_item_at(&mut self, item: NativeMenuItem, position: u32) -> bool {
+        let (accelerator, flags) = match item {
+            NativeMenuItem::Copy => (Some("Ctrl+C"), MF_STRING),
+            NativeMenuItem::Cut => (Some("Ctrl+X"), MF_STRING),
+            NativeMenuItem::Paste => (Some("Ctrl+V"), MF_STRING),
+            NativeMenuItem::SelectAll => (None, MF_STRING),
+            NativeMenuItem::Separator => (None, MF_SEPARATOR),
+            NativeMenuItem::Minimize => (None, MF_STRING),
+            NativeMenuItem::CloseWindow => (None, MF_STRING),
+            NativeMenuItem::Quit => (None, MF_STRING),
+            NativeMenuItem::About(..) => {
                 let id = ABOUT_COUNTER.next();
                 unsafe {
-                    AppendMenuW(
+                    InsertMenuW(
                         self.hmenu,
-                        MF_STRING,
+                        position,
+                        MF_BYPOSITION | MF_STRING,
                         id as _,
-                        encode_wide(format!("About {}", app_name)).as_ptr(),
+                        encode_wide(item.label()).as_ptr(),
                     );
                     ABOUT_MENU_ITEMS.insert(id, item);
                 }
-                return;
+                return true;
             }
-            _ => return,
+            _ => return false,
+        };
+        let label = match accelerator {
+            Some(accelerator) => format!("{}\t{}", item.label(), accelerator),
+            None => item.label(),
         };
         unsafe {
-            AppendMenuW(
+            InsertMenuW(
                 self.hmenu,
-                flags,
+                position,
+                MF_BYPOSITION | flags,
                 item.id() as _,
                 encode_wide(label).as_ptr(),
             )
         };
+        true
     }
 
     pub fn add_check_item<S: AsRef<str>>(
@@ -263,17 +461,276 @@ impl Submenu {
         enabled: bool,
         checked: bool,
         accelerator: Option<Accelerator>,
+        menu_id: Option<MenuId>,
     ) -> CheckMenuItem {
-        let mut item = CheckMenuItem(self.add_item(label, enabled, accelerator));
-        item.set_checked(checked);
-        unsafe { CHECK_MENU_ITEMS.push(item.clone()) };
+        let item = self.insert_item_at(label, enabled, accelerator, menu_id, APPEND);
+        let item = make_check_item(item, checked);
+        self.release_added_item();
         item
     }
+
+    pub fn insert_check_item<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        checked: bool,
+        accelerator: Option<Accelerator>,
+        position: usize,
+        menu_id: Option<MenuId>,
+    ) -> Result<CheckMenuItem, MenuError> {
+        let item = self.insert_item(label, enabled, accelerator, position, menu_id)?;
+        Ok(make_check_item(item, checked))
+    }
+
+    pub fn add_radio_item<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        checked: bool,
+        group: &RadioGroup,
+        accelerator: Option<Accelerator>,
+        menu_id: Option<MenuId>,
+    ) -> RadioMenuItem {
+        let item = self.insert_item_at(label, enabled, accelerator, menu_id, APPEND);
+        let item = make_radio_item(item, checked, group);
+        self.release_added_item();
+        item
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn insert_radio_item<S: AsRef<str>>(
+        &mut self,
+        label: S,
+        enabled: bool,
+        checked: bool,
+        group: &RadioGroup,
+        accelerator: Option<Accelerator>,
+        position: usize,
+        menu_id: Option<MenuId>,
+    ) -> Result<RadioMenuItem, MenuError> {
+        let item = self.insert_item(label, enabled, accelerator, position, menu_id)?;
+        Ok(make_radio_item(item, checked, group))
+    }
+
+    pub fn remove(&mut self, child: MenuChild) -> Result<usize, MenuError> {
+        self.check_removed()?;
+        let position = child_position(self.hmenu, &child)?;
+        remove_item(self.hmenu, position, &mut self.parent_menu);
+        Ok(position as _)
+    }
+
+    pub fn remove_at(&mut self, position: usize) -> Result<(), MenuError> {
+        self.check_removed()?;
+        check_position(position, item_count(self.hmenu))?;
+        remove_item(self.hmenu, position as _, &mut self.parent_menu);
+        Ok(())
+    }
+
+    pub fn move_item(&mut self, child: MenuChild, position: usize) -> Result<usize, MenuError> {
+        self.check_removed()?;
+        let from = child_position(self.hmenu, &child)?;
+        check_position(position, item_count(self.hmenu))?;
+        move_item(self.hmenu, from, position as _);
+        Ok(from as _)
+    }
+
+    pub fn clear(&mut self) -> Result<(), MenuError> {
+        self.check_removed()?;
+        for position in (0..item_count(self.hmenu)).rev() {
+            remove_item(self.hmenu, position as _, &mut self.parent_menu);
+        }
+        Ok(())
+    }
+
+    /// Removes the item that was just appended if this submenu was removed,
+    /// so items added to a removed submenu don't leave their accelerators registered.
+    fn release_added_item(&mut self) {
+        if self.check_removed().is_err() {
+            let position = item_count(self.hmenu) - 1;
+            remove_item(self.hmenu, position as _, &mut self.parent_menu);
+        }
+    }
+
+    fn check_removed(&self) -> Result<(), MenuError> {
+        if unsafe { REMOVED_MENUS.contains(&self.hmenu) } {
+            Err(MenuError::ItemRemoved)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_insert(&self, position: usize) -> Result<(), MenuError> {
+        self.check_removed()?;
+        check_insert_position(position, item_count(self.hmenu))
+    }
+}
+
+fn make_check_item(item: MenuItem, checked: bool) -> CheckMenuItem {
+    let mut item = CheckMenuItem(item);
+    let _ = item.set_checked(checked);
+    unsafe { CHECK_MENU_ITEMS.push(item.clone()) };
+    item
+}
+
+fn make_radio_item(item: MenuItem, checked: bool, group: &RadioGroup) -> RadioMenuItem {
+    let mut info: MENUITEMINFOW = unsafe { std::mem::zeroed() };
+    info.cbSize = std::mem::size_of::<MENUITEMINFOW>() as _;
+    info.fMask = MIIM_FTYPE;
+    info.fType = MFT_STRING | MFT_RADIOCHECK;
+
+    unsafe { SetMenuItemInfoW(item.parent_hmenu, item.id as _, false.into(), &info) };
+
+    let no_checked = group.0.borrow().checked.is_none();
+    group.0.borrow_mut().items.push(item.clone());
+    if checked || no_checked {
+        group.check(item.id);
+    }
+    let item = RadioMenuItem(item, group.clone());
+    unsafe { RADIO_MENU_ITEMS.push(item.clone()) };
+    item
+}
+
+fn insert_submenu(parent_hmenu: HMENU, label: &str, enabled: bool, position: u32) -> HMENU {
+    let hmenu = unsafe { CreateMenu() };
+    let mut flags = MF_POPUP;
+    if !enabled {
+        flags |= MF_GRAYED;
+    }
+    unsafe {
+        InsertMenuW(
+            parent_hmenu,
+            position,
+            MF_BYPOSITION | flags,
+            hmenu as _,
+            encode_wide(label).as_ptr(),
+        )
+    };
+    hmenu
+}
+
+fn item_count(hmenu: HMENU) -> usize {
+    unsafe { GetMenuItemCount(hmenu) }.max(0) as usize
+}
+
+/// Returns the position of `child` within `hmenu`.
+fn child_position(hmenu: HMENU, child: &MenuChild) -> Result<u32, MenuError> {
+    let (id, is_submenu, removed) = match child {
+        MenuChild::Submenu(submenu) => (submenu.hmenu as u64, true, unsafe {
+            REMOVED_MENUS.contains(&submenu.hmenu)
+        }),
+        MenuChild::MenuItem(item) => (item.id, false, unsafe { !MENU_IDS.contains_key(&item.id) }),
+        MenuChild::CheckMenuItem(item) => (item.0.id, false, unsafe {
+            !MENU_IDS.contains_key(&item.0.id)
+        }),
+        MenuChild::RadioMenuItem(item) => (item.0.id, false, unsafe {
+            !MENU_IDS.contains_key(&item.0.id)
+        }),
+    };
+    if removed {
+        return Err(MenuError::ItemRemoved);
+    }
+
+    for i in 0..item_count(hmenu) {
+        let mut info: MENUITEMINFOW = unsafe { std::mem::zeroed() };
+        info.cbSize = std::mem::size_of::<MENUITEMINFOW>() as _;
+        info.fMask = MIIM_ID | MIIM_SUBMENU;
+
+        unsafe { GetMenuItemInfoW(hmenu, i as _, true.into(), &mut info) };
+
+        let found = if is_submenu {
+            info.hSubMenu as u64 == id
+        } else {
+            info.hSubMenu == 0 && info.wID as u64 == id
+        };
+        if found {
+            return Ok(i as _);
+        }
+    }
+    Err(MenuError::NotAChild)
+}
+
+/// Removes the item at `position` from `hmenu` and forgets the ids and accelerators
+/// of the item, or of all the items within it if it is a submenu.
+fn remove_item(hmenu: HMENU, position: u32, menu: &mut Menu) {
+    let mut info: MENUITEMINFOW = unsafe { std::mem::zeroed() };
+    info.cbSize = std::mem::size_of::<MENUITEMINFOW>() as _;
+    info.fMask = MIIM_BITMAP | MIIM_ID | MIIM_SUBMENU;
+
+    unsafe { GetMenuItemInfoW(hmenu, position, true.into(), &mut info) };
+
+    let mut ids = Vec::new();
+    release_item(&info, &mut ids);
+    unsafe { RemoveMenu(hmenu, position, MF_BYPOSITION) };
+
+    {
+        let mut inner = menu.0.borrow_mut();
+        inner
+            .accelerators
+            .retain(|accel| !ids.contains(&(accel.cmd as u64)));
+    }
+    menu.update_haccel();
+}
+
+/// Forgets the item described by `info`, or all the items within it if it is a submenu,
+/// deletes their icons and collects their ids in `ids`.
+fn release_item(info: &MENUITEMINFOW, ids: &mut Vec<u64>) {
+    if info.hbmpItem != 0 {
+        unsafe { DeleteObject(info.hbmpItem) };
+    }
+    if info.hSubMenu != 0 {
+        unsafe { REMOVED_MENUS.insert(info.hSubMenu) };
+        for i in 0..item_count(info.hSubMenu) {
+            let mut child: MENUITEMINFOW = unsafe { std::mem::zeroed() };
+            child.cbSize = std::mem::size_of::<MENUITEMINFOW>() as _;
+            child.fMask = MIIM_BITMAP | MIIM_ID | MIIM_SUBMENU;
+
+            unsafe { GetMenuItemInfoW(info.hSubMenu, i as _, true.into(), &mut child) };
+
+            release_item(&child, ids);
+        }
+        return;
+    }
+
+    let id = info.wID as u64;
+    unsafe {
+        MENU_IDS.remove(&id);
+        TOOLTIPS.remove(&id);
+        HELP_TEXTS.remove(&id);
+        ACCELERATORS.remove(&id);
+        ABOUT_MENU_ITEMS.remove(&id);
+        CHECK_MENU_ITEMS.retain(|item| item.0.id != id);
+        if let Some(item) = RADIO_MENU_ITEMS.iter().find(|item| item.0.id == id) {
+            item.1.remove(id);
+        }
+        RADIO_MENU_ITEMS.retain(|item| item.0.id != id);
+    }
+    ids.push(id);
+}
+
+/// Moves the item at `from` within `hmenu` to `to`.
+fn move_item(hmenu: HMENU, from: u32, to: u32) {
+    let mut info: MENUITEMINFOW = unsafe { std::mem::zeroed() };
+    info.cbSize = std::mem::size_of::<MENUITEMINFOW>() as _;
+    info.fMask = MIIM_BITMAP | MIIM_FTYPE | MIIM_ID | MIIM_STATE | MIIM_STRING | MIIM_SUBMENU;
+
+    unsafe { GetMenuItemInfoW(hmenu, from, true.into(), &mut info) };
+
+    let mut label = vec![0u16; info.cch as usize + 1];
+    info.cch += 1;
+    info.dwTypeData = label.as_mut_ptr();
+
+    unsafe {
+        GetMenuItemInfoW(hmenu, from, true.into(), &mut info);
+        // `RemoveMenu` doesn't destroy the submenu of the item, unlike `DeleteMenu`
+        RemoveMenu(hmenu, from, MF_BYPOSITION);
+        InsertMenuItemW(hmenu, to, true.into(), &info);
+    }
 }
 
 #[derive(Clone)]
 pub struct MenuItem {
     id: u64,
+    menu_id: MenuId,
     parent_hmenu: HMENU,
 }
 
@@ -304,7 +761,8 @@ impl MenuItem {
         decode_wide(info.dwTypeData)
     }
 
-    pub fn set_label<S: AsRef<str>>(&mut self, label: S) {
+    pub fn set_label<S: AsRef<str>>(&mut self, label: S) -> Result<(), MenuError> {
+        self.check_removed()?;
         let mut label = label.as_ref().to_string();
         let prev_label = self.label_with_accel();
         if let Some(accel_str) = prev_label.split("\t").nth(1) {
@@ -318,6 +776,7 @@ impl MenuItem {
         info.dwTypeData = encode_wide(label).as_mut_ptr();
 
         unsafe { SetMenuItemInfoW(self.parent_hmenu, self.id as u32, false.into(), &info) };
+        Ok(())
     }
 
     pub fn enabled(&self) -> bool {
@@ -330,7 +789,8 @@ impl MenuItem {
         (info.fState & MFS_DISABLED) == 0
     }
 
-    pub fn set_enabled(&mut self, enabled: bool) {
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<(), MenuError> {
+        self.check_removed()?;
         unsafe {
             EnableMenuItem(
                 self.parent_hmenu,
@@ -338,10 +798,67 @@ impl MenuItem {
                 if enabled { MF_ENABLED } else { MF_DISABLED },
             )
         };
+        Ok(())
+    }
+
+    pub fn set_icon(&mut self, icon: Option<Icon>) -> Result<(), MenuError> {
+        self.check_removed()?;
+        set_menu_item_icon(self.parent_hmenu, self.id as _, icon.as_ref());
+        Ok(())
+    }
+
+    pub fn tooltip(&self) -> Option<String> {
+        unsafe { TOOLTIPS.get(&self.id).cloned() }
+    }
+
+    pub fn set_tooltip(&mut self, tooltip: Option<&str>) -> Result<(), MenuError> {
+        self.check_removed()?;
+        unsafe {
+            match tooltip {
+                Some(tooltip) => TOOLTIPS.insert(self.id, tooltip.to_string()),
+                None => TOOLTIPS.remove(&self.id),
+            };
+        }
+        Ok(())
+    }
+
+    pub fn help_text(&self) -> Option<String> {
+        unsafe { HELP_TEXTS.get(&self.id).cloned() }
+    }
+
+    pub fn set_help_text(&mut self, help_text: Option<&str>) -> Result<(), MenuError> {
+        self.check_removed()?;
+        unsafe {
+            match help_text {
+                Some(help_text) => HELP_TEXTS.insert(self.id, help_text.to_string()),
+                None => HELP_TEXTS.remove(&self.id),
+            };
+        }
+        Ok(())
     }
 
-    pub fn id(&self) -> u64 {
-        self.id
+    pub fn id(&self) -> MenuId {
+        self.menu_id.clone()
+    }
+
+    pub fn accelerator(&self) -> Option<Accelerator> {
+        unsafe { ACCELERATORS.get(&self.id).cloned() }
+    }
+
+    pub fn check_accelerator(&self) -> Result<(), UnsupportedAcceleratorError> {
+        crate::check_accelerator(self.accelerator())
+    }
+
+    pub fn activate(&self) {
+        activate_custom_item(self.id);
+    }
+
+    fn check_removed(&self) -> Result<(), MenuError> {
+        if unsafe { MENU_IDS.contains_key(&self.id) } {
+            Ok(())
+        } else {
+            Err(MenuError::ItemRemoved)
+        }
     }
 }
 
@@ -353,7 +870,7 @@ impl CheckMenuItem {
         self.0.label()
     }
 
-    pub fn set_label<S: AsRef<str>>(&mut self, label: S) {
+    pub fn set_label<S: AsRef<str>>(&mut self, label: S) -> Result<(), MenuError> {
         self.0.set_label(label)
     }
 
@@ -361,10 +878,30 @@ impl CheckMenuItem {
         self.0.enabled()
     }
 
-    pub fn set_enabled(&mut self, enabled: bool) {
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<(), MenuError> {
         self.0.set_enabled(enabled)
     }
 
+    pub fn set_icon(&mut self, icon: Option<Icon>) -> Result<(), MenuError> {
+        self.0.set_icon(icon)
+    }
+
+    pub fn tooltip(&self) -> Option<String> {
+        self.0.tooltip()
+    }
+
+    pub fn set_tooltip(&mut self, tooltip: Option<&str>) -> Result<(), MenuError> {
+        self.0.set_tooltip(tooltip)
+    }
+
+    pub fn help_text(&self) -> Option<String> {
+        self.0.help_text()
+    }
+
+    pub fn set_help_text(&mut self, help_text: Option<&str>) -> Result<(), MenuError> {
+        self.0.set_help_text(help_text)
+    }
+
     pub fn checked(&self) -> bool {
         let mut info: MENUITEMINFOW = unsafe { std::mem::zeroed() };
         info.cbSize = std::mem::size_of::<MENUITEMINFOW>() as _;
@@ -375,7 +912,8 @@ impl CheckMenuItem {
         !((info.fState & MFS_CHECKED) == 0)
     }
 
-    pub fn set_checked(&mut self, checked: bool) {
+    pub fn set_checked(&mut self, checked: bool) -> Result<(), MenuError> {
+        self.0.check_removed()?;
         use windows_sys::Win32::UI::WindowsAndMessaging;
         unsafe {
             WindowsAndMessaging::CheckMenuItem(
@@ -384,11 +922,199 @@ impl CheckMenuItem {
                 if checked { MF_CHECKED } else { MF_UNCHECKED },
             )
         };
+        Ok(())
+    }
+
+    pub fn id(&self) -> MenuId {
+        self.0.id()
+    }
+
+    pub fn accelerator(&self) -> Option<Accelerator> {
+        self.0.accelerator()
+    }
+
+    pub fn check_accelerator(&self) -> Result<(), UnsupportedAcceleratorError> {
+        crate::check_accelerator(self.accelerator())
+    }
+
+    pub fn activate(&self) {
+        self.0.activate();
+    }
+}
+
+#[derive(Default)]
+struct InnerRadioGroup {
+    items: Vec<MenuItem>,
+    checked: Option<u64>,
+}
+
+#[derive(Clone, Default)]
+pub struct RadioGroup(Rc<RefCell<InnerRadioGroup>>);
+
+impl RadioGroup {
+    pub fn checked(&self) -> Option<RadioMenuItem> {
+        let inner = self.0.borrow();
+        let id = inner.checked?;
+        inner
+            .items
+            .iter()
+            .find(|item| item.id == id)
+            .map(|item| RadioMenuItem(item.clone(), self.clone()))
+    }
+
+    fn is_checked(&self, id: u64) -> bool {
+        self.0.borrow().checked == Some(id)
+    }
+
+    /// Checks the item with `id` and unchecks the other items,
+    /// returns whether the checked item changed.
+    fn check(&self, id: u64) -> bool {
+        use windows_sys::Win32::UI::WindowsAndMessaging;
+        let mut inner = self.0.borrow_mut();
+        if inner.checked == Some(id) {
+            return false;
+        }
+        inner.checked = Some(id);
+        for item in &inner.items {
+            unsafe {
+                WindowsAndMessaging::CheckMenuItem(
+                    item.parent_hmenu,
+                    item.id as _,
+                    if item.id == id {
+                        MF_CHECKED
+                    } else {
+                        MF_UNCHECKED
+                    },
+                )
+            };
+        }
+        true
+    }
+
+    fn remove(&self, id: u64) {
+        let mut inner = self.0.borrow_mut();
+        inner.items.retain(|item| item.id != id);
+        if inner.checked == Some(id) {
+            inner.checked = None;
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RadioMenuItem(MenuItem, RadioGroup);
+
+impl RadioMenuItem {
+    pub fn label(&self) -> String {
+        self.0.label()
+    }
+
+    pub fn set_label<S: AsRef<str>>(&mut self, label: S) -> Result<(), MenuError> {
+        self.0.set_label(label)
     }
 
-    pub fn id(&self) -> u64 {
+    pub fn enabled(&self) -> bool {
+        self.0.enabled()
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<(), MenuError> {
+        self.0.set_enabled(enabled)
+    }
+
+    pub fn set_icon(&mut self, icon: Option<Icon>) -> Result<(), MenuError> {
+        self.0.set_icon(icon)
+    }
+
+    pub fn tooltip(&self) -> Option<String> {
+        self.0.tooltip()
+    }
+
+    pub fn set_tooltip(&mut self, tooltip: Option<&str>) -> Result<(), MenuError> {
+        self.0.set_tooltip(tooltip)
+    }
+
+    pub fn help_text(&self) -> Option<String> {
+        self.0.help_text()
+    }
+
+    pub fn set_help_text(&mut self, help_text: Option<&str>) -> Result<(), MenuError> {
+        self.0.set_help_text(help_text)
+    }
+
+    pub fn checked(&self) -> bool {
+        self.1.is_checked(self.0.id)
+    }
+
+    pub fn check(&mut self) -> Result<(), MenuError> {
+        self.0.check_removed()?;
+        self.1.check(self.0.id);
+        Ok(())
+    }
+
+    pub fn group(&self) -> RadioGroup {
+        self.1.clone()
+    }
+
+    pub fn id(&self) -> MenuId {
         self.0.id()
     }
+
+    pub fn accelerator(&self) -> Option<Accelerator> {
+        self.0.accelerator()
+    }
+
+    pub fn check_accelerator(&self) -> Result<(), UnsupportedAcceleratorError> {
+        crate::check_accelerator(self.accelerator())
+    }
+
+    pub fn activate(&self) {
+        self.0.activate();
+    }
+}
+
+/// Toggles check menu items, checks radio menu items and emits the event of a custom menu item.
+fn activate_custom_item(id: u64) {
+    // Toggle check menu items
+    // TODO: check the behavior in gtk
+    if let Some(item) = unsafe { CHECK_MENU_ITEMS.iter_mut().find(|i| i.0.id == id) } {
+        let _ = item.set_checked(!item.checked());
+    }
+
+    // activating the checked radio menu item does nothing, like in gtk
+    if let Some(item) = unsafe { RADIO_MENU_ITEMS.iter().find(|i| i.0.id == id) } {
+        if !item.1.check(id) {
+            return;
+        }
+    }
+
+    let id = unsafe { MENU_IDS.get(&id).cloned() }.unwrap_or_else(|| MenuId::generated(id));
+    let _ = crate::MENU_CHANNEL.0.send(crate::MenuEvent { id });
+}
+
+/// Emits the [`crate::MenuHighlightEvent`]s of the custom menu items whose highlight changed,
+/// `id` is `None` when no custom menu item is highlighted anymore.
+fn highlight_custom_item(id: Option<u64>) {
+    let previous = unsafe { std::mem::replace(&mut HIGHLIGHTED_ITEM, id) };
+    if previous == id {
+        return;
+    }
+    if let Some(previous) = previous {
+        send_highlight_event(previous, false);
+    }
+    if let Some(id) = id {
+        send_highlight_event(id, true);
+    }
+}
+
+fn send_highlight_event(id: u64, highlighted: bool) {
+    let help_text = unsafe { HELP_TEXTS.get(&id).cloned() };
+    let id = unsafe { MENU_IDS.get(&id).cloned() }.unwrap_or_else(|| MenuId::generated(id));
+    let _ = crate::MENU_HIGHLIGHT_CHANNEL
+        .0
+        .send(crate::MenuHighlightEvent {
+            id,
+            help_text,
+            highlighted,
+        });
 }
 
 const MENU_SUBCLASS_ID: usize = 200;
@@ -402,18 +1128,23 @@ unsafe extern "system" fn menu_subclass_proc(
     _dwrefdata: usize,
 ) -> LRESULT {
     let mut ret = -1;
+    if msg == WM_MENUSELECT {
+        let id = LOWORD(wparam as _) as u64;
+        let flags = HIWORD(wparam as _) as u32;
+        // the flags are `0xFFFF` when the menu is closed, and submenus have no id
+        let is_custom_item = flags != 0xFFFF
+            && flags & (MF_POPUP | MF_SYSMENU) == 0
+            && COUNTER_START <= id
+            && id <= COUNTER.current();
+        highlight_custom_item(if is_custom_item { Some(id) } else { None });
+    }
+
     if msg == WM_COMMAND {
         let id = LOWORD(wparam as _) as u64;
 
         // Custom menu items
         if COUNTER_START <= id && id <= COUNTER.current() {
-            // Toggle check menu items
-            // TODO: check the behavior in gtk
-            if let Some(item) = CHECK_MENU_ITEMS.iter_mut().find(|i| i.id() == id) {
-                item.set_checked(!item.checked());
-            }
-
-            let _ = crate::MENU_CHANNEL.0.send(crate::MenuEvent { id });
+            activate_custom_item(id);
             ret = 0;
         };
 
@@ -539,6 +1270,21 @@ impl NativeMenuItem {
         }
     }
 
+    /// The label this native menu item is displayed with, using `&` for mnemonics.
+    pub(crate) fn label(&self) -> String {
+        match self {
+            NativeMenuItem::Copy => "&Copy".into(),
+            NativeMenuItem::Cut => "Cu&t".into(),
+            NativeMenuItem::Paste => "&Paste".into(),
+            NativeMenuItem::SelectAll => "Select&All".into(),
+            NativeMenuItem::Minimize => "&Minimize".into(),
+            NativeMenuItem::CloseWindow => "Close".into(),
+            NativeMenuItem::Quit => "Exit".into(),
+            NativeMenuItem::About(app_name, _) => format!("About {}", app_name),
+            _ => format!("{:?}", self),
+        }
+    }
+
     fn is_id_of_native(id: u64) -> bool {
         (301..=308).contains(&id) || (ABOUT_COUNTER_START <= id && id <= ABOUT_COUNTER.current())
     }
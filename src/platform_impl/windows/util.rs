@@ -11,6 +11,12 @@ pub fn LOWORD(dword: u32) -> u16 {
     (dword & 0xFFFF) as u16
 }
 
+#[cfg(target_os = "windows")]
+#[allow(non_snake_case)]
+pub fn HIWORD(dword: u32) -> u16 {
+    ((dword >> 16) & 0xFFFF) as u16
+}
+
 #[cfg(target_os = "windows")]
 pub fn decode_wide(w_str: *mut u16) -> String {
     let len = unsafe { windows_sys::Win32::Globalization::lstrlenW(w_str) } as usize;
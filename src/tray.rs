@@ -0,0 +1,120 @@
+//! Tray icons are displayed in the system tray or the panel of the desktop environment,
+//! with a [`Submenu`] as their menu.
+//!
+//! Activating a tray icon, usually by clicking it, sends a [`MenuEvent`](crate::MenuEvent) carrying the id
+//! of the [`TrayIcon`] through [`menu_event_receiver`](crate::menu_event_receiver), and the items of its menu
+//! send their events as usual, so they can be handled in the same place as the other menu items.
+//!
+//! ```no_run
+//! # use muda::{icon::Icon, tray::TrayIcon, Menu};
+//! #
+//! let mut menu = Menu::new();
+//! let mut tray_menu = menu.add_submenu("Tray", true);
+//! tray_menu.add_item("&Open", true, None);
+//! tray_menu.add_item("&Quit", true, None);
+//!
+//! let mut tray = TrayIcon::new(Some(&tray_menu)).unwrap();
+//! tray.set_icon(Some(Icon::from_name("mail-unread")));
+//! tray.set_title(Some("Mail"));
+//! tray.set_tooltip(Some("3 unread messages"));
+//!
+//! if let Ok(event) = muda::menu_event_receiver().try_recv() {
+//!     if event.id == *tray.id() {
+//!         println!("Tray icon activated");
+//!     }
+//! }
+//! ```
+//!
+//! ## Platform-specific:
+//!
+//! - **Linux**: Tray icons implement the [StatusNotifierItem](https://www.freedesktop.org/wiki/Specifications/StatusNotifierItem/)
+//!   D-Bus protocol and export their menu with the `com.canonical.dbusmenu` protocol. They must be created on the thread
+//!   running the gtk main loop, which handles their D-Bus calls. Desktop environments without a `StatusNotifierWatcher`,
+//!   like GNOME without the AppIndicator extension, don't display them.
+
+use std::fmt;
+
+use crate::{icon::Icon, platform_impl, MenuId, Submenu};
+
+/// An icon in the system tray, removed from the tray when dropped.
+pub struct TrayIcon(platform_impl::TrayIcon);
+
+impl TrayIcon {
+    /// Creates a tray icon with `menu` as its menu and adds it to the system tray.
+    ///
+    /// Fails with [`TrayIconError::NoSessionBus`] if there is no D-Bus session bus to connect to.
+    pub fn new(menu: Option<&Submenu>) -> Result<Self, TrayIconError> {
+        platform_impl::TrayIcon::new(None, menu.map(|m| &m.0)).map(Self)
+    }
+
+    /// Creates a tray icon with the specified id, see [`TrayIcon::new`].
+    pub fn with_id<I: Into<MenuId>>(id: I, menu: Option<&Submenu>) -> Result<Self, TrayIconError> {
+        platform_impl::TrayIcon::new(Some(id.into()), menu.map(|m| &m.0)).map(Self)
+    }
+
+    /// Returns the id of this tray icon, which is the id of the [`MenuEvent`](crate::MenuEvent)s
+    /// sent when it is activated.
+    pub fn id(&self) -> &MenuId {
+        self.0.id()
+    }
+
+    /// Sets the menu of this tray icon, or removes it if `menu` is `None`.
+    ///
+    /// Changes to the menu are picked up when it is about to be shown, set the same menu again
+    /// to update it while it is open.
+    pub fn set_menu(&mut self, menu: Option<&Submenu>) {
+        self.0.set_menu(menu.map(|m| &m.0))
+    }
+
+    /// Sets the icon of this tray icon, icons aren't scaled down so they can be displayed at any size.
+    pub fn set_icon(&mut self, icon: Option<Icon>) {
+        self.0.set_icon(icon)
+    }
+
+    /// Gets the title of this tray icon.
+    pub fn title(&self) -> Option<String> {
+        self.0.title()
+    }
+
+    /// Sets the title of this tray icon, which describes the application to the desktop environment.
+    pub fn set_title(&mut self, title: Option<&str>) {
+        self.0.set_title(title)
+    }
+
+    /// Gets the tooltip of this tray icon.
+    pub fn tooltip(&self) -> Option<String> {
+        self.0.tooltip()
+    }
+
+    /// Sets the tooltip displayed when hovering this tray icon.
+    pub fn set_tooltip(&mut self, tooltip: Option<&str>) {
+        self.0.set_tooltip(tooltip)
+    }
+}
+
+/// An error returned when creating a [`TrayIcon`] fails.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrayIconError {
+    /// Couldn't connect to the D-Bus session bus.
+    NoSessionBus(String),
+    /// The tray icon couldn't be exported on the session bus.
+    Export(String),
+}
+
+impl fmt::Display for TrayIconError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrayIconError::NoSessionBus(e) => write!(
+                f,
+                "[TrayIconError]: Couldn't connect to the session bus: {}",
+                e
+            ),
+            TrayIconError::Export(e) => {
+                write!(f, "[TrayIconError]: Couldn't export the tray icon: {}", e)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TrayIconError {}
@@ -0,0 +1,218 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::{
+    accelerator::{
+        conflicts::{item_path, Binding},
+        Accelerator, KeyPress,
+    },
+    MenuId, MenuItemKind, NativeMenuItem,
+};
+
+/// The items of a [`Menu`](crate::Menu) or [`Submenu`](crate::Submenu), in the same order
+/// as in the platform menu, so they can be listed and looked up the same way on every platform.
+///
+/// The trees of a menu and of all of its submenus share one index of their items by id.
+#[derive(Clone, Default)]
+pub(crate) struct MenuTree {
+    items: Rc<RefCell<Vec<Rc<MenuItemKind>>>>,
+    ids: Rc<RefCell<HashMap<MenuId, Vec<Rc<MenuItemKind>>>>>,
+}
+
+impl MenuTree {
+    /// Creates the tree of a submenu of this tree's menu, sharing its id index.
+    pub fn submenu(&self) -> Self {
+        Self {
+            items: Default::default(),
+            ids: Rc::clone(&self.ids),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.borrow().len()
+    }
+
+    pub fn items(&self) -> Vec<MenuItemKind> {
+        self.items
+            .borrow()
+            .iter()
+            .map(|item| MenuItemKind::clone(item))
+            .collect()
+    }
+
+    pub fn push(&self, item: MenuItemKind) {
+        self.insert(self.len(), item)
+    }
+
+    pub fn insert(&self, position: usize, item: MenuItemKind) {
+        let item = Rc::new(item);
+        if let Some(id) = item.id() {
+            self.ids
+                .borrow_mut()
+                .entry(id)
+                .or_default()
+                .push(Rc::clone(&item));
+        }
+        self.items.borrow_mut().insert(position, item);
+    }
+
+    /// Removes the item at `position` and unindexes it and, for a submenu, all of its items.
+    pub fn remove(&self, position: usize) {
+        let item = self.items.borrow_mut().remove(position);
+        self.unindex(&item);
+    }
+
+    pub fn move_item(&self, from: usize, to: usize) {
+        let mut items = self.items.borrow_mut();
+        let item = items.remove(from);
+        items.insert(to, item);
+    }
+
+    pub fn clear(&self) {
+        for position in (0..self.len()).rev() {
+            self.remove(position);
+        }
+    }
+
+    /// Returns the first added item with the given id in this tree or the trees sharing its index.
+    pub fn find_by_id(&self, id: &MenuId) -> Option<MenuItemKind> {
+        let ids = self.ids.borrow();
+        ids.get(id)
+            .and_then(|items| items.first())
+            .map(|item| MenuItemKind::clone(item))
+    }
+
+    /// Finds the item whose labels joined with `/`, without mnemonics, are `path`.
+    pub fn find_by_path(&self, path: &str) -> Option<MenuItemKind> {
+        self.find_by_path_in("", path)
+    }
+
+    fn find_by_path_in(&self, parent: &str, path: &str) -> Option<MenuItemKind> {
+        for item in self.items.borrow().iter() {
+            let item_path = item_path(parent, &label(item));
+            if item_path == path {
+                return Some(MenuItemKind::clone(item));
+            }
+            if let MenuItemKind::Submenu(submenu) = &**item {
+                if path.starts_with(&item_path) && path[item_path.len()..].starts_with('/') {
+                    if let Some(item) = submenu.1.find_by_path_in(&item_path, path) {
+                        return Some(item);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Finds the first enabled item for which `predicate` returns `true`,
+    /// skipping disabled submenus with all of their items.
+    pub fn find_enabled<F: Fn(&MenuItemKind) -> bool>(
+        &self,
+        predicate: &F,
+    ) -> Option<MenuItemKind> {
+        for item in self.items.borrow().iter() {
+            match &**item {
+                MenuItemKind::Submenu(submenu) if submenu.enabled() => {
+                    if let Some(item) = submenu.1.find_enabled(predicate) {
+                        return Some(item);
+                    }
+                }
+                MenuItemKind::Submenu(_) | MenuItemKind::NativeMenuItem(_) => {}
+                item if enabled(item) && predicate(item) => return Some(item.clone()),
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Finds the enabled item whose accelerator matches `key`, skipping disabled submenus.
+    pub fn find_by_accelerator(&self, key: &KeyPress) -> Option<MenuItemKind> {
+        self.find_enabled(&|item| matches!(item_accelerator(item), Some(a) if key.matches(&a)))
+    }
+
+    /// Collects the accelerators and chords of the items of this tree and of its submenus,
+    /// and its [`NativeMenuItem`]s, with their paths below `parent`.
+    pub fn collect_bindings(
+        &self,
+        parent: &str,
+        bindings: &mut Vec<Binding>,
+        native_items: &mut Vec<(String, NativeMenuItem)>,
+    ) {
+        for item in self.items.borrow().iter() {
+            let path = item_path(parent, &label(item));
+            match &**item {
+                MenuItemKind::Submenu(submenu) => {
+                    submenu.1.collect_bindings(&path, bindings, native_items);
+                    continue;
+                }
+                MenuItemKind::NativeMenuItem(native_item) => {
+                    native_items.push((path, native_item.clone()));
+                    continue;
+                }
+                _ => {}
+            }
+            if let Some(accelerator) = item_accelerator(item) {
+                bindings.push(Binding {
+                    path: path.clone(),
+                    chord: accelerator.into(),
+                    registered: true,
+                });
+            }
+            #[cfg(target_os = "linux")]
+            if let Some(chord) = item.as_menu_item().and_then(|item| item.0.chord()) {
+                bindings.push(Binding {
+                    path,
+                    chord,
+                    registered: false,
+                });
+            }
+        }
+    }
+
+    fn unindex(&self, item: &Rc<MenuItemKind>) {
+        if let Some(id) = item.id() {
+            let mut ids = self.ids.borrow_mut();
+            if let Some(items) = ids.get_mut(&id) {
+                items.retain(|i| !Rc::ptr_eq(i, item));
+                if items.is_empty() {
+                    ids.remove(&id);
+                }
+            }
+        }
+        if let MenuItemKind::Submenu(submenu) = &**item {
+            for child in submenu.1.items.borrow().iter() {
+                self.unindex(child);
+            }
+        }
+    }
+}
+
+/// The label of an item, the one it is displayed with for a [`NativeMenuItem`].
+fn label(item: &MenuItemKind) -> String {
+    match item {
+        MenuItemKind::Submenu(submenu) => submenu.label(),
+        MenuItemKind::MenuItem(item) => item.label(),
+        MenuItemKind::CheckMenuItem(item) => item.label(),
+        MenuItemKind::RadioMenuItem(item) => item.label(),
+        MenuItemKind::NativeMenuItem(item) => item.label(),
+    }
+}
+
+/// The accelerator of a menu item, `None` for submenus and [`NativeMenuItem`]s.
+pub(crate) fn item_accelerator(item: &MenuItemKind) -> Option<Accelerator> {
+    match item {
+        MenuItemKind::MenuItem(item) => item.accelerator(),
+        MenuItemKind::CheckMenuItem(item) => item.accelerator(),
+        MenuItemKind::RadioMenuItem(item) => item.accelerator(),
+        MenuItemKind::Submenu(_) | MenuItemKind::NativeMenuItem(_) => None,
+    }
+}
+
+fn enabled(item: &MenuItemKind) -> bool {
+    match item {
+        MenuItemKind::Submenu(submenu) => submenu.enabled(),
+        MenuItemKind::MenuItem(item) => item.enabled(),
+        MenuItemKind::CheckMenuItem(item) => item.enabled(),
+        MenuItemKind::RadioMenuItem(item) => item.enabled(),
+        MenuItemKind::NativeMenuItem(_) => true,
+    }
+}